@@ -1,11 +1,17 @@
+use std::sync::Arc;
+
 use actix_web::{get, HttpRequest, HttpResponse, Result, web};
-use actix_web::http::StatusCode;
+use actix_web::http::{header, StatusCode};
 use actix_web_actors::ws;
+use serde_json::Value;
 
 use core::error::HttpError;
+use derive::operation;
 use service::{CreateSession, Servers, Session};
+use service::sse::{self, ConnectStream, EventId};
 use user::models::user::User;
 
+#[operation]
 #[get("ws")]
 pub async fn join_chat_server(servers: web::Data<Servers>, req: HttpRequest, stream: web::Payload, user: User)
                               -> Result<HttpResponse> {
@@ -20,4 +26,43 @@ pub async fn join_chat_server(servers: web::Data<Servers>, req: HttpRequest, str
         }))?;
 
     ws::start(session, &req, stream)
-}
\ No newline at end of file
+}
+
+/// Streams the same notifications as the websocket as Server-Sent Events, a reconnecting client receives the ones it
+/// missed if it sends the id of the last event it got
+#[operation]
+#[get("notifications")]
+pub async fn join_notification_stream(servers: web::Data<Servers>, req: HttpRequest, user: User) -> Result<HttpResponse> {
+    let last_event_id = req.headers()
+        .get("Last-Event-ID")
+        .and_then(|id| id.to_str().ok())
+        .and_then(|id| id.trim().parse::<EventId>().ok());
+
+    let (sender, stream) = sse::channel();
+
+    servers.notification.send(ConnectStream {
+        user_id: user.id,
+        last_event_id,
+        sender,
+    })
+        .await
+        .map_err(|_| HttpResponse::build(StatusCode::INTERNAL_SERVER_ERROR).json(HttpError {
+            code: StatusCode::INTERNAL_SERVER_ERROR,
+            error_code: 1,
+            message: String::from("join_failed"),
+        }))?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .header(header::CACHE_CONTROL, "no-cache")
+        // keeps reverse proxies like nginx from buffering the events
+        .header("X-Accel-Buffering", "no")
+        .streaming(stream))
+}
+
+/// OpenAPI document of this version of the API
+#[operation]
+#[get("openapi.json")]
+pub async fn fetch_spec(spec: web::Data<Arc<Value>>) -> Result<HttpResponse> {
+    Ok(HttpResponse::Ok().json(&**spec.get_ref()))
+}
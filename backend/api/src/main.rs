@@ -8,14 +8,21 @@ use actix::prelude::*;
 use actix_cors::Cors;
 use actix_web::{App, http::header, HttpServer, middleware, web};
 use diesel::{PgConnection, r2d2};
+use serde_json::Value;
 
+use core::client_ip::ClientIpConfig;
 use core::Config;
+use core::openapi::{Access, Spec};
 use core::types::DBPool;
 use core::utils::Algorithm;
 use core::utils::Hash;
 use experiment::ExperimentServer;
 use service::{ClientServices, mail::{MailClient, MailClientMock, MailService, SendMailMessage}, NotificationServer, Servers, SessionManager};
-use user::middlewares::auth::Auth;
+use service::storage::{LocalStorage, S3Config, S3Storage, Storage};
+use slot::WaitlistServer;
+use user::middlewares::rate_limit::{Rate, RateLimit, RateLimiter, RouteGroup};
+use user::router::Router;
+use webhook::{WebhookConfig, WebhookServer};
 
 mod handlers;
 
@@ -23,6 +30,60 @@ lazy_static! {
     static ref SECRET_KEY: String = std::env::var("SECRET_KEY").expect("SECRET_KEY is not provided in env");
 }
 
+/// Routes of a version of the API, relative to its prefix. Published versions are kept as they are, breaking changes
+/// are made by new handlers registered under the next version.
+fn register_v1<R: Router>(router: &mut R) {
+    user::register(router);
+    auth::register(router);
+    experiment::register(router);
+    slot::register(router);
+    webhook::register(router);
+    audit::register(router);
+
+    router.service(handlers::fetch_spec);
+
+    router.scope("", Access::User, |router| {
+        router
+            .service(handlers::join_chat_server)
+            .service(handlers::join_notification_stream);
+    });
+}
+
+fn spec_v1() -> Value {
+    let mut spec = Spec::new();
+
+    register_v1(&mut spec);
+
+    spec.document("Testbed API", "/api/v1")
+}
+
+/// Operations that are limited per user or client ip, the ones sending mails and creating jobs are the costly ones
+fn setup_rate_limiter() -> web::Data<RateLimiter> {
+    let rate = |name: &str, default: Rate| match std::env::var(name) {
+        Ok(rate) => Rate::parse(&rate)
+            .unwrap_or_else(|| panic!("Invalid {} is provided, please give requests and period in seconds such as 10/60", name)),
+        Err(_) => default,
+    };
+
+    web::Data::new(RateLimiter::new(vec![
+        RouteGroup {
+            name: "auth",
+            rate: rate("RATE_LIMIT_AUTH", Rate::new(10, 60)),
+            operations: &["login", "reset_password", "verify_account"],
+        },
+        RouteGroup {
+            name: "mail",
+            rate: rate("RATE_LIMIT_MAIL", Rate::new(5, 900)),
+            operations: &["sign_up", "forgot_password"],
+        },
+        RouteGroup {
+            name: "jobs",
+            rate: rate("RATE_LIMIT_JOBS", Rate::new(20, 60)),
+            operations: &["run_experiment", "rerun_job"],
+        },
+    ]))
+}
+
 fn setup_database() -> DBPool {
     let conn_info = std::env::var("DATABASE_URL").expect("DATABASE_URL is not provided in env");
     let manager = r2d2::ConnectionManager::<PgConnection>::new(conn_info);
@@ -59,6 +120,27 @@ fn setup_services() -> ClientServices {
     }
 }
 
+fn setup_storage() -> Arc<dyn Storage> {
+    let backend = std::env::var("STORAGE_BACKEND").unwrap_or_else(|_| String::from("local"));
+
+    match backend.as_str() {
+        "local" => Arc::new(LocalStorage::new(
+            std::env::var("STORAGE_PATH").expect("STORAGE_PATH is not provided in env")
+        )),
+        "s3" => Arc::new(S3Storage::new(S3Config {
+            endpoint: std::env::var("S3_ENDPOINT").expect("S3_ENDPOINT is not provided in env"),
+            bucket: std::env::var("S3_BUCKET").expect("S3_BUCKET is not provided in env"),
+            region: std::env::var("S3_REGION").unwrap_or_else(|_| String::from("us-east-1")),
+            access_key: std::env::var("S3_ACCESS_KEY").expect("S3_ACCESS_KEY is not provided in env"),
+            secret_key: std::env::var("S3_SECRET_KEY").expect("S3_SECRET_KEY is not provided in env"),
+            url_expiry: std::env::var("S3_URL_EXPIRY")
+                .map(|expiry| expiry.parse::<u64>().expect("Invalid S3_URL_EXPIRY is provided, please give a positive integer"))
+                .unwrap_or(300),
+        })),
+        _ => panic!("Invalid STORAGE_BACKEND is provided, please give one of local or s3")
+    }
+}
+
 fn setup_servers() -> Servers {
     let (notification_tx, notification_rx) = channel::<Addr<NotificationServer>>();
     std::thread::Builder::new().name("notification_server".to_string()).spawn(move || {
@@ -84,11 +166,40 @@ fn setup_servers() -> Servers {
     session_rx.recv().expect("Failed to receive Servers from thread")
 }
 
-fn setup_experiment_server(pool: DBPool, notification: Addr<NotificationServer>) -> Addr<ExperimentServer> {
+fn setup_webhook_server(pool: DBPool, config: WebhookConfig) -> Addr<WebhookServer> {
+    let (tx, rx) = channel::<Addr<WebhookServer>>();
+    std::thread::Builder::new().name("webhook_server".to_string()).spawn(move || {
+        let sys = System::new("webhook_server");
+        let webhook_server = WebhookServer::new(pool, config).start();
+        tx.send(webhook_server).expect("Failed to send WebhookServer from thread");
+        sys.run()
+    }).expect("Failed to initialize thread");
+
+    rx.recv().expect("Failed to receive WebhookServer from thread")
+}
+
+fn setup_waitlist_server(pool: DBPool, notification: Addr<NotificationServer>, mail: MailService) -> Addr<WaitlistServer> {
+    let (tx, rx) = channel::<Addr<WaitlistServer>>();
+    std::thread::Builder::new().name("waitlist_server".to_string()).spawn(move || {
+        let sys = System::new("waitlist_server");
+        let waitlist_server = WaitlistServer::new(pool, notification, mail).start();
+        tx.send(waitlist_server).expect("Failed to send WaitlistServer from thread");
+        sys.run()
+    }).expect("Failed to initialize thread");
+
+    rx.recv().expect("Failed to receive WaitlistServer from thread")
+}
+
+fn setup_experiment_server(pool: DBPool, notification: Addr<NotificationServer>, webhook: Addr<WebhookServer>) -> Addr<ExperimentServer> {
     let (tx, rx) = channel::<Addr<ExperimentServer>>();
+    // in minutes, jobs of experiments without any successful job are timed out after it
+    let max_run_time = std::env::var("MAX_RUN_TIME")
+        .map(|minutes| minutes.parse::<u64>().expect("Invalid MAX_RUN_TIME is provided, please give a positive integer"))
+        .unwrap_or(120);
+
     std::thread::Builder::new().name("experiment_server".to_string()).spawn(move || {
         let sys = System::new("experiment_server");
-        let experiment_server = ExperimentServer::new(pool, notification).start();
+        let experiment_server = ExperimentServer::new(pool, notification, webhook, std::time::Duration::from_secs(max_run_time * 60)).start();
         tx.send(experiment_server).expect("Failed to send ExperimentServer from thread");
         sys.run()
     }).expect("Failed to initialize thread");
@@ -107,22 +218,45 @@ async fn main() -> std::io::Result<()> {
     // Setup database
     let pool = setup_database();
 
+    // Import templates
+    if let Ok(path) = std::env::var("TEMPLATES_PATH") {
+        let conn = pool.get().expect("Failed to get a connection for importing templates");
+        let count = experiment::import_templates(&conn, path).expect("Failed to import templates");
+        log::info!("{} templates are imported", count);
+    }
+
     // Setup services
     let client_services = setup_services();
 
+    // Setup artifact storage
+    let storage = setup_storage();
+
     // Create utils
     let hash = Hash::new(&*SECRET_KEY, Algorithm::HS256);
 
     // Setup servers
     let servers = setup_servers();
 
-    let experiment_server = setup_experiment_server(pool.clone(), servers.notification.clone());
+    let webhook_config = WebhookConfig {
+        allow_private_hosts: std::env::var("WEBHOOK_ALLOW_PRIVATE_HOSTS").map(|allow| allow == "true").unwrap_or(false),
+    };
+
+    let webhook_server = setup_webhook_server(pool.clone(), webhook_config);
 
+    let experiment_server = setup_experiment_server(pool.clone(), servers.notification.clone(), webhook_server.clone());
+
+    let waitlist_server = setup_waitlist_server(pool.clone(), servers.notification.clone(), client_services.mail.clone());
+
+
+    let spec = Arc::new(spec_v1());
+
+    let rate_limiter = setup_rate_limiter();
+
+    let trust_proxy = std::env::var("TRUST_PROXY").map(|trust| trust == "true").unwrap_or(false);
 
     let config = Arc::new(Config {
         web_app_url: std::env::var("WEB_APP_URL").expect("WEB_APP_URL is not provided in env"),
         app_url: std::env::var("APP_URL").expect("APP_URL is not provided in env"),
-        storage_path: std::env::var("STORAGE_PATH").expect("STORAGE_PATH is not provided in env"),
     });
 
     let srv = HttpServer::new(move || {
@@ -130,26 +264,38 @@ async fn main() -> std::io::Result<()> {
             .allowed_origin(std::env::var("ALLOWED_ORIGIN").expect("ALLOWED_ORIGIN is not provided in env").as_str())
             .allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
             .allowed_headers(vec![header::AUTHORIZATION, header::ACCEPT, header::CONTENT_TYPE])
+            .allowed_header("last-event-id")
             .allowed_header("enctype")
+            .expose_headers(vec![header::RETRY_AFTER])
             .max_age(60);
 
         App::new()
+            .wrap(RateLimit)
             .wrap(cors)
             .wrap(middleware::Logger::default())
+            .app_data(rate_limiter.clone())
+            .app_data(ClientIpConfig::new(trust_proxy))
             .data(experiment_server.clone())
+            .data(webhook_server.clone())
+            .data(webhook_config)
+            .data(waitlist_server.clone())
             .data(hash.clone())
             .data(pool.clone())
             .data(config.clone())
+            .data(storage.clone())
             .data(client_services.clone())
             .data(servers.clone())
-            .configure(user::register)
-            .configure(auth::register)
-            .configure(experiment::register)
-            .configure(slot::register)
+            .data(spec.clone())
+            .service(web::scope("/api/v1").configure(register_v1))
+            // unversioned routes of the clients written before versioning, the same as v1
             .service(
-                web::scope("api")
-                    .wrap(Auth)
-                    .service(handlers::join_chat_server)
+                web::scope("/api")
+                    .wrap(
+                        middleware::DefaultHeaders::new()
+                            .header("Deprecation", "true")
+                            .header(header::LINK, "</api/v1>; rel=\"successor-version\"")
+                    )
+                    .configure(register_v1)
             )
     })
         .bind(std::env::var("APP_BIND_ADDRESS").expect("APP_BIND_ADDRESS is not provided in env").as_str())?;
@@ -167,3 +313,46 @@ async fn main() -> std::io::Result<()> {
         .run()
         .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v1_routes_are_unchanged() {
+        let spec = spec_v1();
+
+        let mut routes = spec["paths"].as_object().unwrap()
+            .iter()
+            .flat_map(|(path, methods)| methods.as_object().unwrap()
+                .keys()
+                .map(move |method| format!("{} {}", method.to_uppercase(), path))
+            )
+            .collect::<Vec<String>>();
+
+        routes.sort();
+
+        assert_eq!(
+            routes,
+            include_str!("../routes_v1.txt").lines().collect::<Vec<&str>>(),
+            "routes of v1 should not change, see the versioning section of the README"
+        );
+    }
+
+    #[test]
+    fn rate_limited_operations_exist() {
+        let spec = spec_v1();
+
+        let operations = spec["paths"].as_object().unwrap()
+            .values()
+            .flat_map(|methods| methods.as_object().unwrap().values())
+            .map(|operation| operation["operationId"].as_str().unwrap())
+            .collect::<Vec<&str>>();
+
+        for group in setup_rate_limiter().groups() {
+            for operation in group.operations {
+                assert!(operations.contains(operation), "{} of the {} group is not an operation", operation, group.name);
+            }
+        }
+    }
+}
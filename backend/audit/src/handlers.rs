@@ -0,0 +1,56 @@
+use actix_web::{get, web, web::Json};
+use diesel::prelude::*;
+
+use core::db::DieselEnum;
+use core::models::audit::AuditLog;
+use core::models::filter::{DateRangeRequest, FilterDateRange};
+use core::models::paginate::{CountStarOver, Paginate, Pagination, PaginationRequest};
+use core::schema::audit_logs;
+use core::types::{DBPool, Result};
+use derive::operation;
+
+use crate::requests::AuditLogFilterRequest;
+
+/// Audit logs, the latest first, filtered by the actor or the target of the actions
+#[operation]
+#[get("logs")]
+pub async fn fetch_audit_logs(
+    pool: web::Data<DBPool>,
+    pagination: web::Query<PaginationRequest>,
+    filter: web::Query<AuditLogFilterRequest>,
+    range: web::Query<DateRangeRequest>,
+) -> Result<Json<Pagination<AuditLog>>> {
+    let conn = pool.get().unwrap();
+
+    let logs = web::block(move || {
+        let mut query = audit_logs::table
+            .into_boxed()
+            .within(audit_logs::created_at, &range);
+
+        if let Some(actor_id) = filter.actor_id {
+            query = query.filter(audit_logs::actor_id.eq(actor_id));
+        }
+
+        if let Some(action) = &filter.action {
+            query = query.filter(audit_logs::action.eq(action.value()));
+        }
+
+        if let Some(target_kind) = &filter.target_kind {
+            query = query.filter(audit_logs::target_kind.eq(target_kind.value()));
+        }
+
+        if let Some(target_id) = filter.target_id {
+            query = query.filter(audit_logs::target_id.eq(target_id));
+        }
+
+        query
+            .order_by(audit_logs::id.desc())
+            .select((audit_logs::all_columns, CountStarOver))
+            .paginate(pagination.page)
+            .per_page(pagination.per_page)
+            .load_and_count_pages::<AuditLog>(&conn)
+    })
+        .await?;
+
+    Ok(Json(logs))
+}
@@ -0,0 +1,23 @@
+use core::openapi::Access;
+use user::router::Router;
+
+mod handlers;
+mod requests;
+
+/// Audit logs are recorded by the handlers of the other crates through `core::models::audit`, this crate serves them
+/// to the admins
+pub fn register<R: Router>(router: &mut R) {
+    router.scope("/audit", Access::User, |router| {
+        router.scope("", Access::Admin, |router| {
+            router.service(handlers::fetch_audit_logs);
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn it_works() {
+        assert_eq!(2 + 2, 4);
+    }
+}
@@ -0,0 +1,14 @@
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use core::models::audit::{AuditAction, TargetKind};
+use core::types::ModelId;
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLogFilterRequest {
+    pub actor_id: Option<ModelId>,
+    pub action: Option<AuditAction>,
+    pub target_kind: Option<TargetKind>,
+    pub target_id: Option<ModelId>,
+}
@@ -6,16 +6,19 @@ use diesel::prelude::*;
 use diesel::result::Error;
 use validator::Validate;
 
+use core::client_ip::ClientIp;
 use core::Config;
 use core::db::DieselEnum;
 use core::error::{ErrorMessaging, ValidationError};
 use core::ErrorMessage as CoreErrorMessage;
+use core::models::audit::{self, AuditAction, AuditEntry, TargetKind};
 use core::models::token::{AuthToken, IdentityToken, IdentityTokenKind};
 use core::responses::{SuccessResponse, TokenResponse};
 use core::sanitized::SanitizedJson;
 use core::schema::users;
-use core::types::DBPool;
+use core::types::{DBPool, ModelId};
 use core::utils::Hash;
+use derive::operation;
 use service::ClientServices;
 use user::models::user::{User, UserStatus};
 
@@ -25,11 +28,13 @@ use crate::templates::{ForgotPasswordMailTemplate, ResetPasswordMailTemplate, Ve
 
 const TIMEOUT: i64 = 60 * 60 * 24;
 
+#[operation]
 #[post("/login")]
-pub async fn login(pool: web::Data<DBPool>, hash: web::Data<Hash>, request: SanitizedJson<LoginRequest>) -> Result<Json<TokenResponse>, Box<dyn ErrorMessaging>> {
+pub async fn login(pool: web::Data<DBPool>, hash: web::Data<Hash>, ip: ClientIp, request: SanitizedJson<LoginRequest>) -> Result<Json<TokenResponse>, Box<dyn ErrorMessaging>> {
     let conn = pool.get().unwrap();
     let hash = hash.into_inner();
     let request = request.into_inner();
+    let ip = ip.into_inner();
 
     let password = hash.sign512(&request.password);
     let user = web::block(move || -> Result<User, Box<dyn ErrorMessaging>> {
@@ -42,11 +47,40 @@ pub async fn login(pool: web::Data<DBPool>, hash: web::Data<Hash>, request: Sani
                 match user.status {
                     UserStatus::NotVerified => Err(Box::new(ErrorMessage::NotVerified)),
                     UserStatus::Banned => Err(Box::new(ErrorMessage::Banned)),
-                    UserStatus::Verified => Ok(user)
+                    UserStatus::Verified => {
+                        audit::record(&conn, AuditEntry {
+                            actor_id: Some(user.id),
+                            action: AuditAction::Login,
+                            target_kind: TargetKind::User,
+                            target_id: user.id,
+                            ip,
+                        })?;
+
+                        Ok(user)
+                    }
                 }
             }
             Err(err) => match err {
-                diesel::result::Error::NotFound => Err(Box::new(ErrorMessage::InvalidCredentialsOrUser)),
+                diesel::result::Error::NotFound => {
+                    // failed attempts are only recorded for existing users, the ones for unknown emails have no target
+                    let user_id = users::table
+                        .filter(users::email.eq(&request.email))
+                        .select(users::id)
+                        .first::<ModelId>(&conn)
+                        .optional()?;
+
+                    if let Some(user_id) = user_id {
+                        audit::record(&conn, AuditEntry {
+                            actor_id: None,
+                            action: AuditAction::FailedLogin,
+                            target_kind: TargetKind::User,
+                            target_id: user_id,
+                            ip,
+                        })?;
+                    }
+
+                    Err(Box::new(ErrorMessage::InvalidCredentialsOrUser))
+                }
                 _ => Err(Box::new(err))
             }
         }
@@ -58,12 +92,14 @@ pub async fn login(pool: web::Data<DBPool>, hash: web::Data<Hash>, request: Sani
     Ok(Json(TokenResponse { token }))
 }
 
+#[operation]
 #[post("/sign-up")]
 pub async fn sign_up(
     pool: web::Data<DBPool>,
     hash: web::Data<Hash>,
     config: web::Data<Arc<Config>>,
     client_services: web::Data<ClientServices>,
+    ip: ClientIp,
     request: SanitizedJson<SignUpRequest>,
 ) -> Result<Json<SuccessResponse>, Box<dyn ErrorMessaging>> {
     let conn = pool.get().unwrap();
@@ -76,21 +112,32 @@ pub async fn sign_up(
     let insert_model = request.as_insert_model(&hash);
 
     let user = web::block(move || -> Result<User, Box<dyn ErrorMessaging>> {
-        // check if user already exists, leaving this check to database constraints causes gaps between ids since id is a serial
-        let user_exists =  diesel::dsl::select(diesel::dsl::exists(
-                 users::table.filter(users::email.eq(insert_model.email.as_str()))
-            ))
-            .get_result(&conn)?;
-
-        if user_exists {
-            return Err(Box::new(ErrorMessage::UserExists));
-        }
-        
-        // This query can still fail due to unique constraint violation due to concurrency issues
-        diesel::insert_into(users::table)
-            .values(&insert_model)
-            .get_result::<User>(&conn)
-            .map_err(|e| e.into())
+        conn.transaction(|| {
+            // check if user already exists, leaving this check to database constraints causes gaps between ids since id is a serial
+            let user_exists =  diesel::dsl::select(diesel::dsl::exists(
+                     users::table.filter(users::email.eq(insert_model.email.as_str()))
+                ))
+                .get_result(&conn)?;
+
+            if user_exists {
+                return Err(Box::new(ErrorMessage::UserExists) as Box<dyn ErrorMessaging>);
+            }
+
+            // This query can still fail due to unique constraint violation due to concurrency issues
+            let user = diesel::insert_into(users::table)
+                .values(&insert_model)
+                .get_result::<User>(&conn)?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::SignUp,
+                target_kind: TargetKind::User,
+                target_id: user.id,
+                ip: ip.into_inner(),
+            })?;
+
+            Ok(user)
+        })
     })
         .await?;
 
@@ -113,6 +160,7 @@ pub async fn sign_up(
     Ok(Json(SuccessResponse::default()))
 }
 
+#[operation]
 #[post("/forgot-password")]
 pub async fn forgot_password(
     hash: web::Data<Hash>,
@@ -146,11 +194,13 @@ pub async fn forgot_password(
     Ok(Json(SuccessResponse::default()))
 }
 
+#[operation]
 #[put("/reset-password")]
 pub async fn reset_password(
     hash: web::Data<Hash>,
     pool: web::Data<DBPool>,
     client_services: web::Data<ClientServices>,
+    ip: ClientIp,
     request: SanitizedJson<ResetPasswordRequest>,
 ) -> Result<Json<SuccessResponse>, Box<dyn ErrorMessaging>> {
     let conn = pool.get().unwrap();
@@ -170,12 +220,22 @@ pub async fn reset_password(
     let hash = hash.sign512(request.password.as_str());
 
     let user = web::block(move || -> Result<User, Error> {
-        let user = users::table.find(reset_password_token.user_id).first::<User>(&conn)?;
-        diesel::update(&user)
-            .set(users::password.eq(hash))
-            .execute(&conn)?;
-
-        Ok(user)
+        conn.transaction(|| {
+            let user = users::table.find(reset_password_token.user_id).first::<User>(&conn)?;
+            diesel::update(&user)
+                .set(users::password.eq(hash))
+                .execute(&conn)?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::ResetPassword,
+                target_kind: TargetKind::User,
+                target_id: user.id,
+                ip: ip.into_inner(),
+            })?;
+
+            Ok(user)
+        })
     })
         .await?;
 
@@ -192,10 +252,12 @@ pub async fn reset_password(
     Ok(Json(SuccessResponse::default()))
 }
 
+#[operation]
 #[post("verify-account")]
 pub async fn verify_account(
     hash: web::Data<Hash>,
     pool: web::Data<DBPool>,
+    ip: ClientIp,
     request: SanitizedJson<VerifyAccountRequest>,
 ) -> Result<Json<SuccessResponse>, Box<dyn ErrorMessaging>> {
     let conn = pool.get().unwrap();
@@ -208,17 +270,27 @@ pub async fn verify_account(
     }
 
     web::block(move || -> Result<(), Box<dyn ErrorMessaging>> {
-        let user = users::table.find(verify_account_token.user_id).first::<User>(&conn)?;
+        conn.transaction(|| {
+            let user = users::table.find(verify_account_token.user_id).first::<User>(&conn)?;
 
-        if user.status != UserStatus::NotVerified {
-            Err(CoreErrorMessage::InvalidOperationForStatus)?;
-        }
+            if user.status != UserStatus::NotVerified {
+                Err(CoreErrorMessage::InvalidOperationForStatus)?;
+            }
+
+            diesel::update(&user)
+                .set(users::status.eq(UserStatus::Verified.value()))
+                .execute(&conn)?;
 
-        diesel::update(&user)
-            .set(users::status.eq(UserStatus::Verified.value()))
-            .execute(&conn)?;
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::VerifyAccount,
+                target_kind: TargetKind::User,
+                target_id: user.id,
+                ip: ip.into_inner(),
+            })?;
 
-        Ok(())
+            Ok(())
+        })
     })
         .await?;
 
@@ -1,22 +1,22 @@
 use actix_web::http::StatusCode;
-use actix_web::web;
 
 use core::error::{ErrorMessaging, HttpError};
+use core::openapi::Access;
+use user::router::Router;
 
 mod handlers;
 mod requests;
 mod templates;
 
-pub fn register(config: &mut web::ServiceConfig) {
-    config
-        .service(
-            web::scope("/api/auth")
-                .service(handlers::login)
-                .service(handlers::sign_up)
-                .service(handlers::forgot_password)
-                .service(handlers::reset_password)
-                .service(handlers::verify_account)
-        );
+pub fn register<R: Router>(router: &mut R) {
+    router.scope("/auth", Access::Public, |router| {
+        router
+            .service(handlers::login)
+            .service(handlers::sign_up)
+            .service(handlers::forgot_password)
+            .service(handlers::reset_password)
+            .service(handlers::verify_account);
+    });
 }
 
 #[derive(Debug)]
@@ -1,5 +1,6 @@
 use std::fmt::Debug;
 
+use schemars::JsonSchema;
 use serde::Deserialize;
 use validator::Validate;
 
@@ -10,13 +11,13 @@ use core::utils::Hash;
 use derive::Sanitize;
 use user::models::user::UserInsert;
 
-#[derive(Debug, Deserialize, Sanitize)]
+#[derive(Debug, Deserialize, Sanitize, JsonSchema)]
 pub struct LoginRequest {
     pub email: String,
     pub password: String,
 }
 
-#[derive(Debug, Deserialize, Sanitize, Validate)]
+#[derive(Debug, Deserialize, Sanitize, Validate, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SignUpRequest {
     #[validate(length(max = 122))]
@@ -41,19 +42,19 @@ impl SignUpRequest {
     }
 }
 
-#[derive(Deserialize, Sanitize)]
+#[derive(Deserialize, Sanitize, JsonSchema)]
 pub struct ForgotPasswordRequest {
     pub email: String
 }
 
-#[derive(Deserialize, Sanitize, Validate)]
+#[derive(Deserialize, Sanitize, Validate, JsonSchema)]
 pub struct ResetPasswordRequest {
     pub token: String,
     #[validate(length(min = 8, max = 128))]
     pub password: String,
 }
 
-#[derive(Deserialize, Sanitize)]
+#[derive(Deserialize, Sanitize, JsonSchema)]
 pub struct VerifyAccountRequest {
     pub token: String
 }
@@ -7,7 +7,8 @@ use actix::{Actor, Context, StreamHandler, WrapFuture};
 use actix_codec::Framed;
 use awc::error::{SendRequestError, WsClientError, WsProtocolError};
 use awc::ws::{Codec, Frame, Message};
-use awc::{BoxedSocket, Client};
+use awc::http::StatusCode;
+use awc::{BoxedSocket, Client, ClientResponse};
 use futures::stream::{SplitSink, StreamExt};
 use log::{error, info};
 
@@ -19,6 +20,20 @@ use crate::messages::{
     IsJobAborted, RunMessage, RunResultMessage, ControllerReceiversValueMessage, UpdateExecutorMessage,
 };
 
+enum UploadError {
+    Request(SendRequestError),
+    Status(StatusCode),
+}
+
+impl std::fmt::Display for UploadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UploadError::Request(e) => write!(f, "{:?}", e),
+            UploadError::Status(status) => write!(f, "backend responded with {}", status)
+        }
+    }
+}
+
 type Write = SinkWrite<Message, SplitSink<Framed<BoxedSocket, Codec>, Message>>;
 
 const MAX_TIMING: usize = 5;
@@ -92,6 +107,7 @@ impl Connection {
                             let msg = RunMessage {
                                 job_id: run_experiment.data.job_id,
                                 code: run_experiment.data.code,
+                                parameters: run_experiment.data.parameters,
                             };
                             let addr = executor.clone();
 
@@ -206,6 +222,7 @@ impl Connection {
                 data: server::RunResult {
                     job_id: msg.job_id,
                     successful: msg.successful,
+                    failure: msg.failure.clone(),
                 },
             })
             .unwrap(),
@@ -216,8 +233,10 @@ impl Connection {
         msg: RunResultMessage,
         server_url: String,
         access_token: String,
-    ) -> (RunResultMessage, Option<SendRequestError>)  {
-        let res = Client::new()
+    ) -> (RunResultMessage, Option<UploadError>)  {
+        let client = Client::new();
+
+        let res = client
             .post(format!(
                 "{}/experiment/job/{}/output?token={}",
                 server_url, msg.job_id, access_token
@@ -225,12 +244,42 @@ impl Connection {
             .send_body(&msg.output)
             .await;
 
-        let opt = match res {
-            Ok(_) => None,
-            Err(e) => Some(e)
-        };
+        if let Err(e) = Self::check_upload("output", res) {
+            return (msg, Some(e));
+        }
+
+        for artifact in &msg.artifacts {
+            let res = client
+                .post(format!(
+                    "{}/experiment/job/{}/artifact/{}?token={}",
+                    server_url, msg.job_id, artifact.name, access_token
+                ))
+                .content_type(artifact.content_type.as_str())
+                .send_body(artifact.content.clone())
+                .await;
+
+            if let Err(e) = Self::check_upload(artifact.name.as_str(), res) {
+                return (msg, Some(e));
+            }
+        }
 
-        (msg, opt)
+        (msg, None)
+    }
+
+    /// Uploads that are already done are rejected with conflict while retrying, they are not treated as error. Other
+    /// client errors are not fixed by retrying, hence they are logged and the upload is given up, while server errors
+    /// are retried.
+    fn check_upload<S>(name: &str, res: Result<ClientResponse<S>, SendRequestError>) -> Result<(), UploadError> {
+        let status = res.map_err(UploadError::Request)?.status();
+
+        if status.is_success() || status == StatusCode::CONFLICT {
+            Ok(())
+        } else if status.is_client_error() {
+            error!("upload of {} is rejected by backend with {}", name, status);
+            Ok(())
+        } else {
+            Err(UploadError::Status(status))
+        }
     }
 
     fn send_msg_to_server(&mut self, msg: RunResultMessage) -> Option<RunResultMessage> {
@@ -254,7 +303,7 @@ impl Connection {
             .into_actor(self)
             .then(|res, act, _| {
                 if let Some(e) = res.1 {
-                    error!("failed to send output to backend, {:?}", e);
+                    error!("failed to send output to backend, {}", e);
                     act.pending_messages.push(res.0);
                 } else {
                     if let Some(msg) = act.send_msg_to_server(res.0) {
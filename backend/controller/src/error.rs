@@ -1,11 +1,6 @@
 use serde::Serialize;
 
-#[derive(Serialize)]
-pub enum ErrorCause {
-    User,
-    Abort,
-    Internal
-}
+pub use shared::websocket_messages::server::FailureCause as ErrorCause;
 
 #[derive(Serialize)]
 pub struct Error {
@@ -5,18 +5,30 @@ use std::time::Duration;
 
 use actix::prelude::*;
 use log::{error, info};
+use serde_json::{Map, Value};
 use serial::core::SerialDevice;
+use shared::artifact;
+use shared::state::{self, Decoder, END_DELIMITER_NEW_LINE, START_DELIMITER_NEW_LINE, State};
+use shared::websocket_messages::server::Failure;
 
 use crate::connection::Connection;
-use crate::messages::{RunMessage, ControllerReceiversValueMessage, RunResultMessage, IsJobAborted};
+use crate::messages::{Artifact, RunMessage, ControllerReceiversValueMessage, RunResultMessage, IsJobAborted};
 use crate::ModelId;
-use crate::state::{self, Decoder, END_DELIMITER_NEW_LINE, START_DELIMITER_NEW_LINE, State};
-use crate::process::{Error as ProcessError, ErrorKind as ProcessErrorKind, DockerBuilder, DockerProcess};
+use crate::process::{Error as ProcessError, ErrorKind as ProcessErrorKind, DockerBuilder, DockerProcess, OUTPUT_DIR};
 use crate::error::{self, ErrorCause};
 
 // in seconds
 const SEND_RECEIVERS_VALUES_INTERVAL: u64 = 10;
 
+const PARAMETERS_FILE: &str = "/usr/local/scripts/parameters.json";
+const PARAMETER_ENV_PREFIX: &str = "TESTBED_PARAM_";
+
+// names of the standard output and transmitter state artifacts, files with the same names in the output dir are skipped
+const OUTPUT_ARTIFACT_NAME: &str = "output.txt";
+const STATE_ARTIFACT_NAME: &str = "state.txt";
+const MAX_ARTIFACTS: usize = 32;
+const MAX_ARTIFACTS_SIZE: u64 = 32 * 1024 * 1024;
+
 mod incoming {
     pub mod arduino {
         pub const SETUP_MESSAGE: &str = "arduino_available";
@@ -55,11 +67,34 @@ impl Executor {
         format!("/tmp/controller/{}", job_id)
     }
 
-    fn create_dir_and_files(script_dir: &str, code: String) -> Result<(), Error> {
+    fn gen_output_dir(script_dir: &str) -> String {
+        format!("{}/output", script_dir)
+    }
+
+    /// Parameters are exposed both as one env var per parameter and as a json file in the script dir.
+    fn gen_envs(parameters: &Map<String, Value>) -> Vec<String> {
+        let mut envs = vec![
+            format!("TESTBED_PARAMETERS_FILE={}", PARAMETERS_FILE),
+            format!("TESTBED_OUTPUT_DIR={}", OUTPUT_DIR),
+        ];
+
+        for (name, value) in parameters {
+            let value = match value {
+                Value::String(value) => value.clone(),
+                value => value.to_string()
+            };
+
+            envs.push(format!("{}{}={}", PARAMETER_ENV_PREFIX, name.to_uppercase(), value));
+        }
+
+        envs
+    }
+
+    fn create_dir_and_files(script_dir: &str, code: String, parameters: &Map<String, Value>) -> Result<(), Error> {
         let file = String::from(script_dir) + "/job.py";
 
-        std::fs::create_dir_all(script_dir)
-            .map_err(|e| Error::IO(e, "creating script dir"))?;
+        std::fs::create_dir_all(Self::gen_output_dir(script_dir))
+            .map_err(|e| Error::IO(e, "creating script and output dirs"))?;
 
         let mut f = std::fs::File::create(file.as_str())
             .map_err(|e| Error::IO(e, "creating script file"))?;
@@ -67,15 +102,70 @@ impl Executor {
         f.write(code.as_bytes())
             .map_err(|e| Error::IO(e, "writing script file"))?;
 
+        let mut f = std::fs::File::create(String::from(script_dir) + "/parameters.json")
+            .map_err(|e| Error::IO(e, "creating parameters file"))?;
+
+        f.write(Value::Object(parameters.clone()).to_string().as_bytes())
+            .map_err(|e| Error::IO(e, "writing parameters file"))?;
+
         Ok(())
     }
 
+    /// Reads the regular files in the output dir, skipping the ones that exceed the limits
+    fn collect_artifacts(script_dir: &str) -> Vec<Artifact> {
+        let entries = match std::fs::read_dir(Self::gen_output_dir(script_dir)) {
+            Ok(entries) => entries,
+            Err(e) => {
+                error!("failed to read output dir, {:?}", e);
+                return Vec::new();
+            }
+        };
+
+        let mut artifacts = Vec::new();
+        let mut total_size = 0;
+
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let name = entry.file_name().to_string_lossy().to_string();
+
+            let size = match entry.metadata() {
+                Ok(metadata) if metadata.is_file() => metadata.len(),
+                _ => continue
+            };
+
+            // names go into the upload url as they are, the server rejects the ones outside of its safe subset
+            if !artifact::is_valid_name(name.as_str()) {
+                error!("skipping artifact {}, name is not valid", name);
+                continue;
+            }
+
+            if name == OUTPUT_ARTIFACT_NAME || name == STATE_ARTIFACT_NAME || artifacts.len() == MAX_ARTIFACTS || total_size + size > MAX_ARTIFACTS_SIZE {
+                error!("skipping artifact {}, name is reserved or limits are exceeded", name);
+                continue;
+            }
+
+            match std::fs::read(entry.path()) {
+                Ok(content) => {
+                    total_size += size;
+
+                    artifacts.push(Artifact {
+                        content_type: String::from(content_type(name.as_str())),
+                        name,
+                        content,
+                    });
+                }
+                Err(e) => error!("failed to read artifact {}, {:?}", name, e)
+            }
+        }
+
+        artifacts
+    }
+
     fn remove_dir(script_dir: &str) -> Result<(), Error> {
         std::fs::remove_dir_all(script_dir)
             .map_err(|e| Error::IO(e, "removing script dir"))
     }
 
-    fn run_transmitter_code(&self, script_dir: &str) -> Result<String, Error> {
+    fn run_transmitter_code(&self, script_dir: &str, envs: &[String]) -> Result<String, Error> {
         let process = DockerBuilder::new(
             self.docker_path.as_str(),
             self.python_lib_path.as_str(),
@@ -83,6 +173,8 @@ impl Executor {
             &["python", "/usr/local/scripts/job.py", "--transmitter"]
         )
             .name("nrgtestbed-transmitter")
+            .envs(envs)
+            .output_dir(Self::gen_output_dir(script_dir).as_str())
             .build()
             .map_err(|e| Error::ProcessErrorKind(e))?;
 
@@ -90,7 +182,7 @@ impl Executor {
             .map_err(|e| Error::Process(e))
     }
 
-    fn start_receiver(&self, script_dir: &str) -> Result<DockerProcess, Error> {
+    fn start_receiver(&self, script_dir: &str, envs: &[String]) -> Result<DockerProcess, Error> {
         let devices = (&self.rx_dev_paths)
             .into_iter()
             .map(|dev| dev.as_str())
@@ -104,6 +196,8 @@ impl Executor {
         )
             .name("nrgtestbed-receiver")
             .devices(&devices)
+            .envs(envs)
+            .output_dir(Self::gen_output_dir(script_dir).as_str())
             .build()
             .map_err(|e| Error::ProcessErrorKind(e))
     }
@@ -229,32 +323,39 @@ impl Executor {
         Ok(())
     }
 
-    fn handle_execution(&self, job_id: ModelId, code: String) -> Result<String, Error> {
+    /// Returns the output of the receiver together with the serialized state of the transmitter
+    fn handle_execution(&self, job_id: ModelId, code: String, parameters: Map<String, Value>) -> Result<(String, String), Error> {
         info!("generating tmp dirs");
         let script_dir = Self::gen_tmp_dir(job_id);
 
         info!("creating dirs and files");
-        Self::create_dir_and_files(script_dir.as_str(), code)?;
+        Self::create_dir_and_files(script_dir.as_str(), code, &parameters)?;
+
+        let envs = Self::gen_envs(&parameters);
 
         info!("running the transmitter code");
-        let serialized_state = self.run_transmitter_code(script_dir.as_str())?;
+        let serialized_state = self.run_transmitter_code(script_dir.as_str(), &envs)?;
 
         info!("decoding the state");
-        let state = Decoder::decode(serialized_state.as_str())
-            .map_err(|e| Error::Decoding(e, serialized_state))?;
+        let state = match Decoder::decode(serialized_state.as_str()) {
+            Ok(state) => state,
+            Err(e) => return Err(Error::Decoding(e, serialized_state))
+        };
 
         info!("starting the transmitter");
         let mut port = self.start_transmitter()?;
 
         info!("starting the receiver");
-        let mut receiver = self.start_receiver(script_dir.as_str())?;
+        let mut receiver = self.start_receiver(script_dir.as_str(), &envs)?;
 
         info!("syncronizing the receiver");
         match Self::syncronize_receiver(&mut receiver) {
             Ok(()) => {},
             Err(Error::EarlyExit) => {
                 info!("receiver is exited early");
-                return receiver.wait(1).map_err(|e| Error::Process(e))
+                return receiver.wait(1)
+                    .map(|output| (output, serialized_state))
+                    .map_err(Error::Process)
             },
             Err(e) => {
                 receiver.kill()
@@ -284,7 +385,7 @@ impl Executor {
             },
             Err(Error::EarlyExit) => {
                 info!("receiver is exited early");
-                return Ok(receiver.wait(1).map_err(|e| Error::Process(e))?)
+                return Ok((receiver.wait(1).map_err(Error::Process)?, serialized_state))
             },
             Err(e) => {
                 // just kill everything without checking error and return error
@@ -295,11 +396,8 @@ impl Executor {
             }
         };
 
-        info!("removing script dir");
-        Self::remove_dir(script_dir.as_str())?;
-
         info!("returning");
-        Ok(output)
+        Ok((output, serialized_state))
     }
 }
 
@@ -394,8 +492,27 @@ impl Handler<RunMessage> for Executor {
         // lock the receiver
         let _ = self.rx_lock.lock().unwrap();
 
-        let (output, successful) = match self.handle_execution(msg.job_id, msg.code) {
-            Ok(output) => (output, true),
+        let (output, artifacts, failure) = match self.handle_execution(msg.job_id, msg.code, msg.parameters) {
+            Ok((output, serialized_state)) => {
+                let script_dir = Self::gen_tmp_dir(job_id);
+
+                info!("collecting artifacts");
+                let mut artifacts = Self::collect_artifacts(script_dir.as_str());
+
+                // kept for the analysis of the receiver output in the backend
+                artifacts.push(Artifact {
+                    name: String::from(STATE_ARTIFACT_NAME),
+                    content_type: String::from(content_type(STATE_ARTIFACT_NAME)),
+                    content: serialized_state.into_bytes(),
+                });
+
+                info!("removing script dir");
+                if let Err(e) = Self::remove_dir(script_dir.as_str()) {
+                    error!("failed to remove script dir, {:?}", e);
+                }
+
+                (output, artifacts, None)
+            }
             Err(e) => {
                 let error = e.error();
 
@@ -403,12 +520,17 @@ impl Handler<RunMessage> for Executor {
                 // just try to remove script files, even error originated from remove_script_files, we should try it.
                 let _ = Self::remove_dir(Self::gen_tmp_dir(job_id).as_str());
 
-                (serde_json::to_string(&error).unwrap(), false)
+                let failure = Failure {
+                    kind: error.kind.to_string(),
+                    cause: error.cause.clone(),
+                };
+
+                (serde_json::to_string(&error).unwrap(), Vec::new(), Some(failure))
             }
         };
 
         async move {
-            if let Err(e) = addr.send(RunResultMessage { job_id, output, successful })
+            if let Err(e) = addr.send(RunResultMessage { job_id, output, artifacts, successful: failure.is_none(), failure })
                 .await {
                 error!("could not send run result to connection, {:?}", e);
             }
@@ -460,3 +582,18 @@ impl Error {
         }
     }
 }
+
+fn content_type(name: &str) -> &'static str {
+    let extension = name.rsplit('.').next().unwrap_or_default().to_lowercase();
+
+    match extension.as_str() {
+        "txt" | "log" => "text/plain",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "svg" => "image/svg+xml",
+        "pdf" => "application/pdf",
+        _ => "application/octet-stream"
+    }
+}
@@ -11,7 +11,6 @@ mod error;
 mod executor;
 mod process;
 mod messages;
-mod state;
 
 type ModelId = i32;
 
@@ -1,4 +1,6 @@
 use actix::{Message, Recipient};
+use serde_json::{Map, Value};
+use shared::websocket_messages::server::Failure;
 
 use crate::ModelId;
 
@@ -7,6 +9,7 @@ use crate::ModelId;
 pub struct RunMessage {
     pub job_id: ModelId,
     pub code: String,
+    pub parameters: Map<String, Value>,
 }
 
 #[derive(Message)]
@@ -14,7 +17,16 @@ pub struct RunMessage {
 pub struct RunResultMessage {
     pub job_id: ModelId,
     pub output: String,
+    pub artifacts: Vec<Artifact>,
     pub successful: bool,
+    pub failure: Option<Failure>,
+}
+
+/// File written by the job into the output directory
+pub struct Artifact {
+    pub name: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
 }
 
 #[derive(Message)]
@@ -9,6 +9,7 @@ use crate::error::{self, ErrorCause};
 
 const PYTHON_VERSION: &str = "3.9";
 const ALPINE_VERSION: &str = "3.13";
+pub const OUTPUT_DIR: &str = "/usr/local/output/";
 
 mod limits {
     pub const MEMORY: &str = "512m";
@@ -23,6 +24,8 @@ pub struct DockerBuilder<'a> {
     exec: &'a [&'a str],
     name: Option<&'a str>,
     devices: Option<&'a [&'a str]>,
+    envs: Option<&'a [String]>,
+    output_dir: Option<&'a str>,
 }
 
 impl<'a> DockerBuilder<'a> {
@@ -34,6 +37,8 @@ impl<'a> DockerBuilder<'a> {
             exec,
             name: None,
             devices: None,
+            envs: None,
+            output_dir: None,
         }
     }
 
@@ -49,6 +54,20 @@ impl<'a> DockerBuilder<'a> {
         self
     }
 
+    /// Environment variables passed to the container, each in `NAME=value` form
+    pub fn envs(mut self, envs: &'a [String]) -> DockerBuilder<'a> {
+        self.envs = Some(envs);
+
+        self
+    }
+
+    /// Directory that is mounted writable into the container for the files produced by the job
+    pub fn output_dir(mut self, output_dir: &'a str) -> DockerBuilder<'a> {
+        self.output_dir = Some(output_dir);
+
+        self
+    }
+
     pub fn build(self) -> Result<DockerProcess, ErrorKind> {
         let mut command = std::process::Command::new(self.docker_path);
 
@@ -67,6 +86,16 @@ impl<'a> DockerBuilder<'a> {
             .args(&["--mount", format!("type=bind,source={},target=/usr/local/lib/python{}/site-packages/,readonly", self.python_lib_path, PYTHON_VERSION).as_str()])
             .args(&["--mount", format!("type=bind,source={},target=/usr/local/scripts/,readonly", self.script_dir).as_str()]);
 
+        if let Some(output_dir) = self.output_dir {
+            command.args(["--mount", format!("type=bind,source={},target={}", output_dir, OUTPUT_DIR).as_str()]);
+        }
+
+        if let Some(envs) = self.envs {
+            for env in envs {
+                command.args(["-e", env.as_str()]);
+            }
+        }
+
         if let Some(devices) = self.devices {
             let devices = devices
                 .into_iter()
@@ -0,0 +1,146 @@
+use std::net::SocketAddr;
+
+use actix_web::{Error, FromRequest, HttpRequest};
+use actix_web::dev::{Payload, ServiceRequest};
+use actix_web::http::HeaderMap;
+use futures::future::{ok, Ready};
+
+/// Tells whether the client ip can be taken from the headers set by a reverse proxy, given as app data
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ClientIpConfig {
+    trust_proxy: bool,
+}
+
+impl ClientIpConfig {
+    pub fn new(trust_proxy: bool) -> Self {
+        ClientIpConfig { trust_proxy }
+    }
+}
+
+/// Ip of the client, taken from the last entry of the `Forwarded` or `X-Forwarded-For` headers if the proxy is trusted,
+/// from the peer address otherwise
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientIp(pub Option<String>);
+
+impl ClientIp {
+    pub fn of(req: &HttpRequest) -> Self {
+        Self::resolve(req.app_data::<ClientIpConfig>(), req.headers(), req.peer_addr())
+    }
+
+    /// Ip of the client for the middlewares
+    pub fn of_service(req: &ServiceRequest) -> Self {
+        Self::resolve(req.app_data::<ClientIpConfig>(), req.headers(), req.peer_addr())
+    }
+
+    fn resolve(config: Option<&ClientIpConfig>, headers: &HeaderMap, peer_addr: Option<SocketAddr>) -> Self {
+        let trust_proxy = config.map(|config| config.trust_proxy).unwrap_or(false);
+        let peer_ip = peer_addr.map(|addr| addr.ip().to_string());
+
+        let ip = if trust_proxy {
+            forwarded_for(headers).or(peer_ip)
+        } else {
+            peer_ip
+        };
+
+        ClientIp(ip)
+    }
+
+    pub fn into_inner(self) -> Option<String> {
+        self.0
+    }
+}
+
+impl FromRequest for ClientIp {
+    type Error = Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+    type Config = ClientIpConfig;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ok(ClientIp::of(req))
+    }
+}
+
+/// Address the trusted proxy appended to the forwarding headers. Entries before it are sent by the client, which can
+/// write anything there, so only the last one is used. The header map does not keep the order of a header repeated on
+/// several lines, so such headers are not used at all.
+fn forwarded_for(headers: &HeaderMap) -> Option<String> {
+    let forwarded = single_value(headers, "forwarded")
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|element| {
+            element.split(';')
+                .find_map(|pair| {
+                    let mut pair = pair.splitn(2, '=');
+                    let name = pair.next()?.trim();
+                    let value = pair.next()?.trim();
+
+                    if name.eq_ignore_ascii_case("for") {
+                        Some(value.trim_matches('"'))
+                    } else {
+                        None
+                    }
+                })
+        });
+
+    let entry = forwarded.or_else(|| {
+        single_value(headers, "x-forwarded-for")
+            .and_then(|value| value.rsplit(',').next())
+    })?;
+
+    Some(without_port(entry.trim())).filter(|ip| !ip.is_empty())
+}
+
+fn single_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    let mut values = headers.get_all(name);
+
+    match (values.next(), values.next()) {
+        (Some(value), None) => value.to_str().ok(),
+        _ => None,
+    }
+}
+
+// forwarded addresses may be given together with their port, and ipv6 ones within brackets
+fn without_port(addr: &str) -> String {
+    match addr.parse::<SocketAddr>() {
+        Ok(addr) => addr.ip().to_string(),
+        Err(_) => String::from(addr.trim_start_matches('[').trim_end_matches(']')),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::http::{HeaderName, HeaderValue};
+
+    use super::*;
+
+    fn header_map(entries: &[(&'static str, &'static str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in entries {
+            headers.append(HeaderName::from_static(name), HeaderValue::from_static(value));
+        }
+        headers
+    }
+
+    #[test]
+    fn last_forwarded_entry_is_used() {
+        let headers = header_map(&[("x-forwarded-for", "1.1.1.1, 2.2.2.2")]);
+        assert_eq!(forwarded_for(&headers), Some(String::from("2.2.2.2")));
+
+        let headers = header_map(&[("forwarded", "for=1.1.1.1, for=\"[2001:db8::1]:4711\";proto=https")]);
+        assert_eq!(forwarded_for(&headers), Some(String::from("2001:db8::1")));
+
+        let headers = header_map(&[("x-forwarded-for", "1.1.1.1"), ("x-forwarded-for", "2.2.2.2")]);
+        assert_eq!(forwarded_for(&headers), None);
+    }
+
+    #[test]
+    fn peer_address_is_used_without_trusted_proxy() {
+        let headers = header_map(&[("x-forwarded-for", "1.1.1.1")]);
+        let peer_addr = "4.4.4.4:1234".parse().ok();
+
+        let ip = ClientIp::resolve(Some(&ClientIpConfig::new(false)), &headers, peer_addr);
+        assert_eq!(ip, ClientIp(Some(String::from("4.4.4.4"))));
+
+        let ip = ClientIp::resolve(Some(&ClientIpConfig::new(true)), &headers, peer_addr);
+        assert_eq!(ip, ClientIp(Some(String::from("1.1.1.1"))));
+    }
+}
@@ -9,9 +9,11 @@ pub use htmlescape::encode_minimal;
 
 use crate::error::{ErrorMessaging, HttpError};
 
+pub mod client_ip;
 pub mod db;
 pub mod error;
 pub mod models;
+pub mod openapi;
 pub mod responses;
 pub mod sanitized;
 pub mod schema;
@@ -37,7 +39,9 @@ pub enum ErrorMessage {
     Forbidden,
     MiddlewareFailed,
     Custom(&'static str),
-    IOError
+    IOError,
+    InvalidCursor,
+    TooManyRequests,
 }
 
 impl ErrorMessaging for ErrorMessage {
@@ -128,6 +132,16 @@ impl ErrorMessaging for ErrorMessage {
                 error_code: 116,
                 message: String::from("io_error"),
             },
+            ErrorMessage::InvalidCursor => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 117,
+                message: String::from("invalid_cursor"),
+            },
+            ErrorMessage::TooManyRequests => HttpError {
+                code: StatusCode::TOO_MANY_REQUESTS,
+                error_code: 118,
+                message: String::from("too_many_requests"),
+            },
         }
     }
 }
@@ -135,7 +149,6 @@ impl ErrorMessaging for ErrorMessage {
 pub struct Config {
     pub web_app_url: String,
     pub app_url: String,
-    pub storage_path: String,
 }
 
 #[cfg(test)]
@@ -0,0 +1,106 @@
+use chrono::NaiveDateTime;
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel::sql_types::VarChar;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::db::DieselEnum;
+use crate::schema::audit_logs;
+use crate::types::ModelId;
+
+/// Security relevant and administrative actions recorded into the audit log
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy, JsonSchema, Default)]
+pub enum AuditAction {
+    #[default]
+    Login,
+    FailedLogin,
+    SignUp,
+    VerifyAccount,
+    ResetPassword,
+    UpdatePassword,
+    ResetCalendarToken,
+    ReserveSlot,
+    MoveSlot,
+    DeleteSlot,
+    ClaimSlotOffer,
+    ReserveSlotSeries,
+    UpdateSlotSeries,
+    CancelSlotSeries,
+    UpdateSlotPolicy,
+    UpdateSlotQuota,
+    CreateMaintenanceWindow,
+    DeleteMaintenanceWindow,
+    AbortJob,
+    CancelJob,
+    UpdateJobPriority,
+    DeleteExperiment,
+    FetchControllerToken,
+}
+
+impl Queryable<VarChar, Pg> for AuditAction {
+    type Row = String;
+
+    fn build(row: Self::Row) -> Self {
+        Self::build_from_string(row)
+    }
+}
+
+/// Kind of the model an action is taken on
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy, JsonSchema, Default)]
+pub enum TargetKind {
+    #[default]
+    User,
+    Slot,
+    SlotSeries,
+    Job,
+    Experiment,
+    Controller,
+    Role,
+    /// Maintenance window of a controller
+    Maintenance,
+}
+
+impl Queryable<VarChar, Pg> for TargetKind {
+    type Row = String;
+
+    fn build(row: Self::Row) -> Self {
+        Self::build_from_string(row)
+    }
+}
+
+/// Entries are only appended, the database rejects updating or deleting them
+#[derive(Identifiable, Queryable, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditLog {
+    pub id: ModelId,
+    pub actor_id: Option<ModelId>,
+    pub action: AuditAction,
+    pub target_kind: TargetKind,
+    pub target_id: ModelId,
+    pub ip: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Entry recorded by the handlers, actor is missing when the client is not authenticated
+pub struct AuditEntry {
+    pub actor_id: Option<ModelId>,
+    pub action: AuditAction,
+    pub target_kind: TargetKind,
+    pub target_id: ModelId,
+    pub ip: Option<String>,
+}
+
+/// Should be called within the transaction of the action, so that the action is not taken without being recorded
+pub fn record(conn: &PgConnection, entry: AuditEntry) -> QueryResult<()> {
+    diesel::insert_into(audit_logs::table)
+        .values((
+            audit_logs::actor_id.eq(entry.actor_id),
+            audit_logs::action.eq(entry.action.value()),
+            audit_logs::target_kind.eq(entry.target_kind.value()),
+            audit_logs::target_id.eq(entry.target_id),
+            audit_logs::ip.eq(entry.ip),
+        ))
+        .execute(conn)
+        .map(|_| ())
+}
@@ -0,0 +1,132 @@
+use chrono::NaiveDateTime;
+use diesel::dsl::{Asc, Desc, GtEq, LtEq};
+use diesel::expression::AsExpression;
+use diesel::expression::NonAggregate;
+use diesel::pg::expression::operators::NullsLast;
+use diesel::pg::Pg;
+use diesel::prelude::*;
+use diesel::query_builder::*;
+use diesel::query_dsl::methods::{FilterDsl, ThenOrderDsl};
+use diesel::sql_types::{Bool, SingleValue, Text, Timestamp};
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Text search configuration, kept in sync with the expressions of full text search indexes
+const TEXT_SEARCH_CONFIG: &str = "simple";
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, JsonSchema, Default)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Field to sort by is given by each listing as an enum of the fields it allows
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[schemars(bound = "F: JsonSchema + Default")]
+pub struct SortRequest<F> {
+    #[serde(default)]
+    pub sort_by: F,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct DateRangeRequest {
+    pub from: Option<NaiveDateTime>,
+    pub to: Option<NaiveDateTime>,
+}
+
+pub trait Sort: Sized {
+    /// Orders by the expression, can be chained to break ties
+    fn sort<E>(self, expr: E, order: SortOrder) -> Self
+        where
+            E: Expression,
+            E::SqlType: SingleValue,
+            Self: ThenOrderDsl<Asc<E>, Output=Self> + ThenOrderDsl<Desc<E>, Output=Self>,
+    {
+        match order {
+            SortOrder::Asc => self.then_order_by(expr.asc()),
+            SortOrder::Desc => self.then_order_by(expr.desc()),
+        }
+    }
+
+    /// Same as sort but rows having NULL come last in both orders
+    fn sort_nulls_last<E>(self, expr: E, order: SortOrder) -> Self
+        where
+            E: Expression,
+            E::SqlType: SingleValue,
+            Self: ThenOrderDsl<NullsLast<Asc<E>>, Output=Self> + ThenOrderDsl<NullsLast<Desc<E>>, Output=Self>,
+    {
+        match order {
+            SortOrder::Asc => self.then_order_by(expr.asc().nulls_last()),
+            SortOrder::Desc => self.then_order_by(expr.desc().nulls_last()),
+        }
+    }
+}
+
+impl<T> Sort for T {}
+
+pub trait FilterDateRange: Sized {
+    /// Keeps the rows whose column is within the range, both ends are inclusive and optional
+    fn within<E>(self, column: E, range: &DateRangeRequest) -> Self
+        where
+            E: Expression<SqlType=Timestamp> + Copy,
+            NaiveDateTime: AsExpression<Timestamp>,
+            Self: FilterDsl<GtEq<E, NaiveDateTime>, Output=Self> + FilterDsl<LtEq<E, NaiveDateTime>, Output=Self>,
+    {
+        let mut query = self;
+
+        if let Some(from) = range.from {
+            query = FilterDsl::filter(query, column.ge(from));
+        }
+
+        if let Some(to) = range.to {
+            query = FilterDsl::filter(query, column.le(to));
+        }
+
+        query
+    }
+}
+
+impl<T> FilterDateRange for T {}
+
+/// Full text search over a text column, matches if the document contains all the words of the query.
+/// Written out with a literal search configuration so that expression indexes on
+/// `to_tsvector('simple', column)` can be used.
+pub struct TextSearch<E> {
+    document: E,
+    query: String,
+}
+
+pub fn text_search<E: Expression<SqlType=Text>>(document: E, query: String) -> TextSearch<E> {
+    TextSearch { document, query }
+}
+
+impl<E> Expression for TextSearch<E> {
+    type SqlType = Bool;
+}
+
+impl<E: QueryFragment<Pg>> QueryFragment<Pg> for TextSearch<E> {
+    fn walk_ast(&self, mut out: AstPass<Pg>) -> QueryResult<()> {
+        out.push_sql(&format!("to_tsvector('{}', ", TEXT_SEARCH_CONFIG));
+        self.document.walk_ast(out.reborrow())?;
+        out.push_sql(&format!(") @@ plainto_tsquery('{}', ", TEXT_SEARCH_CONFIG));
+        out.push_bind_param::<Text, _>(&self.query)?;
+        out.push_sql(")");
+        Ok(())
+    }
+}
+
+impl<E: AppearsOnTable<QS>, QS> AppearsOnTable<QS> for TextSearch<E> {}
+
+impl<E: SelectableExpression<QS>, QS> SelectableExpression<QS> for TextSearch<E> {}
+
+impl<E: NonAggregate> NonAggregate for TextSearch<E> {}
+
+impl<E> QueryId for TextSearch<E> {
+    type QueryId = ();
+
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
@@ -1,3 +1,5 @@
+pub mod audit;
+pub mod filter;
 pub mod paginate;
 pub mod role;
 pub mod token;
@@ -3,8 +3,17 @@ use diesel::pg::Pg;
 use diesel::prelude::*;
 use diesel::query_builder::*;
 use diesel::query_dsl::methods::LoadQuery;
-use diesel::sql_types::BigInt;
+use diesel::dsl::{Asc, Desc};
+use diesel::query_dsl::methods::{FilterDsl, LimitDsl, ThenOrderDsl};
+use diesel::serialize::ToSql;
+use diesel::sql_types::{BigInt, HasSqlType, Integer, SingleValue};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use crate::ErrorMessage;
+use crate::models::filter::{Sort, SortOrder};
+use crate::types::ModelId;
 
 pub trait Paginate: Sized {
     fn paginate(self, page: Option<i64>) -> Paginated<Self>;
@@ -13,7 +22,7 @@ pub trait Paginate: Sized {
 impl<T> Paginate for T {
     fn paginate(self, page: Option<i64>) -> Paginated<Self> {
         let page = match page {
-            Some(num) => num,
+            Some(num) => num.max(1),
             None => 1
         };
 
@@ -26,6 +35,16 @@ impl<T> Paginate for T {
 }
 
 const DEFAULT_PER_PAGE: i64 = 10;
+/// Upper bound of the page size clients can ask for
+pub const MAX_PER_PAGE: i64 = 100;
+
+/// Page size asked by the client, bounded by MAX_PER_PAGE
+pub fn per_page(per_page: Option<i64>) -> i64 {
+    match per_page {
+        Some(num) => num.clamp(1, MAX_PER_PAGE),
+        None => DEFAULT_PER_PAGE,
+    }
+}
 
 #[derive(Debug, Clone, Copy, QueryId)]
 pub struct Paginated<T> {
@@ -36,12 +55,7 @@ pub struct Paginated<T> {
 
 impl<T> Paginated<T> {
     pub fn per_page(self, per_page: Option<i64>) -> Self {
-        let per_page = match per_page {
-            Some(num) => num,
-            None => DEFAULT_PER_PAGE,
-        };
-
-        Paginated { per_page, ..self }
+        Paginated { per_page: self::per_page(per_page), ..self }
     }
 
     pub fn load_and_count_pages<U>(self, conn: &PgConnection) -> QueryResult<Pagination<U>>
@@ -88,7 +102,7 @@ impl<T> QueryFragment<Pg> for Paginated<T>
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Pagination<T> {
     per_page: i64,
@@ -98,11 +112,29 @@ pub struct Pagination<T> {
     items: Vec<T>,
 }
 
-#[derive(Deserialize)]
+impl<T> Pagination<T> {
+    pub fn items(&self) -> &[T] {
+        &self.items
+    }
+
+    pub fn map<U, F: FnMut(T) -> U>(self, f: F) -> Pagination<U> {
+        Pagination {
+            per_page: self.per_page,
+            current_page: self.current_page,
+            total_pages: self.total_pages,
+            total_items: self.total_items,
+            items: self.items.into_iter().map(f).collect(),
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct PaginationRequest {
     pub per_page: Option<i64>,
     pub page: Option<i64>,
+    /// Switches to keyset pagination on the listings supporting it, empty for the first page
+    pub cursor: Option<String>,
 }
 
 pub struct CountStarOver;
@@ -127,3 +159,161 @@ impl NonAggregate for CountStarOver {}
 impl QueryId for CountStarOver {
     type QueryId = <Self as Expression>::SqlType;
 }
+
+/// Position of the last row of a page in keyset pagination, the sort key of the row together with its id.
+/// Handed to clients as an opaque string.
+#[derive(Serialize, Deserialize)]
+pub struct Cursor<K> {
+    key: K,
+    id: ModelId,
+}
+
+impl<K> Cursor<K> where K: Serialize + DeserializeOwned {
+    pub fn new(key: K, id: ModelId) -> Self {
+        Cursor { key, id }
+    }
+
+    /// An empty cursor points to the start of the listing
+    pub fn decode(cursor: &str) -> Result<Option<Self>, ErrorMessage> {
+        if cursor.is_empty() {
+            return Ok(None);
+        }
+
+        base64::decode_config(cursor, base64::URL_SAFE_NO_PAD)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .map(Some)
+            .ok_or(ErrorMessage::InvalidCursor)
+    }
+
+    pub fn encode(&self) -> String {
+        base64::encode_config(serde_json::to_vec(self).unwrap(), base64::URL_SAFE_NO_PAD)
+    }
+}
+
+/// Rows coming after the cursor in the order of (key, id)
+pub struct KeysetAfter<EK, EI, K> {
+    key: EK,
+    id: EI,
+    cursor: Cursor<K>,
+    order: SortOrder,
+}
+
+impl<EK, EI, K> Expression for KeysetAfter<EK, EI, K> {
+    type SqlType = diesel::sql_types::Bool;
+}
+
+impl<EK, EI, K> QueryFragment<Pg> for KeysetAfter<EK, EI, K>
+    where
+        EK: Expression + QueryFragment<Pg>,
+        EI: QueryFragment<Pg>,
+        K: ToSql<EK::SqlType, Pg>,
+        Pg: HasSqlType<EK::SqlType>,
+{
+    fn walk_ast(&self, mut out: AstPass<Pg>) -> QueryResult<()> {
+        out.push_sql("(");
+        self.key.walk_ast(out.reborrow())?;
+        out.push_sql(", ");
+        self.id.walk_ast(out.reborrow())?;
+        out.push_sql(match self.order {
+            SortOrder::Asc => ") > (",
+            SortOrder::Desc => ") < (",
+        });
+        out.push_bind_param::<EK::SqlType, _>(&self.cursor.key)?;
+        out.push_sql(", ");
+        out.push_bind_param::<Integer, _>(&self.cursor.id)?;
+        out.push_sql(")");
+        Ok(())
+    }
+}
+
+impl<EK: AppearsOnTable<QS>, EI: AppearsOnTable<QS>, K, QS> AppearsOnTable<QS> for KeysetAfter<EK, EI, K> {}
+
+impl<EK: NonAggregate, EI: NonAggregate, K> NonAggregate for KeysetAfter<EK, EI, K> {}
+
+impl<EK, EI, K> QueryId for KeysetAfter<EK, EI, K> {
+    type QueryId = ();
+
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+pub trait KeysetPaginate: Sized {
+    /// Orders by the key and the id, and keeps the rows after the cursor. The key can not be nullable.
+    fn after<EK, EI, K>(self, key: EK, id: EI, cursor: Option<Cursor<K>>, order: SortOrder) -> Self
+        where
+            EK: Expression + Copy,
+            EK::SqlType: SingleValue,
+            EI: Expression<SqlType=Integer> + Copy,
+            Self: FilterDsl<KeysetAfter<EK, EI, K>, Output=Self>,
+            Self: ThenOrderDsl<Asc<EK>, Output=Self> + ThenOrderDsl<Desc<EK>, Output=Self>,
+            Self: ThenOrderDsl<Asc<EI>, Output=Self> + ThenOrderDsl<Desc<EI>, Output=Self>,
+    {
+        let query = match cursor {
+            Some(cursor) => FilterDsl::filter(self, KeysetAfter { key, id, cursor, order }),
+            None => self
+        };
+
+        query.sort(key, order).sort(id, order)
+    }
+}
+
+impl<T> KeysetPaginate for T {}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CursorPagination<T> {
+    per_page: i64,
+    /// None on the last page
+    next_cursor: Option<String>,
+    items: Vec<T>,
+}
+
+impl<T> CursorPagination<T> {
+    /// Loads a page of the query ordered by `KeysetPaginate::after`, `cursor_of` gives the cursor of a row
+    pub fn load<Q, F, K>(query: Q, per_page: i64, conn: &PgConnection, cursor_of: F) -> QueryResult<Self>
+        where
+            Q: LimitDsl,
+            Q::Output: LoadQuery<PgConnection, T>,
+            F: Fn(&T) -> Cursor<K>,
+            K: Serialize + DeserializeOwned,
+    {
+        // one more row tells whether there is a next page
+        let mut items = query.limit(per_page + 1).load::<T>(conn)?;
+
+        let next_cursor = if items.len() as i64 > per_page {
+            items.truncate(per_page as usize);
+            items.last().map(|item| cursor_of(item).encode())
+        } else {
+            None
+        };
+
+        Ok(CursorPagination {
+            per_page,
+            next_cursor,
+            items,
+        })
+    }
+}
+
+/// Page of a listing supporting both offset and keyset pagination
+#[derive(Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Page<T> {
+    Offset(Pagination<T>),
+    Cursor(CursorPagination<T>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_is_decoded_back() {
+        let cursor = Cursor::new(String::from("receiver crash"), 4).encode();
+        let decoded = Cursor::<String>::decode(&cursor).unwrap().unwrap();
+
+        assert_eq!((decoded.key, decoded.id), (String::from("receiver crash"), 4));
+        assert!(Cursor::<String>::decode("").unwrap().is_none());
+        assert!(Cursor::<i32>::decode(&cursor).is_err());
+    }
+}
@@ -0,0 +1,269 @@
+use schemars::gen::{SchemaGenerator, SchemaSettings};
+use schemars::JsonSchema;
+use schemars::schema::Schema;
+use serde_json::{json, Map, Value};
+
+/// Version of the API served under `/api/v1`, routes and types of a published version are not changed in a breaking way
+pub const VERSION: &str = "1";
+
+/// Who can call the operations of a scope
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+pub enum Access {
+    Public,
+    User,
+    /// Only valid inside a `User` scope, admin check relies on the authenticated user
+    Admin,
+}
+
+/// Implemented for handlers by the `derive::operation` attribute, describes the route of the handler together with the
+/// types it takes and returns
+pub trait Operation {
+    fn describe(operation: &mut OperationBuilder);
+}
+
+pub struct OperationBuilder<'a> {
+    generator: &'a mut SchemaGenerator,
+    method: &'static str,
+    path: &'static str,
+    operation: Map<String, Value>,
+    parameters: Vec<Value>,
+    responses: Map<String, Value>,
+}
+
+impl<'a> OperationBuilder<'a> {
+    fn new(generator: &'a mut SchemaGenerator) -> Self {
+        OperationBuilder {
+            generator,
+            method: "get",
+            path: "",
+            operation: Map::new(),
+            parameters: Vec::new(),
+            responses: Map::new(),
+        }
+    }
+
+    pub fn route(&mut self, method: &'static str, path: &'static str, id: &'static str, summary: &'static str) -> &mut Self {
+        self.method = method;
+        self.path = path;
+        self.operation.insert(String::from("operationId"), json!(id));
+
+        if !summary.is_empty() {
+            self.operation.insert(String::from("summary"), json!(summary));
+        }
+
+        self
+    }
+
+    pub fn path_param<T: JsonSchema>(&mut self, name: &'static str) -> &mut Self {
+        let schema = self.generator.subschema_for::<T>();
+
+        self.parameters.push(json!({ "name": name, "in": "path", "required": true, "schema": schema }));
+
+        self
+    }
+
+    /// Each field of the query type becomes a parameter
+    pub fn query<T: JsonSchema>(&mut self) -> &mut Self {
+        let schema = T::json_schema(self.generator).into_object();
+        let object = schema.object.unwrap_or_default();
+
+        for (name, schema) in object.properties {
+            let required = object.required.contains(&name);
+
+            self.parameters.push(json!({ "name": name, "in": "query", "required": required, "schema": schema }));
+        }
+
+        self
+    }
+
+    pub fn json_body<T: JsonSchema>(&mut self, required: bool) -> &mut Self {
+        let schema = self.generator.subschema_for::<T>();
+
+        self.operation.insert(String::from("requestBody"), json!({
+            "required": required,
+            "content": { "application/json": { "schema": schema } }
+        }));
+
+        self
+    }
+
+    pub fn binary_body(&mut self) -> &mut Self {
+        self.operation.insert(String::from("requestBody"), json!({
+            "required": true,
+            "content": { "application/octet-stream": { "schema": { "type": "string", "format": "binary" } } }
+        }));
+
+        self
+    }
+
+    pub fn json_response<T: JsonSchema>(&mut self) -> &mut Self {
+        let schema = self.generator.subschema_for::<T>();
+
+        self.responses.insert(String::from("200"), json!({
+            "description": "Successful response",
+            "content": { "application/json": { "schema": schema } }
+        }));
+
+        self
+    }
+
+    /// Response whose body is not JSON, like downloads, redirects and websocket upgrades
+    pub fn response(&mut self) -> &mut Self {
+        self.responses.insert(String::from("200"), json!({ "description": "Successful response" }));
+
+        self
+    }
+}
+
+/// OpenAPI 3 document of the routes registered into it
+pub struct Spec {
+    generator: SchemaGenerator,
+    paths: Map<String, Value>,
+    // prefixes and access of the scopes being registered, the outermost first
+    scopes: Vec<(String, Access)>,
+}
+
+impl Default for Spec {
+    fn default() -> Self {
+        Spec {
+            generator: SchemaGenerator::new(SchemaSettings::openapi3()),
+            paths: Map::new(),
+            scopes: Vec::new(),
+        }
+    }
+}
+
+impl Spec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn scope<F: FnOnce(&mut Self)>(&mut self, path: &str, access: Access, f: F) {
+        self.scopes.push((String::from(path), access));
+        f(self);
+        self.scopes.pop();
+    }
+
+    pub fn add<O: Operation>(&mut self) {
+        let mut builder = OperationBuilder::new(&mut self.generator);
+        O::describe(&mut builder);
+
+        let OperationBuilder { method, path, mut operation, parameters, mut responses, .. } = builder;
+
+        let access = self.scopes.iter()
+            .map(|(_, access)| *access)
+            .fold(Access::Public, |max, access| if access > max { access } else { max });
+
+        let segments = self.scopes.iter()
+            .map(|(prefix, _)| prefix.as_str())
+            .chain(std::iter::once(path))
+            .flat_map(|segment| segment.split('/'))
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<&str>>();
+
+        if let Some(tag) = segments.first() {
+            operation.insert(String::from("tags"), json!([tag]));
+        }
+
+        if !parameters.is_empty() {
+            operation.insert(String::from("parameters"), Value::Array(parameters));
+        }
+
+        if access != Access::Public {
+            operation.insert(String::from("security"), json!([{ "bearer": [] }]));
+        }
+
+        if access == Access::Admin {
+            operation.insert(String::from("description"), json!("Requires an admin user."));
+        }
+
+        responses.insert(String::from("default"), json!({
+            "description": "Error",
+            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/Error" } } }
+        }));
+        operation.insert(String::from("responses"), Value::Object(responses));
+
+        let path = segments.iter()
+            .map(|segment| route_segment(segment))
+            .fold(String::new(), |path, segment| path + "/" + &segment);
+
+        if let Value::Object(methods) = self.paths.entry(path).or_insert_with(|| json!({})) {
+            methods.insert(String::from(method), Value::Object(operation));
+        }
+    }
+
+    /// The document served at the given url
+    pub fn document(mut self, title: &str, server_url: &str) -> Value {
+        let mut schemas = self.generator.take_definitions()
+            .into_iter()
+            .map(|(name, schema)| (name, schema_value(schema)))
+            .collect::<Map<String, Value>>();
+
+        schemas.insert(String::from("Error"), json!({
+            "type": "object",
+            "required": ["code", "errorCode", "message"],
+            "properties": {
+                "code": { "type": "integer", "description": "HTTP status code" },
+                "errorCode": { "type": "integer" },
+                "message": { "type": "string" },
+                "validationErrors": { "type": "object", "description": "Only given for invalid requests" }
+            }
+        }));
+
+        json!({
+            "openapi": "3.0.3",
+            "info": { "title": title, "version": VERSION },
+            "servers": [{ "url": server_url }],
+            "paths": self.paths,
+            "components": {
+                "schemas": schemas,
+                "securitySchemes": { "bearer": { "type": "http", "scheme": "bearer" } }
+            }
+        })
+    }
+}
+
+/// Drops the regex of a dynamic segment, `{name:\d+}` becomes `{name}`
+fn route_segment(segment: &str) -> String {
+    match segment.find(':') {
+        Some(index) if segment.starts_with('{') => format!("{}}}", &segment[..index]),
+        _ => String::from(segment),
+    }
+}
+
+fn schema_value(schema: Schema) -> Value {
+    serde_json::to_value(schema).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FetchThing;
+
+    impl Operation for FetchThing {
+        fn describe(operation: &mut OperationBuilder) {
+            operation
+                .route("get", "thing/{id:\\d+}", "fetch_thing", "")
+                .path_param::<i32>("id")
+                .json_response::<String>();
+        }
+    }
+
+    #[test]
+    fn operations_are_placed_under_scopes() {
+        let mut spec = Spec::new();
+
+        spec.scope("/things", Access::Public, |spec| {
+            spec.scope("", Access::User, |spec| spec.add::<FetchThing>());
+        });
+
+        let document = spec.document("Things", "/api/v1");
+        let operation = &document["paths"]["/things/thing/{id}"]["get"];
+
+        assert_eq!(operation["operationId"], "fetch_thing");
+        assert_eq!(operation["tags"], json!(["things"]));
+        assert_eq!(operation["security"], json!([{ "bearer": [] }]));
+        assert_eq!(operation["parameters"][0]["name"], "id");
+    }
+}
@@ -14,6 +14,18 @@ impl Sanitize for String {
     }
 }
 
+macro_rules! impl_sanitize_as_is {
+    ($($t:ty),*) => {
+        $(impl Sanitize for $t {
+            fn sanitize(self) -> Self {
+                self
+            }
+        })*
+    };
+}
+
+impl_sanitize_as_is!(bool, i32, i64);
+
 impl<T> Sanitize for Option<T> where T: Sanitize {
     fn sanitize(self) -> Self {
         if let Some(t) = self {
@@ -24,6 +36,12 @@ impl<T> Sanitize for Option<T> where T: Sanitize {
     }
 }
 
+impl<T> Sanitize for Vec<T> where T: Sanitize {
+    fn sanitize(self) -> Self {
+        self.into_iter().map(|t| t.sanitize()).collect()
+    }
+}
+
 pub struct SanitizedPath<T>(T);
 
 impl<T> SanitizedPath<T> {
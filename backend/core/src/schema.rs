@@ -1,3 +1,24 @@
+table! {
+    audit_logs (id) {
+        id -> Int4,
+        actor_id -> Nullable<Int4>,
+        action -> Varchar,
+        target_kind -> Varchar,
+        target_id -> Int4,
+        ip -> Nullable<Varchar>,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    calendar_tokens (user_id) {
+        user_id -> Int4,
+        token -> Varchar,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     controllers (id) {
         id -> Int4,
@@ -7,6 +28,16 @@ table! {
     }
 }
 
+table! {
+    experiment_shares (id) {
+        id -> Int4,
+        experiment_id -> Int4,
+        user_id -> Int4,
+        role -> Varchar,
+        created_at -> Timestamp,
+    }
+}
+
 table! {
     experiments (id) {
         id -> Int4,
@@ -15,6 +46,35 @@ table! {
         code -> Text,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        parameters -> Jsonb,
+        forked_from_id -> Nullable<Int4>,
+        template_version_id -> Nullable<Int4>,
+    }
+}
+
+table! {
+    job_analyses (id) {
+        id -> Int4,
+        job_id -> Int4,
+        sample_frequency -> Float8,
+        threshold -> Nullable<Float8>,
+        max_latency -> Int4,
+        transmitted -> Array<Bool>,
+        receivers -> Jsonb,
+        bit_error_rate -> Float8,
+        latency -> Nullable<Float8>,
+        analyzed_at -> Timestamp,
+    }
+}
+
+table! {
+    job_artifacts (id) {
+        id -> Int4,
+        job_id -> Int4,
+        name -> Varchar,
+        content_type -> Varchar,
+        size -> Int8,
+        created_at -> Timestamp,
     }
 }
 
@@ -27,6 +87,38 @@ table! {
         status -> Varchar,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        parameters -> Jsonb,
+        sweep_id -> Nullable<Int4>,
+        priority -> Int4,
+        priority_override -> Nullable<Int4>,
+        started_at -> Nullable<Timestamp>,
+        finished_at -> Nullable<Timestamp>,
+        failure_kind -> Nullable<Varchar>,
+        failure_cause -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    maintenance_windows (id) {
+        id -> Int4,
+        controller_id -> Int4,
+        start_at -> Timestamp,
+        end_at -> Timestamp,
+        reason -> Nullable<Varchar>,
+        created_by -> Nullable<Int4>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    role_slot_quotas (role_id) {
+        role_id -> Int4,
+        max_future_slots -> Nullable<Int4>,
+        max_hours_per_week -> Nullable<Int4>,
+        max_horizon_days -> Nullable<Int4>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
     }
 }
 
@@ -37,6 +129,50 @@ table! {
     }
 }
 
+table! {
+    slot_policies (controller_id) {
+        controller_id -> Int4,
+        slot_minutes -> Int4,
+        gap_minutes -> Int4,
+        horizon_days -> Int4,
+        earliest_hour -> Int4,
+        latest_hour -> Int4,
+        notice_minutes -> Int4,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    slot_series (id) {
+        id -> Int4,
+        user_id -> Int4,
+        controller_id -> Int4,
+        frequency -> Varchar,
+        start_at -> Timestamp,
+        until -> Nullable<Timestamp>,
+        count -> Nullable<Int4>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    slot_waitlist_entries (id) {
+        id -> Int4,
+        user_id -> Int4,
+        controller_id -> Int4,
+        window_start -> Timestamp,
+        window_end -> Timestamp,
+        status -> Varchar,
+        offered_start_at -> Nullable<Timestamp>,
+        offered_end_at -> Nullable<Timestamp>,
+        offer_expires_at -> Nullable<Timestamp>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
 table! {
     slots (id) {
         id -> Int4,
@@ -46,6 +182,51 @@ table! {
         end_at -> Timestamp,
         created_at -> Timestamp,
         updated_at -> Timestamp,
+        series_id -> Nullable<Int4>,
+    }
+}
+
+table! {
+    sweeps (id) {
+        id -> Int4,
+        experiment_id -> Int4,
+        controller_id -> Int4,
+        definition -> Jsonb,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    template_versions (id) {
+        id -> Int4,
+        template_id -> Int4,
+        version -> Int4,
+        code -> Text,
+        parameters -> Jsonb,
+        created_at -> Timestamp,
+    }
+}
+
+table! {
+    templates (id) {
+        id -> Int4,
+        name -> Varchar,
+        description -> Text,
+        tags -> Array<Text>,
+        published -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+table! {
+    user_slot_quotas (user_id) {
+        user_id -> Int4,
+        max_future_slots -> Nullable<Int4>,
+        max_hours_per_week -> Nullable<Int4>,
+        max_horizon_days -> Nullable<Int4>,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
     }
 }
 
@@ -61,18 +242,85 @@ table! {
     }
 }
 
+table! {
+    webhook_deliveries (id) {
+        id -> Int4,
+        webhook_id -> Int4,
+        event -> Varchar,
+        payload -> Jsonb,
+        status -> Varchar,
+        attempts -> Int4,
+        next_attempt_at -> Timestamp,
+        response_status -> Nullable<Int4>,
+        error -> Nullable<Text>,
+        created_at -> Timestamp,
+        delivered_at -> Nullable<Timestamp>,
+    }
+}
+
+table! {
+    webhooks (id) {
+        id -> Int4,
+        user_id -> Int4,
+        url -> Varchar,
+        secret -> Varchar,
+        events -> Array<Text>,
+        active -> Bool,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+    }
+}
+
+joinable!(calendar_tokens -> users (user_id));
+joinable!(experiment_shares -> experiments (experiment_id));
+joinable!(experiment_shares -> users (user_id));
+joinable!(experiments -> template_versions (template_version_id));
 joinable!(experiments -> users (user_id));
+joinable!(job_analyses -> jobs (job_id));
+joinable!(job_artifacts -> jobs (job_id));
 joinable!(jobs -> controllers (controller_id));
 joinable!(jobs -> experiments (experiment_id));
+joinable!(jobs -> sweeps (sweep_id));
+joinable!(maintenance_windows -> controllers (controller_id));
+joinable!(maintenance_windows -> users (created_by));
+joinable!(role_slot_quotas -> roles (role_id));
+joinable!(slot_policies -> controllers (controller_id));
+joinable!(slot_series -> controllers (controller_id));
+joinable!(slot_series -> users (user_id));
+joinable!(slot_waitlist_entries -> controllers (controller_id));
+joinable!(slot_waitlist_entries -> users (user_id));
 joinable!(slots -> controllers (controller_id));
+joinable!(slots -> slot_series (series_id));
 joinable!(slots -> users (user_id));
+joinable!(sweeps -> controllers (controller_id));
+joinable!(sweeps -> experiments (experiment_id));
+joinable!(template_versions -> templates (template_id));
+joinable!(user_slot_quotas -> users (user_id));
 joinable!(users -> roles (role_id));
+joinable!(webhook_deliveries -> webhooks (webhook_id));
+joinable!(webhooks -> users (user_id));
 
 allow_tables_to_appear_in_same_query!(
+    audit_logs,
+    calendar_tokens,
     controllers,
+    experiment_shares,
     experiments,
+    job_analyses,
+    job_artifacts,
     jobs,
+    maintenance_windows,
+    role_slot_quotas,
     roles,
+    slot_policies,
+    slot_series,
+    slot_waitlist_entries,
     slots,
+    sweeps,
+    template_versions,
+    templates,
+    user_slot_quotas,
     users,
+    webhook_deliveries,
+    webhooks,
 );
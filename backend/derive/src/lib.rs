@@ -2,6 +2,7 @@ use proc_macro::TokenStream as MainTokenStream;
 
 use proc_macro2::TokenStream;
 use quote::quote;
+use syn::{FnArg, GenericArgument, ItemFn, Lit, Meta, NestedMeta, PathArguments, ReturnType, Type};
 
 #[proc_macro_derive(Sanitize, attributes(sanitize))]
 pub fn sanitize_derive(input: MainTokenStream) -> MainTokenStream {
@@ -64,6 +65,148 @@ fn impl_sanitize(ast: &syn::DeriveInput) -> TokenStream {
     gen.into()
 }
 
+/// Implements `core::openapi::Operation` for an actix handler, it should be placed above the route attribute of the
+/// handler such as `#[get("experiment/{id}")]`. The summary is taken from the doc comment of the handler.
+#[proc_macro_attribute]
+pub fn operation(_args: MainTokenStream, input: MainTokenStream) -> MainTokenStream {
+    let item: ItemFn = syn::parse(input).unwrap();
+
+    let operation = impl_operation(&item);
+
+    let gen = quote! {
+        #item
+        #operation
+    };
+
+    gen.into()
+}
+
+fn impl_operation(item: &ItemFn) -> TokenStream {
+    let (method, path) = item.attrs.iter()
+        .find_map(|attr| {
+            let method = attr.path.segments.last()?.ident.to_string();
+
+            if !["get", "post", "put", "delete", "patch"].contains(&method.as_str()) {
+                return None;
+            }
+
+            match attr.parse_meta() {
+                Ok(Meta::List(list)) => match list.nested.first() {
+                    Some(NestedMeta::Lit(Lit::Str(path))) => Some((method, path.value())),
+                    _ => None
+                },
+                _ => None
+            }
+        })
+        .expect("operation should be placed above the route attribute of the handler");
+
+    let summary = item.attrs.iter()
+        .filter(|attr| attr.path.is_ident("doc"))
+        .filter_map(|attr| match attr.parse_meta() {
+            Ok(Meta::NameValue(doc)) => match doc.lit {
+                Lit::Str(line) => Some(line.value().trim().to_string()),
+                _ => None
+            },
+            _ => None
+        })
+        .collect::<Vec<String>>()
+        .join(" ");
+
+    let mut path_params = path.split('/')
+        .filter(|segment| segment.starts_with('{'))
+        .map(|segment| segment.trim_matches(|c| c == '{' || c == '}').split(':').next().unwrap().to_string());
+
+    let mut describe: Vec<TokenStream> = vec![];
+
+    for input in item.sig.inputs.iter() {
+        let ty = match input {
+            FnArg::Typed(arg) => &*arg.ty,
+            FnArg::Receiver(_) => continue,
+        };
+
+        let (kind, inner) = match extractor(ty) {
+            Some(extractor) => extractor,
+            None => continue
+        };
+
+        match kind.as_str() {
+            "Path" => {
+                let types = match inner {
+                    Some(Type::Tuple(tuple)) => tuple.elems.iter().collect::<Vec<&Type>>(),
+                    Some(ty) => vec![ty],
+                    None => vec![],
+                };
+
+                for ty in types {
+                    let name = path_params.next().expect("path parameters should match the route of the handler");
+
+                    describe.push(quote! { .path_param::<#ty>(#name) });
+                }
+            }
+            "Query" | "SanitizedQuery" => describe.push(quote! { .query::<#inner>() }),
+            "Json" | "SanitizedJson" => describe.push(quote! { .json_body::<#inner>(true) }),
+            "Option" => if let Some((json, Some(inner))) = inner.and_then(extractor) {
+                if json == "Json" || json == "SanitizedJson" {
+                    describe.push(quote! { .json_body::<#inner>(false) });
+                }
+            },
+            "Payload" if method != "get" => describe.push(quote! { .binary_body() }),
+            _ => {}
+        }
+    }
+
+    if path_params.next().is_some() {
+        panic!("path parameters should match the route of the handler");
+    }
+
+    let json_response = match &item.sig.output {
+        ReturnType::Type(_, ty) => match extractor(ty) {
+            Some((result, Some(ok))) if result == "Result" => match extractor(ok) {
+                Some((json, Some(inner))) if json == "Json" => Some(inner),
+                _ => None
+            },
+            _ => None
+        },
+        ReturnType::Default => None,
+    };
+
+    describe.push(match json_response {
+        Some(inner) => quote! { .json_response::<#inner>() },
+        None => quote! { .response() },
+    });
+
+    let name = &item.sig.ident;
+    let id = name.to_string();
+
+    quote! {
+        impl core::openapi::Operation for #name {
+            fn describe(operation: &mut core::openapi::OperationBuilder) {
+                operation
+                    .route(#method, #path, #id, #summary)
+                    #(#describe)*;
+            }
+        }
+    }
+}
+
+/// Last segment of a type path together with its first type argument, `web::Json<T>` gives `("Json", Some(T))`
+fn extractor(ty: &Type) -> Option<(String, Option<&Type>)> {
+    let segment = match ty {
+        Type::Path(path) => path.path.segments.last()?,
+        _ => return None
+    };
+
+    let inner = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            GenericArgument::Type(ty) => Some(ty),
+            _ => None
+        }),
+        _ => None
+    };
+
+    Some((segment.ident.to_string(), inner))
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -0,0 +1,64 @@
+use diesel::prelude::*;
+
+use core::ErrorMessage as CoreErrorMessage;
+use core::schema::{experiment_shares, experiments, jobs};
+use core::types::{ModelId, Result};
+
+use crate::models::share::ShareRole;
+
+/// Access of a user to an experiment, ordered from the least to the most privileged
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum Access {
+    Viewer,
+    Editor,
+    Owner,
+}
+
+impl From<ShareRole> for Access {
+    fn from(role: ShareRole) -> Self {
+        match role {
+            ShareRole::Viewer => Access::Viewer,
+            ShareRole::Editor => Access::Editor,
+        }
+    }
+}
+
+/// Access of the user to the experiment. Experiments that are not shared with the user are reported as not found
+/// to not reveal their existence.
+pub fn experiment_access(conn: &PgConnection, experiment_id: ModelId, user_id: ModelId) -> Result<Access> {
+    let owner_id = experiments::table
+        .find(experiment_id)
+        .select(experiments::user_id)
+        .first::<ModelId>(conn)?;
+
+    if owner_id == user_id {
+        return Ok(Access::Owner);
+    }
+
+    experiment_shares::table
+        .filter(experiment_shares::experiment_id.eq(experiment_id))
+        .filter(experiment_shares::user_id.eq(user_id))
+        .select(experiment_shares::role)
+        .first::<ShareRole>(conn)
+        .map(Access::from)
+        .map_err(|e| e.into())
+}
+
+/// Checks that the user has at least the required access to the experiment
+pub fn check_experiment_access(conn: &PgConnection, experiment_id: ModelId, user_id: ModelId, required: Access) -> Result<()> {
+    if experiment_access(conn, experiment_id, user_id)? >= required {
+        Ok(())
+    } else {
+        Err(Box::new(CoreErrorMessage::Forbidden))
+    }
+}
+
+/// Checks that the user has at least the required access to the experiment of the job
+pub fn check_job_access(conn: &PgConnection, job_id: ModelId, user_id: ModelId, required: Access) -> Result<()> {
+    let experiment_id = jobs::table
+        .find(job_id)
+        .select(jobs::experiment_id)
+        .first::<ModelId>(conn)?;
+
+    check_experiment_access(conn, experiment_id, user_id, required)
+}
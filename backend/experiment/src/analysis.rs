@@ -0,0 +1,271 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use shared::state::Slot;
+
+use crate::ErrorMessage;
+
+/// Upper bound of the sample frequency of receivers, in hertz
+pub const MAX_SAMPLE_FREQUENCY: f64 = 50.0;
+/// Default and upper bound of the delay searched between the transmitter and the receivers, in milliseconds
+pub const DEFAULT_MAX_LATENCY: i32 = 10_000;
+pub const MAX_LATENCY: i32 = 30_000;
+
+/// How the receiver output of a job is analysed
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct AnalysisSettings {
+    /// Samples read by each receiver in one second, as given to the receiver in the code of the job
+    pub sample_frequency: f64,
+    /// Value above which a sample is detected as an emit, the midpoint of the samples of each receiver if not given
+    pub threshold: Option<f64>,
+    #[serde(default = "default_max_latency")]
+    pub max_latency: i32,
+}
+
+fn default_max_latency() -> i32 {
+    DEFAULT_MAX_LATENCY
+}
+
+impl AnalysisSettings {
+    pub fn validate(&self) -> Result<(), ErrorMessage> {
+        let valid = self.sample_frequency > 0.0 &&
+            self.sample_frequency <= MAX_SAMPLE_FREQUENCY &&
+            self.threshold.iter().all(|threshold| threshold.is_finite()) &&
+            (0..=MAX_LATENCY).contains(&self.max_latency);
+
+        if valid {
+            Ok(())
+        } else {
+            Err(ErrorMessage::InvalidAnalysis)
+        }
+    }
+
+    /// Milliseconds between two consecutive samples
+    fn period(&self) -> f64 {
+        1000.0 / self.sample_frequency
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReceiverAnalysis {
+    pub samples: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub threshold: f64,
+    /// Delay between the transmitter and the receiver in milliseconds, None if no delay lines the samples up
+    pub latency: Option<f64>,
+    /// Symbol detected in each slot, None if no sample falls into the slot
+    pub detected: Vec<Option<bool>>,
+    pub errors: usize,
+    pub bit_error_rate: f64,
+}
+
+pub struct Analysis {
+    /// Symbol transmitted in each slot, true if any spray emits
+    pub transmitted: Vec<bool>,
+    pub receivers: Vec<ReceiverAnalysis>,
+}
+
+impl Analysis {
+    /// Receiver with the lowest bit error rate, the first one on ties
+    pub fn best(&self) -> Option<&ReceiverAnalysis> {
+        self.receivers.iter()
+            .fold(None, |best: Option<&ReceiverAnalysis>, receiver| match best {
+                Some(best) if best.bit_error_rate <= receiver.bit_error_rate => Some(best),
+                _ => Some(receiver)
+            })
+    }
+}
+
+/// Parses the output of the receiver into samples, each having one value per receiver.
+/// Samples are separated by new lines or closing brackets so that both `print(' '.join(rx))` and `print(rx, end='')`
+/// are understood. Parts that are not made of numbers only, like logs, or that have a different number of values than
+/// the first sample are skipped.
+pub fn parse_samples(output: &str) -> Vec<Vec<f64>> {
+    let mut samples: Vec<Vec<f64>> = Vec::new();
+
+    for part in output.split(['\n', ']']) {
+        let values = part
+            .split(|c: char| c.is_whitespace() || c == ',' || c == '[' || c == '\'' || c == '"')
+            .filter(|value| !value.is_empty())
+            .map(|value| value.parse::<f64>().ok().filter(|value| value.is_finite()))
+            .collect::<Option<Vec<f64>>>();
+
+        match values {
+            Some(values) if !values.is_empty() && samples.first().iter().all(|first| first.len() == values.len()) => {
+                samples.push(values)
+            }
+            _ => {}
+        }
+    }
+
+    samples
+}
+
+/// Detects the symbols of each slot in the samples of each receiver by on-off keying and compares them with the
+/// transmitted ones. The latency is the delay, up to the maximum one, at which the samples match the transmitted
+/// symbols best, symbols are detected after shifting the samples by it.
+pub fn analyze(slots: &[Slot], samples: &[Vec<f64>], settings: &AnalysisSettings) -> Result<Analysis, ErrorMessage> {
+    let receiver_count = samples.first().map_or(0, |sample| sample.len());
+
+    if slots.is_empty() || receiver_count == 0 {
+        return Err(ErrorMessage::InvalidAnalysis);
+    }
+
+    let transmitted = slots.iter()
+        .map(|slot| slot.sprays.iter().any(|spray| *spray))
+        .collect::<Vec<bool>>();
+
+    let period = settings.period();
+    let expected = expected_samples(slots, &transmitted, period);
+    let max_lag = (settings.max_latency as f64 / period).floor() as usize;
+
+    let receivers = (0..receiver_count)
+        .map(|receiver| {
+            let values = samples.iter().map(|sample| sample[receiver]).collect::<Vec<f64>>();
+
+            analyze_receiver(slots, &transmitted, &expected, &values, period, max_lag, settings.threshold)
+        })
+        .collect();
+
+    Ok(Analysis { transmitted, receivers })
+}
+
+/// Symbol transmitted at the time of each sample, from the start of the first slot to the end of the last one
+fn expected_samples(slots: &[Slot], transmitted: &[bool], period: f64) -> Vec<bool> {
+    let end = slots.last().map_or(0.0, |slot| (slot.start + slot.duration) as f64);
+    let mut expected = Vec::new();
+    let mut slot = 0;
+
+    while (expected.len() as f64) * period < end {
+        let time = expected.len() as f64 * period;
+
+        while (slots[slot].start + slots[slot].duration) as f64 <= time {
+            slot += 1;
+        }
+
+        expected.push(transmitted[slot]);
+    }
+
+    expected
+}
+
+fn analyze_receiver(
+    slots: &[Slot],
+    transmitted: &[bool],
+    expected: &[bool],
+    values: &[f64],
+    period: f64,
+    max_lag: usize,
+    threshold: Option<f64>,
+) -> ReceiverAnalysis {
+    let count = values.len() as f64;
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let mean = values.iter().sum::<f64>() / count;
+    let std_dev = (values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / count).sqrt();
+    let threshold = threshold.unwrap_or((min + max) / 2.0);
+
+    let received = values.iter().map(|value| *value > threshold).collect::<Vec<bool>>();
+
+    // fraction of the samples that disagree with the transmitted symbols when shifted by the lag
+    let lag = (0..=max_lag.min(received.len().saturating_sub(1)))
+        .filter_map(|lag| {
+            let compared = expected.len().min(received.len() - lag);
+
+            if compared == 0 {
+                return None;
+            }
+
+            let mismatches = expected.iter()
+                .zip(&received[lag..])
+                .filter(|(expected, received)| expected != received)
+                .count();
+
+            Some((lag, mismatches as f64 / compared as f64))
+        })
+        .fold(None, |best: Option<(usize, f64)>, (lag, score)| match best {
+            Some(best) if best.1 <= score => Some(best),
+            _ => Some((lag, score))
+        })
+        .map(|(lag, _)| lag);
+
+    let detected = slots.iter()
+        .map(|slot| {
+            let lag = lag?;
+            let first = (slot.start as f64 / period).ceil() as usize + lag;
+            let window = values.iter()
+                .enumerate()
+                .skip(first)
+                .take_while(|(index, _)| (((index - lag) as f64) * period) < (slot.start + slot.duration) as f64)
+                .map(|(_, value)| *value)
+                .collect::<Vec<f64>>();
+
+            if window.is_empty() {
+                None
+            } else {
+                Some(window.iter().sum::<f64>() / window.len() as f64 > threshold)
+            }
+        })
+        .collect::<Vec<Option<bool>>>();
+
+    let errors = detected.iter()
+        .zip(transmitted)
+        .filter(|(detected, transmitted)| **detected != Some(**transmitted))
+        .count();
+
+    ReceiverAnalysis {
+        samples: values.len(),
+        min,
+        max,
+        mean,
+        std_dev,
+        threshold,
+        latency: lag.map(|lag| lag as f64 * period),
+        detected,
+        errors,
+        bit_error_rate: errors as f64 / slots.len() as f64,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::state::Decoder;
+
+    use super::*;
+
+    fn settings() -> AnalysisSettings {
+        AnalysisSettings { sample_frequency: 10.0, threshold: None, max_latency: 1_000 }
+    }
+
+    #[test]
+    fn samples_are_parsed_from_lines_and_lists() {
+        assert_eq!(parse_samples("1 2\n[DEBUG receiver] started\n3,4\n5\n"), vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+        assert_eq!(parse_samples("['1', '2']['3', '4']"), vec![vec![1.0, 2.0], vec![3.0, 4.0]]);
+    }
+
+    #[test]
+    fn delayed_symbols_are_detected() {
+        let state = Decoder::decode("\nstart_delimiter\nemit\n10\n500\nwait\n500\nemit\n01\n500\nwait\n500\nend_delimiter\n").unwrap();
+
+        // one sample per 100 ms, 300 ms late
+        let mut values = vec![0.0; 3];
+        values.extend(vec![100.0; 5]);
+        values.extend(vec![0.0; 5]);
+        values.extend(vec![100.0; 5]);
+        values.extend(vec![0.0; 5]);
+        let samples = values.into_iter().map(|value| vec![value]).collect::<Vec<Vec<f64>>>();
+
+        let analysis = analyze(&state.slots(), &samples, &settings()).unwrap();
+        let receiver = &analysis.receivers[0];
+
+        assert_eq!(analysis.transmitted, vec![true, false, true, false]);
+        assert_eq!(receiver.latency, Some(300.0));
+        assert_eq!(receiver.detected, vec![Some(true), Some(false), Some(true), Some(false)]);
+        assert_eq!(receiver.errors, 0);
+        assert_eq!(receiver.threshold, 50.0);
+    }
+}
@@ -0,0 +1,255 @@
+use std::collections::HashSet;
+use std::io::{Cursor, Read, Write};
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use core::types::ModelId;
+
+use crate::ErrorMessage;
+use crate::models::artifact::JobArtifact;
+use crate::models::job::{FailureCause, JobStatus};
+use crate::parameters::ParameterDefinitions;
+
+/// Identifies the archives produced by the export of experiments
+pub const FORMAT: &str = "testbed-experiment";
+/// Incremented whenever the manifest changes in a way older versions can not read
+pub const FORMAT_VERSION: u32 = 1;
+/// Upper bound of jobs an archive can contain
+pub const MAX_JOBS: usize = 100;
+/// Upper bound of the size of an archive accepted by import, in bytes
+pub const MAX_SIZE: usize = 256 * 1024 * 1024;
+/// Upper bound of the decompressed size of the manifest and of all artifacts of an archive together, in bytes. Sizes
+/// in the zip headers are given by the uploader, hence the contents are measured while they are read.
+pub const MAX_CONTENT_SIZE: u64 = 512 * 1024 * 1024;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const CODE_FILE: &str = "main.py";
+
+/// Describes the content of an archive. Codes are kept as they are written by the user, not html encoded.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Manifest {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: NaiveDateTime,
+    pub experiment: ExperimentEntry,
+    pub jobs: Vec<JobEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExperimentEntry {
+    pub name: String,
+    pub code: String,
+    pub parameters: ParameterDefinitions,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobEntry {
+    /// Id of the job in the deployment it is exported from, only used to locate its files in the archive
+    pub id: ModelId,
+    /// Name of the controller the job is run on
+    pub controller: String,
+    pub status: JobStatus,
+    pub code: String,
+    pub parameters: Value,
+    pub priority: i32,
+    pub created_at: NaiveDateTime,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+    pub failure_kind: Option<String>,
+    pub failure_cause: Option<FailureCause>,
+    pub artifacts: Vec<ArtifactEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactEntry {
+    pub name: String,
+    pub content_type: String,
+    pub size: i64,
+}
+
+impl Manifest {
+    pub fn new(exported_at: NaiveDateTime, experiment: ExperimentEntry, jobs: Vec<JobEntry>) -> Self {
+        Manifest {
+            format: String::from(FORMAT),
+            version: FORMAT_VERSION,
+            exported_at,
+            experiment,
+            jobs,
+        }
+    }
+
+    /// Checks what import relies on, the rest of the manifest is taken as it is
+    pub fn validate(&self) -> Result<(), ErrorMessage> {
+        if self.format != FORMAT || self.version > FORMAT_VERSION || self.jobs.len() > MAX_JOBS {
+            return Err(ErrorMessage::InvalidArchive);
+        }
+
+        self.experiment.parameters.validate()?;
+
+        for job in &self.jobs {
+            if !job.status.is_finished() {
+                return Err(ErrorMessage::InvalidArchive);
+            }
+
+            let mut names = HashSet::with_capacity(job.artifacts.len());
+
+            if job.artifacts.iter().any(|artifact| !JobArtifact::is_valid_name(artifact.name.as_str()) || !names.insert(artifact.name.as_str())) {
+                return Err(ErrorMessage::InvalidArchive);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub fn artifact_path(job_id: ModelId, name: &str) -> String {
+    format!("jobs/{}/artifacts/{}", job_id, name)
+}
+
+/// Writes the manifest together with the codes and the given artifacts, which are keyed by their paths
+pub fn write(manifest: &Manifest, artifacts: Vec<(String, Vec<u8>)>) -> zip::result::ZipResult<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file(MANIFEST_FILE, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(manifest).unwrap())?;
+
+    zip.start_file(format!("experiment/{}", CODE_FILE), options)?;
+    zip.write_all(manifest.experiment.code.as_bytes())?;
+
+    for job in &manifest.jobs {
+        zip.start_file(format!("jobs/{}/{}", job.id, CODE_FILE), options)?;
+        zip.write_all(job.code.as_bytes())?;
+    }
+
+    for (path, content) in artifacts {
+        zip.start_file(path, options)?;
+        zip.write_all(&content)?;
+    }
+
+    Ok(zip.finish()?.into_inner())
+}
+
+pub struct Archive {
+    pub manifest: Manifest,
+    zip: ZipArchive<Cursor<Vec<u8>>>,
+    // decompressed bytes that can still be read
+    remaining: u64,
+}
+
+impl Archive {
+    pub fn read(content: Vec<u8>) -> Result<Self, ErrorMessage> {
+        let mut zip = ZipArchive::new(Cursor::new(content))
+            .map_err(|_| ErrorMessage::InvalidArchive)?;
+
+        let mut remaining = MAX_CONTENT_SIZE;
+
+        let manifest = {
+            let file = zip.by_name(MANIFEST_FILE)
+                .map_err(|_| ErrorMessage::InvalidArchive)?;
+
+            serde_json::from_slice::<Manifest>(&read_limited(file, &mut remaining)?)
+                .map_err(|_| ErrorMessage::InvalidArchive)?
+        };
+
+        manifest.validate()?;
+
+        Ok(Archive { manifest, zip, remaining })
+    }
+
+    pub fn artifact(&mut self, job_id: ModelId, name: &str) -> Result<Vec<u8>, ErrorMessage> {
+        let file = self.zip.by_name(artifact_path(job_id, name).as_str())
+            .map_err(|_| ErrorMessage::InvalidArchive)?;
+
+        read_limited(file, &mut self.remaining)
+    }
+}
+
+/// Reads the file unless it is larger than the remaining bytes, which are reduced by its size
+fn read_limited<R: Read>(file: R, remaining: &mut u64) -> Result<Vec<u8>, ErrorMessage> {
+    let mut content = Vec::new();
+
+    file.take(*remaining + 1)
+        .read_to_end(&mut content)
+        .map_err(|_| ErrorMessage::InvalidArchive)?;
+
+    if content.len() as u64 > *remaining {
+        return Err(ErrorMessage::InvalidArchive);
+    }
+
+    *remaining -= content.len() as u64;
+
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(status: JobStatus) -> Manifest {
+        let at = NaiveDateTime::from_timestamp(0, 0);
+
+        Manifest::new(
+            at,
+            ExperimentEntry { name: String::from("normal"), code: String::from("print('hi')"), parameters: ParameterDefinitions::default(), created_at: at },
+            vec![JobEntry {
+                id: 7,
+                controller: String::from("NanoNetworking Testbed"),
+                status,
+                code: String::from("print('hi')"),
+                parameters: Value::Object(Default::default()),
+                priority: 0,
+                created_at: at,
+                started_at: None,
+                finished_at: None,
+                failure_kind: None,
+                failure_cause: None,
+                artifacts: vec![ArtifactEntry { name: String::from("output.txt"), content_type: String::from("text/plain"), size: 2 }],
+            }],
+        )
+    }
+
+    #[test]
+    fn archive_is_read_back() {
+        let content = write(&manifest(JobStatus::Successful), vec![(artifact_path(7, "output.txt"), b"42".to_vec())]).unwrap();
+
+        let mut archive = Archive::read(content).unwrap();
+
+        assert_eq!(archive.manifest.experiment.name, "normal");
+        assert_eq!(archive.artifact(7, "output.txt").unwrap(), b"42".to_vec());
+        assert!(archive.artifact(7, "missing.txt").is_err());
+    }
+
+    #[test]
+    fn contents_larger_than_the_remaining_size_are_rejected() {
+        let mut remaining = 4;
+
+        assert_eq!(read_limited(&b"abc"[..], &mut remaining).unwrap(), b"abc".to_vec());
+        assert_eq!(remaining, 1);
+        assert!(read_limited(&b"de"[..], &mut remaining).is_err());
+    }
+
+    #[test]
+    fn duplicate_artifact_names_are_rejected() {
+        let mut manifest = manifest(JobStatus::Successful);
+        manifest.jobs[0].artifacts.push(ArtifactEntry { name: String::from("output.txt"), content_type: String::from("text/plain"), size: 2 });
+
+        assert!(manifest.validate().is_err());
+    }
+
+    #[test]
+    fn unfinished_jobs_are_rejected() {
+        let content = write(&manifest(JobStatus::Running), vec![]).unwrap();
+
+        assert!(Archive::read(content).is_err());
+    }
+}
@@ -4,12 +4,15 @@ use core::types::ModelId;
 use shared::ControllerState;
 
 use crate::connection::session::Session;
+use crate::models::job::Failure;
+use crate::parameters::ParameterValues;
 
 #[derive(Message)]
 #[rtype(result = "()")]
 pub struct RunMessage {
     pub job_id: ModelId,
     pub code: String,
+    pub parameters: ParameterValues,
 }
 
 #[derive(Message)]
@@ -32,6 +35,7 @@ pub struct RunResultMessage {
     pub controller_id: ModelId,
     pub job_id: ModelId,
     pub successful: bool,
+    pub failure: Option<Failure>,
 }
 
 #[derive(Message)]
@@ -1,23 +1,28 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
 use actix::prelude::*;
 use actix_web::web;
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
 use diesel::r2d2::{ConnectionManager, PooledConnection};
 use log::{error, info, warn};
 use serde::Serialize;
 
 use core::db::DieselEnum;
-use core::schema::{experiments, jobs, slots};
+use core::schema::{experiments, jobs};
 use core::types::{DBPool, ModelId};
 use service::{Notification, NotificationKind, NotificationMessage, NotificationServer};
 use shared::ControllerState;
+use webhook::{Event, EventKind, WebhookServer};
 
 use crate::connection::messages::{DisconnectServerMessage, JoinServerMessage, RunMessage, RunResultMessage, UpdateControllerValue};
 use crate::connection::ReceiverValues;
 use crate::connection::session::Session;
-use crate::models::job::JobStatus;
+use crate::models::job::{self, Failure, FailureCause, JobStatus};
+use crate::parameters::ParameterValues;
+use crate::scheduler;
+use crate::watchdog::{self, StaleReason};
 
 pub use crate::connection::messages::AbortRunningJob;
 
@@ -31,6 +36,7 @@ struct ConnectedController {
 #[rtype(result = "()")]
 pub struct RunExperiment {
     pub code: String,
+    pub parameters: ParameterValues,
     pub job_id: ModelId,
     pub controller_id: ModelId,
     pub user_id: ModelId,
@@ -40,45 +46,135 @@ pub struct ExperimentServer {
     pool: DBPool,
     controllers: HashMap<ModelId, ConnectedController>,
     notification: Addr<NotificationServer>,
+    webhook: Addr<WebhookServer>,
+    started_at: NaiveDateTime,
+    // time a job of an experiment without any successful job can run before it is timed out
+    max_run_time: chrono::Duration,
+    // controllers are removed once they connect again
+    disconnected_since: HashMap<ModelId, NaiveDateTime>,
 }
 
 impl ExperimentServer {
-    pub fn new(pool: DBPool, notification: Addr<NotificationServer>) -> Self {
+    pub fn new(pool: DBPool, notification: Addr<NotificationServer>, webhook: Addr<WebhookServer>, max_run_time: Duration) -> Self {
         ExperimentServer {
             pool,
             controllers: HashMap::new(),
             notification,
+            webhook,
+            started_at: Utc::now().naive_utc(),
+            max_run_time: chrono::Duration::from_std(max_run_time).expect("max run time is out of range"),
+            disconnected_since: HashMap::new(),
         }
     }
 
+    /// Fails the jobs that are stuck in Running, either because they took too long or their controller is gone
+    fn check_running_jobs(&mut self, ctx: &mut <Self as Actor>::Context) {
+        let conn = self.pool.get().unwrap();
+        let started_at = self.started_at;
+        let connected = self.controllers.keys().cloned().collect::<Vec<ModelId>>();
+        let disconnected_since = self.disconnected_since.clone();
+        let max_run_time = self.max_run_time;
+
+        async move {
+            web::block(move || -> Result<Vec<watchdog::StaleJob>, diesel::result::Error> {
+                let now = Utc::now().naive_utc();
+                let stale_jobs = watchdog::stale_jobs(&conn, now, started_at, &connected, &disconnected_since, max_run_time)?;
+                let mut failed = Vec::new();
+
+                for reason in &[StaleReason::TimedOut, StaleReason::ControllerDisconnected] {
+                    let job_ids = stale_jobs.iter()
+                        .filter(|job| job.reason == *reason)
+                        .map(|job| job.job_id)
+                        .collect::<Vec<ModelId>>();
+
+                    if !job_ids.is_empty() {
+                        failed.extend(watchdog::fail_jobs(&conn, now, job_ids, reason.status(), reason.failure())?);
+                    }
+                }
+
+                Ok(stale_jobs.into_iter()
+                    .filter(|job| failed.contains(&job.job_id))
+                    .collect())
+            })
+                .await
+        }
+            .into_actor(self)
+            .then(|res, act, ctx| {
+                match res {
+                    Ok(stale_jobs) => for job in stale_jobs {
+                        warn!("job {} is ended by the watchdog, {:?}", job.job_id, job.reason);
+
+                        // controller is still running the job, stop it
+                        if job.reason == StaleReason::TimedOut {
+                            if let Some(controller) = act.controllers.get(&job.controller_id) {
+                                controller.session.do_send(AbortRunningJob { job_id: job.job_id, controller_id: job.controller_id });
+                            }
+                        }
+
+                        Self::send_status_notification(act.notification.clone(), act.webhook.clone(), job.user_id, job.job_id, job.reason.status())
+                            .into_actor(act)
+                            .spawn(ctx);
+                    },
+                    Err(e) => error!("Error while checking running jobs, {:?}", e)
+                }
+
+                fut::ready(())
+            })
+            .spawn(ctx);
+    }
+
+    /// Claims the first job in the queue of the controller. The job is set Running before it is sent, so that a job
+    /// cancelled meanwhile is skipped rather than run.
     async fn try_next_job(conn: PooledConnection<ConnectionManager<PgConnection>>, controller_id: ModelId) -> Option<RunExperiment> {
-        web::block(move || {
+        web::block(move || -> QueryResult<RunExperiment> {
             let now = Utc::now().naive_utc();
 
-            let slot_owner_id = slots::table
-                .filter(slots::start_at.le(&now).and(slots::end_at.ge(&now)))
-                .select(slots::user_id)
-                .first::<ModelId>(&conn)?;
-
-            jobs::table
-                .inner_join(experiments::table)
-                .filter(jobs::status.eq(JobStatus::Pending.value()))
-                .filter(jobs::controller_id.eq(controller_id))
-                .filter(experiments::user_id.eq(slot_owner_id))
-                .select((experiments::user_id, jobs::id, jobs::code))
-                .first::<(ModelId, ModelId, String)>(&conn)
-                .map(|job| RunExperiment {
-                    code: job.2,
-                    job_id: job.1,
-                    controller_id,
-                    user_id: job.0,
-                })
+            loop {
+                let job_id = scheduler::next_job(&conn, controller_id, now)?
+                    .ok_or(diesel::NotFound)?;
+
+                let claimed = diesel::update(jobs::table.find(job_id).filter(jobs::status.eq(JobStatus::Pending.value())))
+                    .set((jobs::status.eq(JobStatus::Running.value()), jobs::started_at.eq(now)))
+                    .execute(&conn)?;
+
+                if claimed == 0 {
+                    continue;
+                }
+
+                return jobs::table
+                    .inner_join(experiments::table)
+                    .filter(jobs::id.eq(job_id))
+                    .select((experiments::user_id, jobs::id, jobs::code, jobs::parameters))
+                    .first::<(ModelId, ModelId, String, serde_json::Value)>(&conn)
+                    .map(|job| RunExperiment {
+                        code: job.2,
+                        parameters: job.3.as_object().cloned().unwrap_or_default(),
+                        job_id: job.1,
+                        controller_id,
+                        user_id: job.0,
+                    });
+            }
         })
             .await
             .ok()
     }
 
-    async fn send_status_notification(addr: Addr<NotificationServer>, user_id: ModelId, job_id: ModelId, status: JobStatus) {
+    async fn send_status_notification(
+        addr: Addr<NotificationServer>,
+        webhook: Addr<WebhookServer>,
+        user_id: ModelId,
+        job_id: ModelId,
+        status: JobStatus,
+    ) {
+        webhook.do_send(Event {
+            user_id,
+            kind: EventKind::JobStatus,
+            data: JobUpdate {
+                job_id,
+                status: status.clone(),
+            },
+        });
+
         let res = addr.send(Notification {
             user_id,
             message: NotificationMessage {
@@ -117,6 +213,7 @@ impl ExperimentServer {
                 job_id: experiment.job_id,
                 // We have to decode the code in order to replace encoded html characters like '<' char
                 code: core::decode_html(experiment.code.as_str()).unwrap(),
+                parameters: experiment.parameters,
             })
                 .await?;
 
@@ -132,13 +229,16 @@ impl ExperimentServer {
                     }
                 };
 
-                Self::send_status_notification(act.notification.clone(), user_id, job_id, status.clone())
+                Self::send_status_notification(act.notification.clone(), act.webhook.clone(), user_id, job_id, status.clone())
                     .into_actor(act)
                     .spawn(ctx);
 
-                Self::update_job(act.pool.get().unwrap(), job_id, status)
-                    .into_actor(act)
-                    .spawn(ctx);
+                // the job is already Running since it is claimed
+                if status == JobStatus::Failed {
+                    Self::update_job(act.pool.get().unwrap(), job_id, status)
+                        .into_actor(act)
+                        .spawn(ctx);
+                }
 
                 fut::ready(())
             })
@@ -147,12 +247,61 @@ impl ExperimentServer {
         Ok(())
     }
 
+    /// Runs the first job in the queue of the controller, if there is any
+    fn run_next_job(&mut self, controller_id: ModelId, ctx: &mut <Self as Actor>::Context) {
+        let conn = self.pool.get().unwrap();
+
+        async move {
+            Self::try_next_job(conn, controller_id)
+                .await
+        }
+            .into_actor(self)
+            .then(move |res: Option<RunExperiment>, act: &mut Self, ctx: _| {
+                if let Some(run) = res {
+                    act.dispatch(run, ctx);
+                }
+
+                fut::ready(())
+            })
+            .spawn(ctx);
+    }
+
+    /// Runs the claimed job, it is given back to the queue if the controller cannot take it, such as when it is
+    /// disconnected meanwhile
+    fn dispatch(&mut self, experiment: RunExperiment, ctx: &mut <Self as Actor>::Context) {
+        let job_id = experiment.job_id;
+
+        if let Err(e) = self.run(experiment, ctx) {
+            error!("Failed to run experiment, {}", e);
+
+            Self::update_job(self.pool.get().unwrap(), job_id, JobStatus::Pending)
+                .into_actor(self)
+                .spawn(ctx);
+        }
+    }
+
+    /// Gives the claimed job back to the queue, or fails it. Only a job that is still Running is changed since it can
+    /// be aborted meanwhile.
     async fn update_job(conn: PooledConnection<ConnectionManager<PgConnection>>, job_id: ModelId, status: JobStatus) {
-        let res = web::block(move ||
-            diesel::update(jobs::table.find(job_id))
-                .set(jobs::status.eq(status.value()))
-                .execute(&conn)
-        )
+        let res = web::block(move || {
+            let job = jobs::table
+                .find(job_id)
+                .filter(jobs::status.eq(JobStatus::Running.value()));
+
+            match status {
+                JobStatus::Pending => diesel::update(job)
+                    .set((jobs::status.eq(status.value()), jobs::started_at.eq(None::<NaiveDateTime>)))
+                    .execute(&conn),
+                _ => diesel::update(job)
+                    .set((
+                        jobs::status.eq(status.value()),
+                        jobs::finished_at.eq(Utc::now().naive_utc()),
+                        jobs::failure_kind.eq(job::DISPATCH_FAILED),
+                        jobs::failure_cause.eq(FailureCause::Internal.value()),
+                    ))
+                    .execute(&conn)
+            }
+        })
             .await;
 
         if let Err(e) = res {
@@ -164,8 +313,10 @@ impl ExperimentServer {
 impl Actor for ExperimentServer {
     type Context = Context<Self>;
 
-    fn started(&mut self, _: &mut Self::Context) {
+    fn started(&mut self, ctx: &mut Self::Context) {
         info!("ExperimentServer is started!");
+
+        ctx.run_interval(Duration::from_secs(watchdog::CHECK_INTERVAL), |act, ctx| act.check_running_jobs(ctx));
     }
 
     fn stopped(&mut self, _: &mut Self::Context) {
@@ -179,8 +330,11 @@ impl Handler<RunExperiment> for ExperimentServer {
     fn handle(&mut self, msg: RunExperiment, ctx: &mut Self::Context) {
         info!("Job with id {} received ", msg.job_id);
 
-        if let Err(e) = self.run(msg, ctx) {
-            info!("Error while trying to run experiment, {:?}", e);
+        // the received job waits in the queue like the others, an idle controller takes the first job of the queue
+        match self.controllers.get(&msg.controller_id) {
+            Some(ConnectedController { state: ControllerState::Idle, .. }) => self.run_next_job(msg.controller_id, ctx),
+            Some(_) => info!("Controller {} is running a job, job {} is queued", msg.controller_id, msg.job_id),
+            None => info!("Controller {} is not connected, job {} is queued", msg.controller_id, msg.job_id)
         }
     }
 }
@@ -196,31 +350,54 @@ impl Handler<JoinServerMessage> for ExperimentServer {
             receiver_values: None,
         });
 
-        if let ControllerState::Idle = msg.state {
-            // copy some necessary vals
-            let controller_id = msg.controller_id;
+        self.disconnected_since.remove(&msg.controller_id);
 
-           // try to run a job
-            let conn = self.pool.get().unwrap();
-            async move {
-                Self::try_next_job(conn, controller_id)
-                    .await
-            }
-                .into_actor(self)
-                .then(move |res: Option<RunExperiment>, act: &mut Self, ctx: _| {
-                    if let Some(run) = res {
-                        // maybe controller disconnected, so check it
-                        if act.controllers.contains_key(&controller_id) {
-                            if let Err(e) = act.run(run, ctx) {
-                                error!("Unexpectedly Controller is in running state, {}", e)
-                            }
-                        }
+        // copy some necessary vals
+        let controller_id = msg.controller_id;
+        let running_job_id = match msg.state {
+            ControllerState::Running(job_id) => Some(job_id),
+            ControllerState::Idle => None,
+        };
+        let conn = self.pool.get().unwrap();
+        let notification_server = self.notification.clone();
+        let webhook_server = self.webhook.clone();
+
+        async move {
+            // jobs that are left Running while the controller was away are not going to report back
+            let conn = match web::block(move || {
+                watchdog::reconcile(&conn, Utc::now().naive_utc(), controller_id, running_job_id)
+                    .map(|lost| (lost, conn))
+            }).await {
+                Ok((lost, conn)) => {
+                    for (job_id, user_id) in lost {
+                        warn!("job {} is lost by controller {}", job_id, controller_id);
+                        Self::send_status_notification(notification_server.clone(), webhook_server.clone(), user_id, job_id, JobStatus::Failed)
+                            .await;
                     }
 
-                    fut::ready(())
-                })
-                .spawn(ctx);
-       }
+                    conn
+                }
+                Err(e) => {
+                    error!("Error while reconciling jobs of controller, {:?}", e);
+                    return None;
+                }
+            };
+
+            match running_job_id {
+                // try to run a job
+                None => Self::try_next_job(conn, controller_id).await,
+                Some(_) => None
+            }
+        }
+            .into_actor(self)
+            .then(move |res: Option<RunExperiment>, act: &mut Self, ctx: _| {
+                if let Some(run) = res {
+                    act.dispatch(run, ctx);
+                }
+
+                fut::ready(())
+            })
+            .spawn(ctx);
     }
 }
 
@@ -229,6 +406,7 @@ impl Handler<DisconnectServerMessage> for ExperimentServer {
 
     fn handle(&mut self, msg: DisconnectServerMessage, _: &mut Self::Context) {
         self.controllers.remove(&msg.controller_id);
+        self.disconnected_since.insert(msg.controller_id, Utc::now().naive_utc());
     }
 }
 
@@ -238,6 +416,12 @@ impl Handler<RunResultMessage> for ExperimentServer {
     fn handle(&mut self, msg: RunResultMessage, ctx: &mut Self::Context) {
         info!("got result {} id {}", msg.successful, msg.job_id);
 
+        let status = match (msg.successful, &msg.failure) {
+            (true, _) => JobStatus::Successful,
+            (false, Some(Failure { cause: FailureCause::Abort, .. })) => JobStatus::Aborted,
+            (false, _) => JobStatus::Failed,
+        };
+
         if let Some(controller) = self.controllers.get_mut(&msg.controller_id) {
             match controller.state {
                 ControllerState::Running(job_id) if job_id != msg.job_id => {
@@ -248,59 +432,49 @@ impl Handler<RunResultMessage> for ExperimentServer {
 
             controller.state = ControllerState::Idle;
 
-            let conn = self.pool.get().unwrap();
-            let controller_id = msg.controller_id;
-
-            async move {
-                Self::try_next_job(conn, controller_id)
-                    .await
-            }
-                .into_actor(self)
-                .then(move |res: Option<RunExperiment>, act: &mut Self, ctx: _| {
-                    if let Some(run) = res {
-                        // maybe controller disconnected, so check it
-                        if act.controllers.contains_key(&controller_id) {
-                            if let Err(e) = act.run(run, ctx) {
-                                error!("Failed to run experiment, {}", e);
-                            }
-                        }
-                    }
-
-                    fut::ready(())
-                })
-                .spawn(ctx);
+            self.run_next_job(msg.controller_id, ctx);
         }
 
         let conn = self.pool.get().unwrap();
         let notification_server = self.notification.clone();
-
-        let status = match msg.successful {
-            true => JobStatus::Successful,
-            false => JobStatus::Failed,
-        };
+        let webhook_server = self.webhook.clone();
 
         async move {
             // clone required vals
             let job_id = msg.job_id;
             let status_clone = status.clone();
 
+            let failure = if msg.successful { None } else { msg.failure };
+
             let res = web::block(move || {
-                diesel::update(jobs::table.find(msg.job_id))
-                    .set(jobs::status.eq(status.value()))
+                // jobs that are already ended by the watchdog are left as they are
+                let updated = diesel::update(jobs::table.find(job_id).filter(jobs::status.eq(JobStatus::Running.value())))
+                    .set((
+                        jobs::status.eq(status.value()),
+                        jobs::finished_at.eq(Utc::now().naive_utc()),
+                        jobs::failure_kind.eq(failure.as_ref().map(|failure| failure.kind.clone())),
+                        jobs::failure_cause.eq(failure.map(|failure| failure.cause.value())),
+                    ))
                     .execute(&conn)?;
 
+                if updated == 0 {
+                    return Ok(None);
+                }
+
                 experiments::table
                     .inner_join(jobs::table)
-                    .filter(jobs::id.eq(msg.job_id))
+                    .filter(jobs::id.eq(job_id))
                     .select(experiments::user_id)
                     .first::<ModelId>(&conn)
+                    .map(Some)
             })
                 .await;
 
             // try to notify the user
             match res {
-                Ok(user_id) => Self::send_status_notification(notification_server, user_id, job_id, status_clone)
+                Ok(Some(user_id)) => Self::send_status_notification(notification_server, webhook_server, user_id, job_id, status_clone)
                     .await,
+                Ok(None) => warn!("result of job {} is received after it is ended", job_id),
                 Err(e) => error!("Error while updating job with run result, {:?}", e)
             }
         }
@@ -333,9 +507,10 @@ impl Handler<AbortRunningJob> for ExperimentServer {
         }
     }
 }
+/// Status change of a job, sent to its owner as a notification and a webhook event
 #[derive(Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
-struct JobUpdate {
-    job_id: ModelId,
-    status: JobStatus,
+pub struct JobUpdate {
+    pub job_id: ModelId,
+    pub status: JobStatus,
 }
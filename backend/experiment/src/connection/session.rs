@@ -9,6 +9,7 @@ use shared::websocket_messages::{client, server};
 
 use crate::connection::messages::{DisconnectServerMessage, JoinServerMessage, RunMessage, RunResultMessage, UpdateControllerValue, AbortRunningJob};
 use crate::connection::server::ExperimentServer;
+use crate::models::job::Failure;
 
 pub struct Session {
     experiment_server: Addr<ExperimentServer>,
@@ -49,6 +50,10 @@ impl Session {
                             job_id: run_result.data.job_id,
                             controller_id: self.controller_id,
                             successful: run_result.data.successful,
+                            failure: run_result.data.failure.map(|failure| Failure {
+                                kind: failure.kind,
+                                cause: failure.cause.into(),
+                            }),
                         };
 
                         async move {
@@ -140,7 +145,7 @@ impl Handler<RunMessage> for Session {
 
         ctx.text(serde_json::to_string(&client::SocketMessage {
             kind: client::SocketMessageKind::RunExperiment,
-            data: client::RunExperiment { job_id: msg.job_id, code: msg.code },
+            data: client::RunExperiment { job_id: msg.job_id, code: msg.code, parameters: msg.parameters },
         }).unwrap());
     }
 }
@@ -0,0 +1,131 @@
+use std::fs;
+use std::path::Path;
+
+use diesel::prelude::*;
+use serde::Deserialize;
+
+use core::sanitized::Sanitize;
+use core::schema::{template_versions, templates};
+use core::types::ModelId;
+use derive::Sanitize;
+
+use crate::models::template::{Template, TemplateVersion, TemplateWithVersion};
+use crate::parameters::ParameterDefinitions;
+
+const TEMPLATE_FILE: &str = "template.json";
+const CODE_FILE: &str = "main.py";
+
+/// Metadata of a template kept in the templates directory, next to its code in main.py
+#[derive(Deserialize, Sanitize)]
+struct TemplateFile {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    parameters: ParameterDefinitions,
+}
+
+pub fn latest_version(conn: &PgConnection, template_id: ModelId) -> QueryResult<TemplateVersion> {
+    template_versions::table
+        .filter(template_versions::template_id.eq(template_id))
+        .order_by(template_versions::version.desc())
+        .first::<TemplateVersion>(conn)
+}
+
+/// Pairs the templates with their latest versions, templates without any version are left out
+pub fn with_latest_versions(conn: &PgConnection, templates: Vec<Template>) -> QueryResult<Vec<TemplateWithVersion>> {
+    let mut versions = template_versions::table
+        .filter(template_versions::template_id.eq_any(templates.iter().map(|template| template.id).collect::<Vec<ModelId>>()))
+        .order_by((template_versions::template_id, template_versions::version.desc()))
+        .distinct_on(template_versions::template_id)
+        .load::<TemplateVersion>(conn)?;
+
+    Ok(templates.into_iter()
+        .filter_map(|template| {
+            let index = versions.iter().position(|version| version.template_id == template.id)?;
+
+            Some(TemplateWithVersion {
+                template,
+                latest_version: versions.swap_remove(index),
+            })
+        })
+        .collect())
+}
+
+/// Adds a new version unless the code and parameters are the same as the latest version
+pub fn add_version(
+    conn: &PgConnection,
+    template_id: ModelId,
+    code: String,
+    parameters: ParameterDefinitions,
+) -> QueryResult<TemplateVersion> {
+    let latest = latest_version(conn, template_id).optional()?;
+    let parameters = serde_json::to_value(parameters).unwrap();
+
+    let version = match latest {
+        Some(latest) if latest.code == code && serde_json::to_value(&latest.parameters).unwrap() == parameters => {
+            return Ok(latest);
+        }
+        Some(latest) => latest.version + 1,
+        None => 1
+    };
+
+    diesel::insert_into(template_versions::table)
+        .values((
+            template_versions::template_id.eq(template_id),
+            template_versions::version.eq(version),
+            template_versions::code.eq(code),
+            template_versions::parameters.eq(parameters),
+        ))
+        .get_result::<TemplateVersion>(conn)
+}
+
+/// Imports the templates kept in the directory, one sub directory per template containing template.json and main.py.
+/// Templates are matched by name, a new version is added whenever the code or parameters of a template change.
+/// Returns the number of templates imported.
+pub fn import_templates<P: AsRef<Path>>(conn: &PgConnection, dir: P) -> Result<usize, String> {
+    let mut dirs = fs::read_dir(dir.as_ref())
+        .map_err(|e| format!("{}: {}", dir.as_ref().display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir())
+        .collect::<Vec<_>>();
+    dirs.sort();
+
+    for path in dirs.iter() {
+        import_template(conn, path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    }
+
+    Ok(dirs.len())
+}
+
+fn import_template(conn: &PgConnection, path: &Path) -> Result<(), String> {
+    let file = fs::read_to_string(path.join(TEMPLATE_FILE)).map_err(|e| e.to_string())?;
+    let file = serde_json::from_str::<TemplateFile>(&file).map_err(|e| e.to_string())?.sanitize();
+    let code = fs::read_to_string(path.join(CODE_FILE)).map_err(|e| e.to_string())?.sanitize();
+
+    file.parameters.validate().map_err(|e| format!("{:?}", e))?;
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        let template_id = diesel::insert_into(templates::table)
+            .values((
+                templates::name.eq(&file.name),
+                templates::description.eq(&file.description),
+                templates::tags.eq(&file.tags),
+            ))
+            .on_conflict(templates::name)
+            .do_update()
+            .set((
+                templates::description.eq(&file.description),
+                templates::tags.eq(&file.tags),
+            ))
+            .returning(templates::id)
+            .get_result::<ModelId>(conn)?;
+
+        add_version(conn, template_id, code, file.parameters)?;
+
+        Ok(())
+    })
+        .map_err(|e| e.to_string())
+}
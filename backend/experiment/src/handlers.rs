@@ -1,34 +1,53 @@
 use actix::Addr;
 use actix_web::{delete, get, post, put, web, web::Json, HttpRequest, HttpResponse};
 use actix_web_actors::ws;
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use diesel::prelude::*;
 use log::error;
-use serde_json::json;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Value};
 
+use core::client_ip::ClientIp;
 use core::db::DieselEnum;
 use core::error::ErrorMessaging;
-use core::models::paginate::{CountStarOver, Paginate, Pagination, PaginationRequest};
+use core::models::audit::{self, AuditAction, AuditEntry, TargetKind};
+use core::models::filter::{text_search, DateRangeRequest, FilterDateRange, Sort, SortRequest};
+use core::models::paginate::{self, CountStarOver, Cursor, CursorPagination, KeysetPaginate, Page, Paginate, Pagination, PaginationRequest};
 use core::responses::{SuccessResponse, TokenResponse};
 use core::sanitized::SanitizedJson;
-use core::schema::{experiments, jobs, controllers, slots};
+use core::schema::{experiments, jobs, controllers, slots, sweeps};
 use core::types::{DBPool, DefaultResponse, ModelId, Result};
 use core::utils::Hash;
 use core::ErrorMessage as CoreErrorMessage;
+use derive::operation;
 use shared::{JoinServerRequest, ControllerState};
+use service::{Notification, NotificationKind, NotificationMessage, Servers};
 use user::models::user::User;
+use webhook::{Event, EventKind, WebhookServer};
 
-use crate::connection::server::{ExperimentServer, RunExperiment, AbortRunningJob};
+use crate::connection::server::{ExperimentServer, JobUpdate, RunExperiment, AbortRunningJob};
 use crate::connection::session::Session;
+use crate::access::{check_experiment_access, check_job_access, Access};
 use crate::connection::ReceiverValues;
 use crate::models::experiment::{Experiment, SlimExperiment, SLIM_EXPERIMENT_COLUMNS};
-use crate::models::job::{Job, JobStatus, SlimJob, SLIM_JOB_COLUMNS};
+use crate::models::job::{self, FailureCause, Job, JobStatus, SlimJob, SLIM_JOB_COLUMNS};
 use crate::models::controller::{Controller, ControllerToken, SlimController, SLIM_CONTROLLER_COLUMNS};
-use crate::requests::{ExperimentCodeRequest, ExperimentNameRequest};
+use crate::models::sweep::{Sweep, SweepSummary, SweepWithJobs};
+use crate::requests::{
+    ExperimentCodeRequest, ExperimentFilterRequest, ExperimentNameRequest, ExperimentParametersRequest, ExperimentSortField,
+    JobFilterRequest, JobPriorityRequest, JobSortField, RunExperimentRequest,
+};
+use crate::scheduler::{self, QueueEntry, QueuePosition, MAX_USER_PRIORITY};
 use crate::ErrorMessage;
 
+pub mod analysis;
+pub mod archive;
+pub mod share;
 pub mod storage;
+pub mod template;
 
+#[operation]
 #[get("ws")]
 pub async fn join_server(
     pool: web::Data<DBPool>,
@@ -71,6 +90,7 @@ pub async fn join_server(
     .map_err(|_| Box::new(CoreErrorMessage::WebSocketConnectionError) as Box<dyn ErrorMessaging>)
 }
 
+#[operation]
 #[get("controllers")]
 pub async fn fetch_controllers(pool: web::Data<DBPool>) -> Result<Json<Vec<SlimController>>> {
     let conn = pool.get().unwrap();
@@ -85,6 +105,7 @@ pub async fn fetch_controllers(pool: web::Data<DBPool>) -> Result<Json<Vec<SlimC
     Ok(Json(controllers))
 }
 
+#[operation]
 #[get("controller/{id}")]
 pub async fn fetch_controller(
     pool: web::Data<DBPool>,
@@ -103,6 +124,7 @@ pub async fn fetch_controller(
     Ok(Json(controller))
 }
 
+#[operation]
 #[get("controller/{id}/values")]
 pub async fn controller_receiver_values(
     experiment_server: web::Data<Addr<ExperimentServer>>,
@@ -121,28 +143,81 @@ pub async fn controller_receiver_values(
     Ok(HttpResponse::Ok().json(json!({ "values": values })))
 }
 
+#[operation]
 #[get("experiments")]
 pub async fn fetch_experiments(
     pool: web::Data<DBPool>,
     user: User,
     pagination: web::Query<PaginationRequest>,
-) -> Result<Json<Pagination<SlimExperiment>>> {
+    filter: web::Query<ExperimentFilterRequest>,
+    range: web::Query<DateRangeRequest>,
+    sort: web::Query<SortRequest<ExperimentSortField>>,
+) -> Result<Json<Page<SlimExperiment>>> {
     let conn = pool.get().unwrap();
 
-    let experiments = web::block(move || {
-        experiments::table
+    let experiments = web::block(move || -> Result<_> {
+        let mut query = experiments::table
             .filter(experiments::user_id.eq(user.id))
-            .order(experiments::created_at.desc())
+            .into_boxed()
+            .within(experiments::created_at, &range);
+
+        // code is stored html encoded, search is encoded the same way to match it
+        if let Some(search) = &filter.search {
+            let search = core::encode_minimal(search);
+            query = query.filter(
+                text_search(experiments::name, search.clone()).or(text_search(experiments::code, search))
+            );
+        }
+
+        if let Some(cursor) = &pagination.cursor {
+            let query = query.select(SLIM_EXPERIMENT_COLUMNS);
+            let per_page = paginate::per_page(pagination.per_page);
+
+            let page = match sort.sort_by {
+                ExperimentSortField::CreatedAt => CursorPagination::load(
+                    query.after(experiments::created_at, experiments::id, Cursor::<NaiveDateTime>::decode(cursor)?, sort.order),
+                    per_page,
+                    &conn,
+                    |experiment: &SlimExperiment| Cursor::new(experiment.created_at, experiment.id),
+                ),
+                ExperimentSortField::UpdatedAt => CursorPagination::load(
+                    query.after(experiments::updated_at, experiments::id, Cursor::<NaiveDateTime>::decode(cursor)?, sort.order),
+                    per_page,
+                    &conn,
+                    |experiment: &SlimExperiment| Cursor::new(experiment.updated_at, experiment.id),
+                ),
+                ExperimentSortField::Name => CursorPagination::load(
+                    query.after(experiments::name, experiments::id, Cursor::<String>::decode(cursor)?, sort.order),
+                    per_page,
+                    &conn,
+                    |experiment: &SlimExperiment| Cursor::new(experiment.name.clone(), experiment.id),
+                ),
+            }?;
+
+            return Ok(Page::Cursor(page));
+        }
+
+        query = match sort.sort_by {
+            ExperimentSortField::CreatedAt => query.sort(experiments::created_at, sort.order),
+            ExperimentSortField::UpdatedAt => query.sort(experiments::updated_at, sort.order),
+            ExperimentSortField::Name => query.sort(experiments::name, sort.order),
+        };
+
+        query
+            .then_order_by(experiments::id.desc())
             .select((SLIM_EXPERIMENT_COLUMNS, CountStarOver))
             .paginate(pagination.page)
             .per_page(pagination.per_page)
             .load_and_count_pages::<SlimExperiment>(&conn)
+            .map(Page::Offset)
+            .map_err(|e| e.into())
     })
     .await?;
 
     Ok(Json(experiments))
 }
 
+#[operation]
 #[get("experiment/{id}")]
 pub async fn fetch_experiment(
     pool: web::Data<DBPool>,
@@ -151,46 +226,66 @@ pub async fn fetch_experiment(
 ) -> Result<Json<Experiment>> {
     let conn = pool.get().unwrap();
 
-    let experiment = web::block(move || {
+    let experiment = web::block(move || -> Result<Experiment> {
+        let experiment_id = experiment_id.into_inner();
+
+        check_experiment_access(&conn, experiment_id, user.id, Access::Viewer)?;
+
         experiments::table
-            .filter(experiments::user_id.eq(user.id))
-            .find(experiment_id.into_inner())
+            .find(experiment_id)
             .first::<Experiment>(&conn)
+            .map_err(|e| e.into())
     })
     .await?;
 
     Ok(Json(experiment))
 }
 
+#[operation]
 #[get("experiment/{id}/jobs")]
 pub async fn fetch_experiment_jobs(
     pool: web::Data<DBPool>,
     experiment_id: web::Path<ModelId>,
     user: User,
     pagination: web::Query<PaginationRequest>,
-) -> Result<Json<Pagination<(SlimJob, SlimController)>>> {
+    filter: web::Query<JobFilterRequest>,
+    range: web::Query<DateRangeRequest>,
+    sort: web::Query<SortRequest<JobSortField>>,
+) -> Result<Json<Page<(SlimJob, SlimController)>>> {
     let conn = pool.get().unwrap();
 
-    let jobs = web::block(move || {
-        let experiment = experiments::table
-            .filter(experiments::user_id.eq(user.id))
-            .find(experiment_id.into_inner())
-            .first::<Experiment>(&conn)?;
+    let jobs = web::block(move || -> Result<_> {
+        let experiment_id = experiment_id.into_inner();
 
-        jobs::table
-            .filter(jobs::experiment_id.eq(experiment.id))
-            .inner_join(controllers::table)
-            .order_by(jobs::id.desc())
-            .select(((SLIM_JOB_COLUMNS, SLIM_CONTROLLER_COLUMNS), CountStarOver))
-            .paginate(pagination.page)
-            .per_page(pagination.per_page)
-            .load_and_count_pages::<(SlimJob, SlimController)>(&conn)
+        check_experiment_access(&conn, experiment_id, user.id, Access::Viewer)?;
+
+        load_jobs(&conn, Some(experiment_id), &pagination, &filter, &range, &sort)
+    })
+    .await?;
+
+    Ok(Json(jobs))
+}
+
+#[operation]
+#[get("jobs")]
+pub async fn fetch_jobs(
+    pool: web::Data<DBPool>,
+    pagination: web::Query<PaginationRequest>,
+    filter: web::Query<JobFilterRequest>,
+    range: web::Query<DateRangeRequest>,
+    sort: web::Query<SortRequest<JobSortField>>,
+) -> Result<Json<Page<(SlimJob, SlimController)>>> {
+    let conn = pool.get().unwrap();
+
+    let jobs = web::block(move || {
+        load_jobs(&conn, None, &pagination, &filter, &range, &sort)
     })
     .await?;
 
     Ok(Json(jobs))
 }
 
+#[operation]
 #[get("job/{id}")]
 pub async fn fetch_job(
     pool: web::Data<DBPool>,
@@ -199,30 +294,38 @@ pub async fn fetch_job(
 ) -> Result<Json<(Job, SlimController)>> {
     let conn = pool.get().unwrap();
 
-    let job = web::block(move || {
+    let job = web::block(move || -> Result<(Job, SlimController)> {
+        let job_id = job_id.into_inner();
+
+        check_job_access(&conn, job_id, user.id, Access::Viewer)?;
+
         jobs::table
-            .filter(jobs::id.eq(job_id.into_inner()))
+            .filter(jobs::id.eq(job_id))
             .inner_join(controllers::table)
-            .inner_join(experiments::table)
-            .filter(experiments::user_id.eq(user.id))
             .select((jobs::all_columns, SLIM_CONTROLLER_COLUMNS))
             .first::<(Job, SlimController)>(&conn)
+            .map_err(|e| e.into())
     })
     .await?;
 
     Ok(Json(job))
 }
 
+#[operation]
 #[delete("job/{id}/abort")]
 pub async fn abort_running_job(
     pool: web::Data<DBPool>,
+    servers: web::Data<Servers>,
+    webhook_server: web::Data<Addr<WebhookServer>>,
     job_id: web::Path<ModelId>,
     user: User,
+    ip: ClientIp,
     experiment_server: web::Data<Addr<ExperimentServer>>
 ) -> Result<Json<SuccessResponse>> {
     let conn = pool.get().unwrap();
+    let user_id = user.id;
 
-    let option = web::block(move || {
+    let job = web::block(move || {
         conn.transaction(|| {
             let job = jobs::table
                 .find(job_id.into_inner())
@@ -232,14 +335,24 @@ pub async fn abort_running_job(
                 .select(jobs::all_columns)
                 .first::<Job>(&conn)?;
 
+            if job.status == JobStatus::Running || job.status == JobStatus::Pending {
+                audit::record(&conn, AuditEntry {
+                    actor_id: Some(user.id),
+                    action: AuditAction::AbortJob,
+                    target_kind: TargetKind::Job,
+                    target_id: job.id,
+                    ip: ip.into_inner(),
+                })?;
+            }
+
             match job.status {
-                JobStatus::Running => Ok(Some((job.id, job.controller_id))),
+                JobStatus::Running => Ok(job),
                 JobStatus::Pending => {
                     diesel::update(&job)
-                        .set(jobs::status.eq(JobStatus::Failed.value()))
+                        .set((jobs::status.eq(JobStatus::Cancelled.value()), jobs::finished_at.eq(Utc::now().naive_utc())))
                         .execute(&conn)?;
 
-                    Ok(None)
+                    Ok(job)
                 }
                 _ => Err(Box::new(CoreErrorMessage::InvalidOperationForStatus)
                     as Box<dyn ErrorMessaging>),
@@ -248,15 +361,258 @@ pub async fn abort_running_job(
     })
     .await?;
 
-    if let Some((job_id, controller_id)) = option {
-        experiment_server.send(AbortRunningJob {job_id, controller_id})
+    // status of the job before it is aborted
+    match job.status {
+        JobStatus::Running => experiment_server.send(AbortRunningJob { job_id: job.id, controller_id: job.controller_id })
             .await
-            .map_err(|_| CoreErrorMessage::Custom("Failed to send AbortRunningJob message to experiment server"))?;
+            .map_err(|_| CoreErrorMessage::Custom("Failed to send AbortRunningJob message to experiment server"))?,
+        _ => notify_cancelled(&servers, &webhook_server, user_id, job.id)
     }
 
     Ok(Json(SuccessResponse::default()))
 }
 
+#[operation]
+#[post("job/{id}/cancel")]
+pub async fn cancel_job(
+    pool: web::Data<DBPool>,
+    servers: web::Data<Servers>,
+    webhook_server: web::Data<Addr<WebhookServer>>,
+    job_id: web::Path<ModelId>,
+    user: User,
+    ip: ClientIp,
+) -> Result<Json<SuccessResponse>> {
+    let conn = pool.get().unwrap();
+    let job_id = job_id.into_inner();
+    let user_id = user.id;
+
+    web::block(move || {
+        conn.transaction(|| {
+            let job = jobs::table
+                .find(job_id)
+                .inner_join(experiments::table)
+                .filter(experiments::user_id.eq(user_id))
+                .for_update()
+                .select(jobs::all_columns)
+                .first::<Job>(&conn)?;
+
+            if job.status != JobStatus::Pending {
+                return Err(Box::new(CoreErrorMessage::InvalidOperationForStatus) as Box<dyn ErrorMessaging>);
+            }
+
+            diesel::update(&job)
+                .set((jobs::status.eq(JobStatus::Cancelled.value()), jobs::finished_at.eq(Utc::now().naive_utc())))
+                .execute(&conn)?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user_id),
+                action: AuditAction::CancelJob,
+                target_kind: TargetKind::Job,
+                target_id: job.id,
+                ip: ip.into_inner(),
+            })?;
+
+            Ok(())
+        })
+    })
+    .await?;
+
+    notify_cancelled(&servers, &webhook_server, user_id, job_id);
+
+    Ok(Json(SuccessResponse::default()))
+}
+
+/// Tells the owner that the job is cancelled before it is run, through a notification and the webhooks
+fn notify_cancelled(servers: &Servers, webhook_server: &Addr<WebhookServer>, user_id: ModelId, job_id: ModelId) {
+    servers.notification.do_send(Notification {
+        user_id,
+        message: NotificationMessage {
+            kind: NotificationKind::JobUpdate,
+            data: JobUpdate { job_id, status: JobStatus::Cancelled },
+        },
+    });
+
+    webhook_server.do_send(Event {
+        user_id,
+        kind: EventKind::JobStatus,
+        data: JobUpdate { job_id, status: JobStatus::Cancelled },
+    });
+}
+
+/// Runs the code and parameters of the job again, on the same or another controller
+#[operation]
+#[post("job/{id}/rerun/{controller_id}")]
+pub async fn rerun_job(
+    pool: web::Data<DBPool>,
+    experiment_server: web::Data<Addr<ExperimentServer>>,
+    ids: web::Path<(ModelId, ModelId)>,
+    user: User,
+) -> Result<Json<Job>> {
+    let conn = pool.get().unwrap();
+    let (job_id, controller_id) = ids.into_inner();
+    let user_id = user.id;
+
+    let mut job = web::block(move || -> Result<Job> {
+        let job = jobs::table
+            .find(job_id)
+            .inner_join(experiments::table)
+            .filter(experiments::user_id.eq(user.id))
+            .select(jobs::all_columns)
+            .first::<Job>(&conn)?;
+
+        let controller = controllers::table.find(controller_id).first::<Controller>(&conn)?;
+
+        check_slot(&conn, user.id, controller.id)?;
+
+        diesel::insert_into(jobs::table)
+            .values((
+                jobs::experiment_id.eq(job.experiment_id),
+                jobs::controller_id.eq(controller.id),
+                jobs::code.eq(job.code),
+                jobs::parameters.eq(job.parameters),
+                jobs::priority.eq(job.priority),
+            ))
+            .get_result::<Job>(&conn)
+            .map_err(|e| e.into())
+    })
+    .await?;
+
+    start_job(pool, experiment_server.get_ref(), &mut job, user_id).await?;
+
+    Ok(Json(job))
+}
+
+/// Creates a new experiment from the code of the job
+#[operation]
+#[post("job/{id}/clone")]
+pub async fn clone_job(
+    pool: web::Data<DBPool>,
+    job_id: web::Path<ModelId>,
+    user: User,
+    request: SanitizedJson<ExperimentNameRequest>,
+) -> Result<Json<Experiment>> {
+    let conn = pool.get().unwrap();
+    let request = request.into_inner();
+
+    let experiment = web::block(move || -> Result<Experiment> {
+        let job_id = job_id.into_inner();
+
+        check_job_access(&conn, job_id, user.id, Access::Viewer)?;
+
+        let (code, parameters) = jobs::table
+            .find(job_id)
+            .inner_join(experiments::table)
+            .select((jobs::code, experiments::parameters))
+            .first::<(String, Value)>(&conn)?;
+
+        diesel::insert_into(experiments::table)
+            .values((
+                experiments::user_id.eq(user.id),
+                experiments::name.eq(request.name),
+                experiments::code.eq(code),
+                experiments::parameters.eq(parameters),
+            ))
+            .get_result::<Experiment>(&conn)
+            .map_err(|e| e.into())
+    })
+    .await?;
+
+    Ok(Json(experiment))
+}
+
+#[operation]
+#[get("job/{id}/queue")]
+pub async fn fetch_job_queue_position(
+    pool: web::Data<DBPool>,
+    job_id: web::Path<ModelId>,
+    user: User,
+) -> Result<Json<QueuePosition>> {
+    let conn = pool.get().unwrap();
+
+    let position = web::block(move || -> Result<QueuePosition> {
+        let job = jobs::table
+            .find(job_id.into_inner())
+            .inner_join(experiments::table)
+            .filter(experiments::user_id.eq(user.id))
+            .select(jobs::all_columns)
+            .first::<Job>(&conn)?;
+
+        if job.status != JobStatus::Pending {
+            return Err(Box::new(CoreErrorMessage::InvalidOperationForStatus));
+        }
+
+        scheduler::estimate(&conn, job.controller_id, Utc::now().naive_utc())?
+            .position(job.id)
+            .ok_or_else(|| Box::new(CoreErrorMessage::ItemNotFound) as Box<dyn ErrorMessaging>)
+    })
+    .await?;
+
+    Ok(Json(position))
+}
+
+#[operation]
+#[get("controller/{id}/queue")]
+pub async fn fetch_controller_queue(
+    pool: web::Data<DBPool>,
+    controller_id: web::Path<ModelId>,
+    user: User,
+) -> Result<Json<Vec<QueueEntry>>> {
+    let conn = pool.get().unwrap();
+
+    let entries = web::block(move || -> Result<Vec<QueueEntry>> {
+        let controller = controllers::table
+            .find(controller_id.into_inner())
+            .first::<Controller>(&conn)?;
+
+        Ok(scheduler::estimate(&conn, controller.id, Utc::now().naive_utc())?.entries(user.id))
+    })
+    .await?;
+
+    Ok(Json(entries))
+}
+
+#[operation]
+#[put("job/{id}/priority")]
+pub async fn update_job_priority(
+    pool: web::Data<DBPool>,
+    user: User,
+    ip: ClientIp,
+    job_id: web::Path<ModelId>,
+    request: web::Json<JobPriorityRequest>,
+) -> Result<Json<SuccessResponse>> {
+    let conn = pool.get().unwrap();
+
+    web::block(move || -> Result<()> {
+        conn.transaction(|| {
+            let job = jobs::table
+                .find(job_id.into_inner())
+                .first::<Job>(&conn)?;
+
+            if job.status != JobStatus::Pending {
+                return Err(Box::new(CoreErrorMessage::InvalidOperationForStatus) as Box<dyn ErrorMessaging>);
+            }
+
+            diesel::update(&job)
+                .set(jobs::priority_override.eq(request.priority))
+                .execute(&conn)?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::UpdateJobPriority,
+                target_kind: TargetKind::Job,
+                target_id: job.id,
+                ip: ip.into_inner(),
+            })?;
+
+            Ok(())
+        })
+    })
+    .await?;
+
+    Ok(Json(SuccessResponse::default()))
+}
+
+#[operation]
 #[post("experiment")]
 pub async fn create_new_experiment(
     pool: web::Data<DBPool>,
@@ -279,8 +635,7 @@ pub async fn create_new_experiment(
     Ok(Json(experiment))
 }
 
-/// This will return a SuccessResponse even though update may not occur if experiment's user id is not
-/// equal to user.id. Update endpoints will generally behave like this.
+#[operation]
 #[put("experiment/{id}")]
 pub async fn update_experiment_name(
     pool: web::Data<DBPool>,
@@ -290,32 +645,66 @@ pub async fn update_experiment_name(
 ) -> Result<Json<SuccessResponse>> {
     let conn = pool.get().unwrap();
 
-    web::block(move || {
-        diesel::update(
-            experiments::table
-                .filter(experiments::user_id.eq(user.id))
-                .find(experiment_id.into_inner()),
-        )
-        .set(experiments::name.eq(request.into_inner().name))
-        .execute(&conn)
+    web::block(move || -> Result<usize> {
+        let experiment_id = experiment_id.into_inner();
+
+        check_experiment_access(&conn, experiment_id, user.id, Access::Editor)?;
+
+        diesel::update(experiments::table.find(experiment_id))
+            .set(experiments::name.eq(request.into_inner().name))
+            .execute(&conn)
+            .map_err(|e| e.into())
+    })
+    .await?;
+
+    Ok(Json(SuccessResponse::default()))
+}
+
+#[operation]
+#[put("experiment/{id}/parameters")]
+pub async fn update_experiment_parameters(
+    pool: web::Data<DBPool>,
+    experiment_id: web::Path<ModelId>,
+    user: User,
+    request: web::Json<ExperimentParametersRequest>,
+) -> Result<Json<SuccessResponse>> {
+    let conn = pool.get().unwrap();
+    let parameters = request.into_inner().parameters;
+
+    parameters.validate()?;
+
+    web::block(move || -> Result<usize> {
+        let experiment_id = experiment_id.into_inner();
+
+        check_experiment_access(&conn, experiment_id, user.id, Access::Editor)?;
+
+        diesel::update(experiments::table.find(experiment_id))
+            .set(experiments::parameters.eq(serde_json::to_value(parameters).unwrap()))
+            .execute(&conn)
+            .map_err(|e| e.into())
     })
     .await?;
 
     Ok(Json(SuccessResponse::default()))
 }
 
+/// Runs the experiment with a single value set or, if a sweep is given, creates one job per value set.
+/// The body is optional, in that case parameters take their default values.
+#[operation]
 #[post("experiment/{experiment_id}/run/{controller_id}")]
 pub async fn run_experiment(
     pool: web::Data<DBPool>,
     experiment_server: web::Data<Addr<ExperimentServer>>,
     ids: web::Path<(ModelId, ModelId)>,
     user: User,
-) -> Result<Json<Job>> {
+    request: Option<web::Json<RunExperimentRequest>>,
+) -> Result<Json<RunExperimentResponse>> {
     let conn = pool.get().unwrap();
     let (experiment_id, controller_id) = ids.into_inner();
     let user_id = user.id;
+    let request = request.map(|request| request.into_inner()).unwrap_or_default();
 
-    let mut job = web::block(move || -> Result<Job> {
+    let (sweep, mut jobs) = web::block(move || -> Result<(Option<Sweep>, Vec<Job>)> {
         let experiment = experiments::table
             .filter(experiments::user_id.eq(user.id))
             .find(experiment_id)
@@ -323,60 +712,147 @@ pub async fn run_experiment(
 
         let controller = controllers::table.find(controller_id).first::<Controller>(&conn)?;
 
-        let now = Utc::now().naive_utc();
+        check_slot(&conn, user.id, controller.id)?;
+
+        if request.priority < 0 || request.priority > MAX_USER_PRIORITY {
+            return Err(Box::new(ErrorMessage::InvalidPriority));
+        }
+
+        let priority = request.priority;
+
+        let value_sets = match &request.sweep {
+            Some(sweep) => experiment.parameters.expand(&request.parameters, sweep)?,
+            None => vec![experiment.parameters.resolve(&request.parameters)?],
+        };
 
-        let slot_exist: bool = diesel::dsl::select(diesel::dsl::exists(
-            slots::table
-                .filter(slots::start_at.lt(&now).and(slots::end_at.gt(&now)))
-                .filter(
-                    slots::user_id
-                        .eq(user.id)
-                        .and(slots::controller_id.eq(controller.id)),
+        conn.transaction(|| {
+            let sweep = match request.sweep {
+                Some(definition) => Some(
+                    diesel::insert_into(sweeps::table)
+                        .values((
+                            sweeps::experiment_id.eq(experiment.id),
+                            sweeps::controller_id.eq(controller.id),
+                            sweeps::definition.eq(serde_json::to_value(definition).unwrap()),
+                        ))
+                        .get_result::<Sweep>(&conn)?
                 ),
-        ))
-        .get_result(&conn)?;
+                None => None,
+            };
+
+            let sweep_id = sweep.as_ref().map(|sweep| sweep.id);
+
+            let values = value_sets
+                .into_iter()
+                .map(|parameters| (
+                    jobs::experiment_id.eq(experiment.id),
+                    jobs::controller_id.eq(controller.id),
+                    jobs::code.eq(experiment.code.clone()),
+                    jobs::parameters.eq(Value::Object(parameters)),
+                    jobs::sweep_id.eq(sweep_id),
+                    jobs::priority.eq(priority),
+                ))
+                .collect::<Vec<_>>();
+
+            let jobs = diesel::insert_into(jobs::table)
+                .values(&values)
+                .get_results::<Job>(&conn)?;
+
+            Ok((sweep, jobs))
+        })
+    })
+    .await?;
 
-        if !slot_exist {
-            return Err(Box::new(ErrorMessage::NotAllowedToRunForSlot));
-        }
+    // Jobs of a sweep are queued, the rest is picked up by the experiment server once the first one finishes
+    start_job(pool, experiment_server.get_ref(), &mut jobs[0], user_id).await?;
 
-        diesel::insert_into(jobs::table)
-            .values((
-                jobs::experiment_id.eq(experiment.id),
-                jobs::controller_id.eq(controller.id),
-                jobs::code.eq(experiment.code),
-            ))
-            .get_result::<Job>(&conn)
-            .map_err(|e| e.into())
+    let response = match sweep {
+        Some(sweep) => RunExperimentResponse::Sweep(SweepWithJobs {
+            summary: SweepSummary::new(sweep, jobs.iter().map(|job| &job.status)),
+            jobs: jobs.into_iter().map(SlimJob::from).collect(),
+        }),
+        None => RunExperimentResponse::Job(jobs.remove(0)),
+    };
+
+    Ok(Json(response))
+}
+
+#[operation]
+#[get("experiment/{id}/sweeps")]
+pub async fn fetch_experiment_sweeps(
+    pool: web::Data<DBPool>,
+    experiment_id: web::Path<ModelId>,
+    user: User,
+    pagination: web::Query<PaginationRequest>,
+) -> Result<Json<Pagination<SweepSummary>>> {
+    let conn = pool.get().unwrap();
+
+    let sweeps = web::block(move || -> Result<_> {
+        let experiment_id = experiment_id.into_inner();
+
+        check_experiment_access(&conn, experiment_id, user.id, Access::Viewer)?;
+
+        let sweeps = sweeps::table
+            .filter(sweeps::experiment_id.eq(experiment_id))
+            .order_by(sweeps::id.desc())
+            .select((sweeps::all_columns, CountStarOver))
+            .paginate(pagination.page)
+            .per_page(pagination.per_page)
+            .load_and_count_pages::<Sweep>(&conn)?;
+
+        let statuses = jobs::table
+            .filter(jobs::sweep_id.eq_any(sweeps.items().iter().map(|sweep| sweep.id).collect::<Vec<ModelId>>()))
+            .select((jobs::sweep_id, jobs::status))
+            .load::<(Option<ModelId>, JobStatus)>(&conn)?;
+
+        Ok(sweeps.map(|sweep| {
+            let id = sweep.id;
+
+            SweepSummary::new(
+                sweep,
+                statuses.iter()
+                    .filter(|(sweep_id, _)| *sweep_id == Some(id))
+                    .map(|(_, status)| status),
+            )
+        }))
     })
     .await?;
 
-    let job_id = job.id;
+    Ok(Json(sweeps))
+}
 
-    if let Err(e) = experiment_server
-        .send(RunExperiment {
-            code: job.code.clone(),
-            job_id,
-            controller_id,
-            user_id,
-        })
-        .await
-    {
-        error!("Error while sending run to ExperimentServer: {:?}", e);
+#[operation]
+#[get("sweep/{id}")]
+pub async fn fetch_sweep(
+    pool: web::Data<DBPool>,
+    sweep_id: web::Path<ModelId>,
+    user: User,
+) -> Result<Json<SweepWithJobs>> {
+    let conn = pool.get().unwrap();
 
-        web::block(move || {
-            diesel::update(jobs::table.find(job_id))
-                .set(jobs::status.eq(JobStatus::Failed.value()))
-                .execute(&pool.get().unwrap())
-        })
-        .await?;
+    let sweep = web::block(move || -> Result<_> {
+        let sweep = sweeps::table
+            .find(sweep_id.into_inner())
+            .first::<Sweep>(&conn)?;
 
-        job.status = JobStatus::Failed;
-    }
+        check_experiment_access(&conn, sweep.experiment_id, user.id, Access::Viewer)?;
 
-    Ok(Json(job))
+        let jobs = jobs::table
+            .filter(jobs::sweep_id.eq(sweep.id))
+            .order_by(jobs::id.asc())
+            .select(SLIM_JOB_COLUMNS)
+            .load::<SlimJob>(&conn)?;
+
+        Ok(SweepWithJobs {
+            summary: SweepSummary::new(sweep, jobs.iter().map(|job| &job.status)),
+            jobs,
+        })
+    })
+    .await?;
+
+    Ok(Json(sweep))
 }
 
+#[operation]
 #[put("experiment/{id}/code")]
 pub async fn update_experiment_code(
     pool: web::Data<DBPool>,
@@ -386,14 +862,15 @@ pub async fn update_experiment_code(
 ) -> Result<Json<SuccessResponse>> {
     let conn = pool.get().unwrap();
 
-    web::block(move || {
-        diesel::update(
-            experiments::table
-                .filter(experiments::user_id.eq(user.id))
-                .find(experiment_id.into_inner()),
-        )
-        .set(experiments::code.eq(request.into_inner().code))
-        .execute(&conn)
+    web::block(move || -> Result<usize> {
+        let experiment_id = experiment_id.into_inner();
+
+        check_experiment_access(&conn, experiment_id, user.id, Access::Editor)?;
+
+        diesel::update(experiments::table.find(experiment_id))
+            .set(experiments::code.eq(request.into_inner().code))
+            .execute(&conn)
+            .map_err(|e| e.into())
     })
     .await?;
 
@@ -402,36 +879,71 @@ pub async fn update_experiment_code(
 
 /// This will return a SuccessResponse even though delete may not occur if experiment's user id is not
 /// equal to user.id. Delete endpoints will generally behave like this.
+#[operation]
 #[delete("experiment/{id}")]
 pub async fn delete_experiment(
     pool: web::Data<DBPool>,
     experiment_id: web::Path<ModelId>,
     user: User,
+    ip: ClientIp,
 ) -> Result<Json<SuccessResponse>> {
     let conn = pool.get().unwrap();
+    let experiment_id = experiment_id.into_inner();
 
     web::block(move || {
-        diesel::delete(
-            experiments::table
-                .filter(experiments::user_id.eq(user.id))
-                .find(experiment_id.into_inner()),
-        )
-        .execute(&conn)
+        conn.transaction(|| {
+            let deleted = diesel::delete(
+                experiments::table
+                    .filter(experiments::user_id.eq(user.id))
+                    .find(experiment_id),
+            )
+            .execute(&conn)?;
+
+            if deleted == 0 {
+                return Ok(());
+            }
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::DeleteExperiment,
+                target_kind: TargetKind::Experiment,
+                target_id: experiment_id,
+                ip: ip.into_inner(),
+            })
+        })
     })
     .await?;
 
     Ok(Json(SuccessResponse::default()))
 }
 
+#[operation]
 #[get("controller/{id}/token")]
-pub async fn controller_token(pool: web::Data<DBPool>, hash: web::Data<Hash>, controller_id: web::Path<ModelId>) -> Result<Json<TokenResponse>> {
+pub async fn controller_token(
+    pool: web::Data<DBPool>,
+    hash: web::Data<Hash>,
+    user: User,
+    ip: ClientIp,
+    controller_id: web::Path<ModelId>,
+) -> Result<Json<TokenResponse>> {
     let conn = pool.get().unwrap();
-
-    let access_key = web::block(move ||
-                                controllers::table.find(controller_id.into_inner())
-                                .select(controllers::access_key)
-                                .first(&conn)
-        )
+    let controller_id = controller_id.into_inner();
+
+    let access_key = web::block(move || -> QueryResult<String> {
+        let access_key = controllers::table.find(controller_id)
+            .select(controllers::access_key)
+            .first::<String>(&conn)?;
+
+        audit::record(&conn, AuditEntry {
+            actor_id: Some(user.id),
+            action: AuditAction::FetchControllerToken,
+            target_kind: TargetKind::Controller,
+            target_id: controller_id,
+            ip: ip.into_inner(),
+        })?;
+
+        Ok(access_key)
+    })
         .await?;
 
     let token = hash.encode::<ControllerToken>(&ControllerToken{
@@ -442,3 +954,143 @@ pub async fn controller_token(pool: web::Data<DBPool>, hash: web::Data<Hash>, co
 
     Ok(Json(TokenResponse{token}))
 }
+
+#[derive(Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum RunExperimentResponse {
+    Job(Job),
+    Sweep(SweepWithJobs),
+}
+
+/// Checks that the user owns the current slot of the controller
+fn check_slot(conn: &PgConnection, user_id: ModelId, controller_id: ModelId) -> Result<()> {
+    let now = Utc::now().naive_utc();
+
+    let slot_exist: bool = diesel::dsl::select(diesel::dsl::exists(
+        slots::table
+            .filter(slots::start_at.lt(&now).and(slots::end_at.gt(&now)))
+            .filter(
+                slots::user_id
+                    .eq(user_id)
+                    .and(slots::controller_id.eq(controller_id)),
+            ),
+    ))
+    .get_result(conn)?;
+
+    if slot_exist {
+        Ok(())
+    } else {
+        Err(Box::new(ErrorMessage::NotAllowedToRunForSlot))
+    }
+}
+
+/// Sends the newly created job to the experiment server, the job is marked as Failed if it cannot be sent.
+/// Experiment server queues the job if the controller is busy.
+async fn start_job(
+    pool: web::Data<DBPool>,
+    experiment_server: &Addr<ExperimentServer>,
+    job: &mut Job,
+    user_id: ModelId,
+) -> Result<()> {
+    let job_id = job.id;
+
+    if let Err(e) = experiment_server
+        .send(RunExperiment {
+            code: job.code.clone(),
+            parameters: job.parameters.as_object().cloned().unwrap_or_default(),
+            job_id,
+            controller_id: job.controller_id,
+            user_id,
+        })
+        .await
+    {
+        error!("Error while sending run to ExperimentServer: {:?}", e);
+
+        web::block(move || {
+            diesel::update(jobs::table.find(job_id))
+                .set((
+                    jobs::status.eq(JobStatus::Failed.value()),
+                    jobs::finished_at.eq(Utc::now().naive_utc()),
+                    jobs::failure_kind.eq(job::DISPATCH_FAILED),
+                    jobs::failure_cause.eq(FailureCause::Internal.value()),
+                ))
+                .execute(&pool.get().unwrap())
+        })
+        .await?;
+
+        job.status = JobStatus::Failed;
+    }
+
+    Ok(())
+}
+
+/// Jobs together with their controllers for the job listings, only the ones of the experiment if it is given
+fn load_jobs(
+    conn: &PgConnection,
+    experiment_id: Option<ModelId>,
+    pagination: &PaginationRequest,
+    filter: &JobFilterRequest,
+    range: &DateRangeRequest,
+    sort: &SortRequest<JobSortField>,
+) -> Result<Page<(SlimJob, SlimController)>> {
+    let mut query = jobs::table
+        .inner_join(controllers::table)
+        .into_boxed()
+        .within(jobs::created_at, range);
+
+    if let Some(experiment_id) = experiment_id {
+        query = query.filter(jobs::experiment_id.eq(experiment_id));
+    }
+
+    if let Some(status) = &filter.status {
+        query = query.filter(jobs::status.eq(status.value()));
+    }
+
+    if let Some(failure_cause) = &filter.failure_cause {
+        query = query.filter(jobs::failure_cause.eq(failure_cause.value()));
+    }
+
+    if let Some(controller_id) = filter.controller_id {
+        query = query.filter(jobs::controller_id.eq(controller_id));
+    }
+
+    if let Some(cursor) = &pagination.cursor {
+        let query = query.select((SLIM_JOB_COLUMNS, SLIM_CONTROLLER_COLUMNS));
+        let per_page = paginate::per_page(pagination.per_page);
+
+        let page = match sort.sort_by {
+            JobSortField::CreatedAt => CursorPagination::load(
+                query.after(jobs::created_at, jobs::id, Cursor::<NaiveDateTime>::decode(cursor)?, sort.order),
+                per_page,
+                conn,
+                |(job, _): &(SlimJob, SlimController)| Cursor::new(job.created_at, job.id),
+            ),
+            JobSortField::Priority => CursorPagination::load(
+                query.after(jobs::priority, jobs::id, Cursor::<i32>::decode(cursor)?, sort.order),
+                per_page,
+                conn,
+                |(job, _): &(SlimJob, SlimController)| Cursor::new(job.priority, job.id),
+            ),
+            // jobs that are not started or finished yet have no key to position a cursor on
+            JobSortField::StartedAt | JobSortField::FinishedAt => return Err(Box::new(CoreErrorMessage::InvalidCursor)),
+        }?;
+
+        return Ok(Page::Cursor(page));
+    }
+
+    query = match sort.sort_by {
+        JobSortField::CreatedAt => query.sort(jobs::created_at, sort.order),
+        JobSortField::StartedAt => query.sort_nulls_last(jobs::started_at, sort.order),
+        JobSortField::FinishedAt => query.sort_nulls_last(jobs::finished_at, sort.order),
+        JobSortField::Priority => query.sort(jobs::priority, sort.order),
+    };
+
+    query
+        .then_order_by(jobs::id.desc())
+        .select(((SLIM_JOB_COLUMNS, SLIM_CONTROLLER_COLUMNS), CountStarOver))
+        .paginate(pagination.page)
+        .per_page(pagination.per_page)
+        .load_and_count_pages::<(SlimJob, SlimController)>(conn)
+        .map(Page::Offset)
+        .map_err(|e| e.into())
+}
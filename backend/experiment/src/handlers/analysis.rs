@@ -0,0 +1,134 @@
+use std::sync::Arc;
+
+use actix_web::{get, post, web, web::Json};
+use diesel::prelude::*;
+use diesel::pg::upsert::excluded;
+
+use core::ErrorMessage as CoreErrorMessage;
+use core::schema::{job_analyses, job_artifacts, jobs};
+use core::types::{DBPool, ModelId, Result};
+use derive::operation;
+use service::storage::Storage;
+use shared::state::Decoder;
+use user::models::user::User;
+
+use crate::access::{check_job_access, Access};
+use crate::analysis::{self, AnalysisSettings};
+use crate::ErrorMessage;
+use crate::handlers::storage::{read_artifact, OUTPUT_FILE_NAME, STATE_FILE_NAME};
+use crate::models::analysis::JobAnalysis;
+use crate::models::job::JobStatus;
+
+#[operation]
+#[get("job/{id}/analysis")]
+pub async fn fetch_job_analysis(
+    pool: web::Data<DBPool>,
+    job_id: web::Path<ModelId>,
+    user: User,
+) -> Result<Json<JobAnalysis>> {
+    let conn = pool.get().unwrap();
+    let job_id = job_id.into_inner();
+
+    let analysis = web::block(move || -> Result<JobAnalysis> {
+        check_job_access(&conn, job_id, user.id, Access::Viewer)?;
+
+        job_analyses::table
+            .filter(job_analyses::job_id.eq(job_id))
+            .first::<JobAnalysis>(&conn)
+            .map_err(|e| e.into())
+    })
+        .await?;
+
+    Ok(Json(analysis))
+}
+
+/// Analyses the receiver output of a successful job against the state its transmitter has executed,
+/// replacing the previous analysis of the job
+#[operation]
+#[post("job/{id}/analysis")]
+pub async fn analyze_job(
+    pool: web::Data<DBPool>,
+    storage: web::Data<Arc<dyn Storage>>,
+    job_id: web::Path<ModelId>,
+    user: User,
+    settings: web::Json<AnalysisSettings>,
+) -> Result<Json<JobAnalysis>> {
+    let settings = settings.into_inner();
+    let job_id = job_id.into_inner();
+
+    settings.validate()?;
+
+    let conn = pool.get().unwrap();
+
+    web::block(move || -> Result<()> {
+        check_job_access(&conn, job_id, user.id, Access::Editor)?;
+
+        let status = jobs::table
+            .find(job_id)
+            .select(jobs::status)
+            .first::<JobStatus>(&conn)?;
+
+        if status != JobStatus::Successful {
+            return Err(Box::new(CoreErrorMessage::InvalidOperationForStatus));
+        }
+
+        // jobs run before controllers started to upload their states can not be analysed
+        let artifacts = job_artifacts::table
+            .filter(job_artifacts::job_id.eq(job_id))
+            .filter(job_artifacts::name.eq_any(vec![OUTPUT_FILE_NAME, STATE_FILE_NAME]))
+            .count()
+            .get_result::<i64>(&conn)?;
+
+        if artifacts != 2 {
+            return Err(Box::new(ErrorMessage::InvalidAnalysis));
+        }
+
+        Ok(())
+    })
+        .await?;
+
+    let output = read_artifact(storage.get_ref(), job_id, OUTPUT_FILE_NAME).await?;
+    let state = read_artifact(storage.get_ref(), job_id, STATE_FILE_NAME).await?;
+
+    let conn = pool.get().unwrap();
+
+    let analysis = web::block(move || -> Result<JobAnalysis> {
+        let state = Decoder::decode(String::from_utf8_lossy(&state).as_ref())
+            .map_err(|_| ErrorMessage::InvalidAnalysis)?;
+        let samples = analysis::parse_samples(String::from_utf8_lossy(&output).as_ref());
+
+        let analysis = analysis::analyze(&state.slots(), &samples, &settings)?;
+        let best = analysis.best();
+        let bit_error_rate = best.map_or(1.0, |receiver| receiver.bit_error_rate);
+        let latency = best.and_then(|receiver| receiver.latency);
+
+        diesel::insert_into(job_analyses::table)
+            .values((
+                job_analyses::job_id.eq(job_id),
+                job_analyses::sample_frequency.eq(settings.sample_frequency),
+                job_analyses::threshold.eq(settings.threshold),
+                job_analyses::max_latency.eq(settings.max_latency),
+                job_analyses::transmitted.eq(&analysis.transmitted),
+                job_analyses::receivers.eq(serde_json::to_value(&analysis.receivers).unwrap()),
+                job_analyses::bit_error_rate.eq(bit_error_rate),
+                job_analyses::latency.eq(latency),
+            ))
+            .on_conflict(job_analyses::job_id)
+            .do_update()
+            .set((
+                job_analyses::sample_frequency.eq(excluded(job_analyses::sample_frequency)),
+                job_analyses::threshold.eq(excluded(job_analyses::threshold)),
+                job_analyses::max_latency.eq(excluded(job_analyses::max_latency)),
+                job_analyses::transmitted.eq(excluded(job_analyses::transmitted)),
+                job_analyses::receivers.eq(excluded(job_analyses::receivers)),
+                job_analyses::bit_error_rate.eq(excluded(job_analyses::bit_error_rate)),
+                job_analyses::latency.eq(excluded(job_analyses::latency)),
+                job_analyses::analyzed_at.eq(diesel::dsl::now),
+            ))
+            .get_result::<JobAnalysis>(&conn)
+            .map_err(|e| e.into())
+    })
+        .await?;
+
+    Ok(Json(analysis))
+}
@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use actix_web::{get, post, web, HttpResponse};
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::web::{Bytes, Json};
+use chrono::Utc;
+use diesel::prelude::*;
+use futures_util::stream::{self, StreamExt as _};
+use log::error;
+
+use core::db::DieselEnum;
+use core::error::ErrorMessaging;
+use core::ErrorMessage as CoreErrorMessage;
+use core::schema::{controllers, experiments, job_artifacts, jobs};
+use core::types::{DBPool, ModelId, Result};
+use derive::operation;
+use service::storage::{ByteStream, Storage};
+use user::models::user::User;
+
+use crate::access::{check_experiment_access, Access};
+use crate::archive::{self, Archive, ArtifactEntry, ExperimentEntry, JobEntry, Manifest};
+use crate::ErrorMessage;
+use crate::handlers::storage::artifact_key;
+use crate::models::artifact::JobArtifact;
+use crate::models::experiment::Experiment;
+use crate::models::job::{Job, JobStatus};
+use crate::requests::{ExportExperimentRequest, ImportExperimentRequest};
+
+/// Storage key of an imported artifact together with its content, put into the storage once its job is created
+type ImportedArtifact = (String, Vec<u8>);
+
+/// Zip archive of the experiment containing a manifest, the code of the experiment, and the selected jobs with their
+/// codes, outputs and artifacts
+#[operation]
+#[get("experiment/{id}/export")]
+pub async fn export_experiment(
+    pool: web::Data<DBPool>,
+    storage: web::Data<Arc<dyn Storage>>,
+    experiment_id: web::Path<ModelId>,
+    user: User,
+    request: web::Query<ExportExperimentRequest>,
+) -> Result<HttpResponse> {
+    let conn = pool.get().unwrap();
+    let experiment_id = experiment_id.into_inner();
+
+    let job_ids = match &request.jobs {
+        Some(jobs) => Some(
+            jobs.split(',')
+                .map(|id| id.trim().parse::<ModelId>())
+                .collect::<std::result::Result<Vec<ModelId>, _>>()
+                .map_err(|_| ErrorMessage::InvalidArchive)?
+        ),
+        None => None
+    };
+
+    if let Some(job_ids) = &job_ids {
+        if job_ids.len() > archive::MAX_JOBS {
+            return Err(Box::new(ErrorMessage::InvalidArchive));
+        }
+    }
+
+    let manifest = web::block(move || -> Result<Manifest> {
+        check_experiment_access(&conn, experiment_id, user.id, Access::Viewer)?;
+
+        let experiment = experiments::table
+            .find(experiment_id)
+            .first::<Experiment>(&conn)?;
+
+        let finished = vec![
+            JobStatus::Successful.value(),
+            JobStatus::Failed.value(),
+            JobStatus::Aborted.value(),
+            JobStatus::TimedOut.value(),
+            JobStatus::Cancelled.value(),
+        ];
+
+        let mut query = jobs::table
+            .inner_join(controllers::table)
+            .filter(jobs::experiment_id.eq(experiment_id))
+            .filter(jobs::status.eq_any(finished))
+            .select((jobs::all_columns, controllers::name))
+            .order_by(jobs::id.desc())
+            .limit(archive::MAX_JOBS as i64)
+            .into_boxed();
+
+        if let Some(job_ids) = job_ids {
+            query = query.filter(jobs::id.eq_any(job_ids));
+        }
+
+        let mut jobs = query.load::<(Job, String)>(&conn)?;
+        jobs.reverse();
+
+        let mut artifacts = job_artifacts::table
+            .filter(job_artifacts::job_id.eq_any(jobs.iter().map(|(job, _)| job.id).collect::<Vec<ModelId>>()))
+            .order_by(job_artifacts::name.asc())
+            .load::<JobArtifact>(&conn)?
+            .into_iter()
+            .fold(HashMap::<ModelId, Vec<ArtifactEntry>>::new(), |mut artifacts, artifact| {
+                artifacts.entry(artifact.job_id).or_default().push(ArtifactEntry {
+                    name: artifact.name,
+                    content_type: artifact.content_type,
+                    size: artifact.size,
+                });
+                artifacts
+            });
+
+        Ok(Manifest::new(
+            Utc::now().naive_utc(),
+            ExperimentEntry {
+                name: decode(experiment.name),
+                code: decode(experiment.code),
+                parameters: experiment.parameters,
+                created_at: experiment.created_at,
+            },
+            jobs.into_iter()
+                .map(|(job, controller)| JobEntry {
+                    id: job.id,
+                    controller,
+                    status: job.status,
+                    code: decode(job.code),
+                    parameters: job.parameters,
+                    priority: job.priority,
+                    created_at: job.created_at,
+                    started_at: job.started_at,
+                    finished_at: job.finished_at,
+                    failure_kind: job.failure_kind,
+                    failure_cause: job.failure_cause,
+                    artifacts: artifacts.remove(&job.id).unwrap_or_default(),
+                })
+                .collect(),
+        ))
+    })
+        .await?;
+
+    let mut files = Vec::new();
+
+    for job in &manifest.jobs {
+        for artifact in &job.artifacts {
+            let mut stream = storage.read(artifact_key(job.id, artifact.name.as_str()).as_str()).await?;
+            let mut content = Vec::with_capacity(artifact.size as usize);
+
+            while let Some(chunk) = stream.next().await {
+                content.extend_from_slice(&chunk?);
+            }
+
+            files.push((archive::artifact_path(job.id, artifact.name.as_str()), content));
+        }
+    }
+
+    let content = web::block(move || {
+        archive::write(&manifest, files)
+            .map_err(|_| CoreErrorMessage::IOError)
+    })
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/zip")
+        .set(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!("experiment-{}.zip", experiment_id))],
+        })
+        .body(content))
+}
+
+/// Recreates the experiment of an exported archive for the user, together with its jobs and their artifacts.
+/// Jobs are assigned to the controllers with the same name, or to the given one if there is no such controller.
+#[operation]
+#[post("experiments/import")]
+pub async fn import_experiment(
+    pool: web::Data<DBPool>,
+    storage: web::Data<Arc<dyn Storage>>,
+    user: User,
+    request: web::Query<ImportExperimentRequest>,
+    mut payload: web::Payload,
+) -> Result<Json<Experiment>> {
+    let mut content = Vec::new();
+
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|_| CoreErrorMessage::IOError)?;
+
+        if content.len() + chunk.len() > archive::MAX_SIZE {
+            return Err(Box::new(ErrorMessage::InvalidArchive));
+        }
+
+        content.extend_from_slice(&chunk);
+    }
+
+    let conn = pool.get().unwrap();
+    let fallback_controller_id = request.controller_id;
+
+    let (experiment, artifacts) = web::block(move || -> Result<(Experiment, Vec<ImportedArtifact>)> {
+        let mut archive = Archive::read(content)?;
+
+        let controller_ids = controllers::table
+            .select((controllers::name, controllers::id))
+            .load::<(String, ModelId)>(&conn)?
+            .into_iter()
+            .collect::<HashMap<String, ModelId>>();
+
+        if let Some(controller_id) = fallback_controller_id {
+            if !controller_ids.values().any(|id| *id == controller_id) {
+                return Err(Box::new(ErrorMessage::UnknownController));
+            }
+        }
+
+        // artifacts are read before anything is written, so that a broken archive is rejected before the database
+        // is touched
+        let exported = archive.manifest.jobs.iter()
+            .enumerate()
+            .flat_map(|(index, job)| job.artifacts.iter().map(move |artifact| (index, job.id, artifact.name.clone(), artifact.content_type.clone())))
+            .collect::<Vec<_>>();
+
+        let mut contents = Vec::with_capacity(exported.len());
+
+        for (_, exported_job_id, name, _) in &exported {
+            contents.push(archive.artifact(*exported_job_id, name.as_str())?);
+        }
+
+        let manifest = &archive.manifest;
+
+        conn.transaction::<_, Box<dyn ErrorMessaging>, _>(|| {
+            let experiment = diesel::insert_into(experiments::table)
+                .values((
+                    experiments::user_id.eq(user.id),
+                    experiments::name.eq(core::encode_minimal(manifest.experiment.name.as_str())),
+                    experiments::code.eq(core::encode_minimal(manifest.experiment.code.as_str())),
+                    experiments::parameters.eq(serde_json::to_value(&manifest.experiment.parameters).unwrap()),
+                    experiments::created_at.eq(manifest.experiment.created_at),
+                ))
+                .get_result::<Experiment>(&conn)?;
+
+            let mut job_ids = Vec::with_capacity(manifest.jobs.len());
+
+            for job in &manifest.jobs {
+                let controller_id = controller_ids.get(&job.controller)
+                    .copied()
+                    .or(fallback_controller_id)
+                    .ok_or(ErrorMessage::UnknownController)?;
+
+                let job_id = diesel::insert_into(jobs::table)
+                    .values((
+                        jobs::experiment_id.eq(experiment.id),
+                        jobs::controller_id.eq(controller_id),
+                        jobs::code.eq(core::encode_minimal(job.code.as_str())),
+                        jobs::status.eq(job.status.value()),
+                        jobs::parameters.eq(&job.parameters),
+                        jobs::priority.eq(job.priority),
+                        jobs::created_at.eq(job.created_at),
+                        jobs::started_at.eq(job.started_at),
+                        jobs::finished_at.eq(job.finished_at),
+                        jobs::failure_kind.eq(&job.failure_kind),
+                        jobs::failure_cause.eq(job.failure_cause.as_ref().map(|cause| cause.value())),
+                    ))
+                    .returning(jobs::id)
+                    .get_result::<ModelId>(&conn)?;
+
+                job_ids.push(job_id);
+            }
+
+            let mut artifacts = Vec::with_capacity(exported.len());
+
+            for ((index, _, name, content_type), content) in exported.into_iter().zip(contents) {
+                let job_id = job_ids[index];
+
+                diesel::insert_into(job_artifacts::table)
+                    .values((
+                        job_artifacts::job_id.eq(job_id),
+                        job_artifacts::name.eq(&name),
+                        job_artifacts::content_type.eq(content_type),
+                        job_artifacts::size.eq(content.len() as i64),
+                    ))
+                    .execute(&conn)?;
+
+                artifacts.push((artifact_key(job_id, name.as_str()), content));
+            }
+
+            Ok((experiment, artifacts))
+        })
+    })
+        .await?;
+
+    // the experiment is committed together with its artifact rows, it is removed again if any of the contents can not
+    // be stored so that a failed import leaves nothing behind
+    let mut stored = Vec::with_capacity(artifacts.len());
+
+    for (key, content) in artifacts {
+        let body: ByteStream = stream::once(async move { Ok(Bytes::from(content)) }).boxed_local();
+
+        if let Err(e) = storage.put(key.as_str(), body).await {
+            discard_import(pool, storage.get_ref(), experiment.id, stored).await;
+
+            return Err(Box::new(e));
+        }
+
+        stored.push(key);
+    }
+
+    Ok(Json(experiment))
+}
+
+/// Removes the stored contents and the experiment of a failed import, jobs and artifact rows go with the experiment
+async fn discard_import(pool: web::Data<DBPool>, storage: &Arc<dyn Storage>, experiment_id: ModelId, keys: Vec<String>) {
+    for key in keys {
+        if let Err(e) = storage.delete(key.as_str()).await {
+            error!("Error while deleting the artifact {} of a failed import, {:?}", key, e);
+        }
+    }
+
+    let conn = pool.get().unwrap();
+
+    let res = web::block(move ||
+        diesel::delete(experiments::table.find(experiment_id))
+            .execute(&conn)
+    )
+        .await;
+
+    if let Err(e) = res {
+        error!("Error while deleting the experiment of a failed import, {:?}", e);
+    }
+}
+
+/// Names and codes are stored html encoded, archives keep them as they are written
+fn decode(value: String) -> String {
+    core::decode_html(value.as_str()).unwrap_or(value)
+}
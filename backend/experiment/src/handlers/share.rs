@@ -0,0 +1,178 @@
+use actix_web::{delete, get, post, put, web, web::Json};
+use diesel::prelude::*;
+
+use core::db::DieselEnum;
+use core::models::paginate::{CountStarOver, Paginate, Pagination, PaginationRequest};
+use core::responses::SuccessResponse;
+use core::sanitized::SanitizedJson;
+use core::schema::{experiment_shares, experiments, users};
+use core::types::{DBPool, ModelId, Result};
+use derive::operation;
+use user::models::user::{SlimUser, User, SLIM_USER_COLUMNS};
+
+use crate::access::{check_experiment_access, Access};
+use crate::ErrorMessage;
+use crate::models::experiment::{Experiment, SlimExperiment, SLIM_EXPERIMENT_COLUMNS};
+use crate::models::share::{ExperimentShare, ShareRole};
+use crate::requests::{ExperimentNameRequest, ShareExperimentRequest};
+
+#[operation]
+#[get("experiments/shared")]
+pub async fn fetch_shared_experiments(
+    pool: web::Data<DBPool>,
+    user: User,
+    pagination: web::Query<PaginationRequest>,
+) -> Result<Json<Pagination<(SlimExperiment, ShareRole)>>> {
+    let conn = pool.get().unwrap();
+
+    let experiments = web::block(move || {
+        experiment_shares::table
+            .filter(experiment_shares::user_id.eq(user.id))
+            .inner_join(experiments::table)
+            .order(experiment_shares::created_at.desc())
+            .select(((SLIM_EXPERIMENT_COLUMNS, experiment_shares::role), CountStarOver))
+            .paginate(pagination.page)
+            .per_page(pagination.per_page)
+            .load_and_count_pages::<(SlimExperiment, ShareRole)>(&conn)
+    })
+    .await?;
+
+    Ok(Json(experiments))
+}
+
+#[operation]
+#[get("experiment/{id}/shares")]
+pub async fn fetch_experiment_shares(
+    pool: web::Data<DBPool>,
+    experiment_id: web::Path<ModelId>,
+    user: User,
+) -> Result<Json<Vec<(ExperimentShare, SlimUser)>>> {
+    let conn = pool.get().unwrap();
+
+    let shares = web::block(move || -> Result<Vec<(ExperimentShare, SlimUser)>> {
+        let experiment_id = experiment_id.into_inner();
+
+        check_experiment_access(&conn, experiment_id, user.id, Access::Owner)?;
+
+        experiment_shares::table
+            .filter(experiment_shares::experiment_id.eq(experiment_id))
+            .inner_join(users::table)
+            .order(experiment_shares::id.asc())
+            .select((experiment_shares::all_columns, SLIM_USER_COLUMNS))
+            .load::<(ExperimentShare, SlimUser)>(&conn)
+            .map_err(|e| e.into())
+    })
+    .await?;
+
+    Ok(Json(shares))
+}
+
+/// Shares the experiment with the user having the given email, the role is updated if it is already shared
+#[operation]
+#[put("experiment/{id}/share")]
+pub async fn share_experiment(
+    pool: web::Data<DBPool>,
+    experiment_id: web::Path<ModelId>,
+    user: User,
+    request: web::Json<ShareExperimentRequest>,
+) -> Result<Json<ExperimentShare>> {
+    let conn = pool.get().unwrap();
+    let request = request.into_inner();
+
+    let share = web::block(move || -> Result<ExperimentShare> {
+        let experiment_id = experiment_id.into_inner();
+
+        check_experiment_access(&conn, experiment_id, user.id, Access::Owner)?;
+
+        let user_id = users::table
+            .filter(users::email.eq(request.email))
+            .select(users::id)
+            .first::<ModelId>(&conn)?;
+
+        if user_id == user.id {
+            return Err(Box::new(ErrorMessage::InvalidShare));
+        }
+
+        diesel::insert_into(experiment_shares::table)
+            .values((
+                experiment_shares::experiment_id.eq(experiment_id),
+                experiment_shares::user_id.eq(user_id),
+                experiment_shares::role.eq(request.role.value()),
+            ))
+            .on_conflict((experiment_shares::experiment_id, experiment_shares::user_id))
+            .do_update()
+            .set(experiment_shares::role.eq(request.role.value()))
+            .get_result::<ExperimentShare>(&conn)
+            .map_err(|e| e.into())
+    })
+    .await?;
+
+    Ok(Json(share))
+}
+
+/// Removes the access of the user, either by the owner or by the user itself
+#[operation]
+#[delete("experiment/{id}/share/{user_id}")]
+pub async fn unshare_experiment(
+    pool: web::Data<DBPool>,
+    path: web::Path<(ModelId, ModelId)>,
+    user: User,
+) -> Result<Json<SuccessResponse>> {
+    let conn = pool.get().unwrap();
+    let (experiment_id, user_id) = path.into_inner();
+
+    web::block(move || -> Result<usize> {
+        if user_id != user.id {
+            check_experiment_access(&conn, experiment_id, user.id, Access::Owner)?;
+        }
+
+        diesel::delete(
+            experiment_shares::table
+                .filter(experiment_shares::experiment_id.eq(experiment_id))
+                .filter(experiment_shares::user_id.eq(user_id))
+        )
+            .execute(&conn)
+            .map_err(|e| e.into())
+    })
+    .await?;
+
+    Ok(Json(SuccessResponse::default()))
+}
+
+/// Copies the code and parameters of an experiment the user has access to into a new experiment of the user
+#[operation]
+#[post("experiment/{id}/fork")]
+pub async fn fork_experiment(
+    pool: web::Data<DBPool>,
+    experiment_id: web::Path<ModelId>,
+    user: User,
+    request: SanitizedJson<ExperimentNameRequest>,
+) -> Result<Json<Experiment>> {
+    let conn = pool.get().unwrap();
+    let request = request.into_inner();
+
+    let experiment = web::block(move || -> Result<Experiment> {
+        let experiment_id = experiment_id.into_inner();
+
+        check_experiment_access(&conn, experiment_id, user.id, Access::Viewer)?;
+
+        let (code, parameters) = experiments::table
+            .find(experiment_id)
+            .select((experiments::code, experiments::parameters))
+            .first::<(String, serde_json::Value)>(&conn)?;
+
+        diesel::insert_into(experiments::table)
+            .values((
+                experiments::user_id.eq(user.id),
+                experiments::name.eq(request.name),
+                experiments::code.eq(code),
+                experiments::parameters.eq(parameters),
+                experiments::forked_from_id.eq(experiment_id),
+            ))
+            .get_result::<Experiment>(&conn)
+            .map_err(|e| e.into())
+    })
+    .await?;
+
+    Ok(Json(experiment))
+}
@@ -1,66 +1,220 @@
+use std::cell::Cell;
+use std::io::Write;
+use std::rc::Rc;
 use std::sync::Arc;
 
-use actix_files::NamedFile;
-use actix_web::{get, post, web};
-use actix_web::http::header::{DispositionParam, DispositionType};
+use actix::Addr;
+use actix_web::{get, post, web, HttpRequest, HttpResponse};
+use actix_web::http::header::{self, ContentDisposition, DispositionParam, DispositionType};
 use actix_web::web::Json;
 use diesel::prelude::*;
-use futures_util::AsyncWriteExt;
 use futures_util::stream::StreamExt as _;
+use log::error;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
 
-use core::{Config, ErrorMessage as CoreErrorMessage};
+use core::ErrorMessage as CoreErrorMessage;
 use core::error::ErrorMessaging;
 use core::responses::{SuccessResponse, TokenResponse};
-use core::schema::{experiments, jobs, controllers};
+use core::schema::{jobs, job_artifacts, controllers, experiments};
 use core::types::{DBPool, ModelId, Result};
 use core::utils::Hash;
+use derive::operation;
+use service::storage::{self, ByteStream, Download, Storage};
 use user::models::user::User;
+use webhook::{Event, EventKind, WebhookServer};
 
+use crate::access::{check_job_access, Access};
 use crate::ErrorMessage;
+use crate::models::artifact::{JobArtifact, MAX_NAME_LENGTH};
 use crate::models::controller::{Controller, ControllerToken};
 
+pub const OUTPUT_FILE_NAME: &str = "output.txt";
+/// Serialized state of the transmitter, uploaded by controllers as an artifact
+pub const STATE_FILE_NAME: &str = "state.txt";
+const OUTPUT_CONTENT_TYPE: &str = "text/plain";
+const DEFAULT_CONTENT_TYPE: &str = "application/octet-stream";
+
+#[operation]
 #[get("job/{id}/output")]
-pub async fn download_job_output(pool: web::Data<DBPool>, job_id: web::Path<ModelId>, user: User, config: web::Data<Arc<Config>>) -> Result<NamedFile> {
+pub async fn download_job_output(pool: web::Data<DBPool>, job_id: web::Path<ModelId>, user: User, storage: web::Data<Arc<dyn Storage>>) -> Result<HttpResponse> {
     let conn = pool.get().unwrap();
 
-    let job_id = web::block(move ||
-        jobs::table
-            .filter(jobs::id.eq(job_id.into_inner()))
-            .inner_join(experiments::table)
-            .filter(experiments::user_id.eq(user.id))
-            .select(jobs::id)
-            .first::<ModelId>(&conn)
-    )
+    let job_id = job_id.into_inner();
+
+    web::block(move || check_job_access(&conn, job_id, user.id, Access::Viewer))
         .await?;
 
-    let named_file = NamedFile::open(format!("{}/{}/output.txt", config.storage_path, job_id))
-        .map_err(|e| {
-            match e.kind() {
-                std::io::ErrorKind::NotFound => CoreErrorMessage::ItemNotFound,
-                _ => CoreErrorMessage::IOError
-            }
-        })?
-        .set_content_disposition(actix_web::http::header::ContentDisposition {
-            disposition: DispositionType::Attachment,
-            parameters: vec![DispositionParam::Filename(String::from("output.txt"))],
-        });
+    let download = storage.get(artifact_key(job_id, OUTPUT_FILE_NAME).as_str(), OUTPUT_FILE_NAME, OUTPUT_CONTENT_TYPE)
+        .await?;
 
-    Ok(named_file)
+    Ok(download_response(download, OUTPUT_FILE_NAME, OUTPUT_CONTENT_TYPE))
 }
 
+#[operation]
 #[post("job/{id}/output")]
 pub async fn store_job_output(
     pool: web::Data<DBPool>,
     hash: web::Data<Hash>,
-    config: web::Data<Arc<Config>>,
+    storage: web::Data<Arc<dyn Storage>>,
+    webhook_server: web::Data<Addr<WebhookServer>>,
     controller_token: web::Query<TokenResponse>,
-    mut stream: web::Payload,
+    stream: web::Payload,
     job_id: web::Path<ModelId>,
 )
     -> Result<Json<SuccessResponse>> {
+    let job_id = job_id.into_inner();
+
+    check_controller_job(pool.clone(), hash.get_ref(), controller_token.token.as_str(), job_id).await?;
+
+    store_artifact(pool, storage.get_ref(), webhook_server.get_ref(), job_id, String::from(OUTPUT_FILE_NAME), String::from(OUTPUT_CONTENT_TYPE), stream).await?;
+
+    Ok(Json(SuccessResponse::default()))
+}
+
+#[operation]
+#[get("job/{id}/artifacts")]
+pub async fn fetch_job_artifacts(pool: web::Data<DBPool>, job_id: web::Path<ModelId>, user: User) -> Result<Json<Vec<JobArtifact>>> {
+    let artifacts = load_artifacts(pool, job_id.into_inner(), user.id).await?;
+
+    Ok(Json(artifacts))
+}
+
+#[operation]
+#[get("job/{id}/artifact/{name}")]
+pub async fn download_job_artifact(
+    pool: web::Data<DBPool>,
+    storage: web::Data<Arc<dyn Storage>>,
+    path: web::Path<(ModelId, String)>,
+    user: User,
+) -> Result<HttpResponse> {
     let conn = pool.get().unwrap();
+    let (job_id, name) = path.into_inner();
+
+    let artifact = web::block(move || -> Result<JobArtifact> {
+        check_job_access(&conn, job_id, user.id, Access::Viewer)?;
+
+        job_artifacts::table
+            .filter(job_artifacts::job_id.eq(job_id))
+            .filter(job_artifacts::name.eq(name))
+            .first::<JobArtifact>(&conn)
+            .map_err(|e| e.into())
+    })
+        .await?;
+
+    let download = storage.get(
+        artifact_key(artifact.job_id, artifact.name.as_str()).as_str(),
+        artifact.name.as_str(),
+        artifact.content_type.as_str(),
+    )
+        .await?;
+
+    Ok(download_response(download, artifact.name.as_str(), artifact.content_type.as_str()))
+}
+
+#[operation]
+#[get("job/{id}/artifacts/zip")]
+pub async fn download_job_artifacts_zip(
+    pool: web::Data<DBPool>,
+    storage: web::Data<Arc<dyn Storage>>,
+    job_id: web::Path<ModelId>,
+    user: User,
+) -> Result<HttpResponse> {
     let job_id = job_id.into_inner();
-    let token = hash.decode::<ControllerToken>(controller_token.token.as_str())
+    let artifacts = load_artifacts(pool, job_id, user.id).await?;
+
+    let mut files = Vec::with_capacity(artifacts.len());
+
+    for artifact in artifacts {
+        let mut stream = storage.read(artifact_key(job_id, artifact.name.as_str()).as_str()).await?;
+        let mut content = Vec::with_capacity(artifact.size as usize);
+
+        while let Some(chunk) = stream.next().await {
+            content.extend_from_slice(&chunk?);
+        }
+
+        files.push((artifact.name, content));
+    }
+
+    let archive = web::block(move || -> Result<Vec<u8>> {
+        let mut zip = ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+
+        for (name, content) in files {
+            zip.start_file(name, options)
+                .map_err(|_| CoreErrorMessage::IOError)?;
+
+            zip.write_all(&content)
+                .map_err(|_| CoreErrorMessage::IOError)?;
+        }
+
+        let cursor = zip.finish()
+            .map_err(|_| CoreErrorMessage::IOError)?;
+
+        Ok(cursor.into_inner())
+    })
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/zip")
+        .set(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!("job-{}-artifacts.zip", job_id))],
+        })
+        .body(archive))
+}
+
+#[operation]
+#[post("job/{id}/artifact/{name}")]
+pub async fn store_job_artifact(
+    pool: web::Data<DBPool>,
+    hash: web::Data<Hash>,
+    storage: web::Data<Arc<dyn Storage>>,
+    webhook_server: web::Data<Addr<WebhookServer>>,
+    controller_token: web::Query<TokenResponse>,
+    request: HttpRequest,
+    stream: web::Payload,
+    path: web::Path<(ModelId, String)>,
+) -> Result<Json<SuccessResponse>> {
+    let (job_id, name) = path.into_inner();
+
+    let content_type = request.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .unwrap_or(DEFAULT_CONTENT_TYPE)
+        .to_string();
+
+    if !JobArtifact::is_valid_name(name.as_str()) || content_type.len() > MAX_NAME_LENGTH {
+        return Err(Box::new(ErrorMessage::InvalidArtifact));
+    }
+
+    check_controller_job(pool.clone(), hash.get_ref(), controller_token.token.as_str(), job_id).await?;
+
+    store_artifact(pool, storage.get_ref(), webhook_server.get_ref(), job_id, name, content_type, stream).await?;
+
+    Ok(Json(SuccessResponse::default()))
+}
+
+pub fn artifact_key(job_id: ModelId, name: &str) -> String {
+    format!("{}/{}", job_id, name)
+}
+
+/// Reads the whole content of the artifact into memory
+pub async fn read_artifact(storage: &Arc<dyn Storage>, job_id: ModelId, name: &str) -> Result<Vec<u8>> {
+    let mut stream = storage.read(artifact_key(job_id, name).as_str()).await?;
+    let mut content = Vec::new();
+
+    while let Some(chunk) = stream.next().await {
+        content.extend_from_slice(&chunk?);
+    }
+
+    Ok(content)
+}
+
+/// Checks that the job is run by the controller owning the token
+async fn check_controller_job(pool: web::Data<DBPool>, hash: &Hash, token: &str, job_id: ModelId) -> Result<()> {
+    let conn = pool.get().unwrap();
+    let token = hash.decode::<ControllerToken>(token)
         .map_err(|_| CoreErrorMessage::InvalidToken)?;
 
     web::block(move || -> Result<()> {
@@ -80,32 +234,110 @@ pub async fn store_job_output(
             Err(Box::new(CoreErrorMessage::ItemNotFound))
         }
     })
-        .await?;
+        .await
+        .map_err(|e| e.into())
+}
 
-    let path = format!("{}/{}", config.storage_path, job_id);
+/// Puts the content into the storage and records it as an artifact of the job, the owner of the job is notified through
+/// webhooks
+async fn store_artifact(
+    pool: web::Data<DBPool>,
+    storage: &Arc<dyn Storage>,
+    webhook_server: &Addr<WebhookServer>,
+    job_id: ModelId,
+    name: String,
+    content_type: String,
+    stream: web::Payload,
+) -> Result<()> {
+    let size = Rc::new(Cell::new(0i64));
+    let counter = size.clone();
 
-    async_std::fs::create_dir_all(&path)
-        .await
-        .map_err(|_| Box::new(CoreErrorMessage::IOError) as Box<dyn ErrorMessaging>)?;
+    let body: ByteStream = stream
+        .map(move |chunk| {
+            let chunk = chunk.map_err(|e| storage::Error::Request(e.to_string()))?;
+            counter.set(counter.get() + chunk.len() as i64);
 
-    let mut file = async_std::fs::OpenOptions::new()
-        .write(true)
-        .create_new(true)
-        .open(format!("{}/output.txt", path))
+            Ok(chunk)
+        })
+        .boxed_local();
+
+    let key = artifact_key(job_id, name.as_str());
+
+    storage.put(key.as_str(), body)
         .await
-        .map_err(|e| match e.kind() {
-            std::io::ErrorKind::AlreadyExists => Box::new(ErrorMessage::OutputAlreadyExist) as Box<dyn ErrorMessaging>,
-            _ => Box::new(CoreErrorMessage::IOError)
+        .map_err(|e| match e {
+            storage::Error::AlreadyExists => Box::new(ErrorMessage::OutputAlreadyExist) as Box<dyn ErrorMessaging>,
+            e => Box::new(e)
         })?;
 
-    while let Some(chunk) = stream.next().await {
-        let bytes = chunk
-            .map_err(|_| Box::new(CoreErrorMessage::IOError) as Box<dyn ErrorMessaging>)?;
+    let conn = pool.get().unwrap();
+    let size = size.get();
 
-        file.write_all(&bytes)
-            .await
-            .map_err(|_| Box::new(CoreErrorMessage::IOError) as Box<dyn ErrorMessaging>)?
-    }
+    let res = web::block(move || -> std::result::Result<(JobArtifact, ModelId), diesel::result::Error> {
+        let artifact = diesel::insert_into(job_artifacts::table)
+            .values((
+                job_artifacts::job_id.eq(job_id),
+                job_artifacts::name.eq(name),
+                job_artifacts::content_type.eq(content_type),
+                job_artifacts::size.eq(size),
+            ))
+            .get_result::<JobArtifact>(&conn)?;
 
-    Ok(Json(SuccessResponse::default()))
+        let user_id = jobs::table
+            .inner_join(experiments::table)
+            .filter(jobs::id.eq(job_id))
+            .select(experiments::user_id)
+            .first::<ModelId>(&conn)?;
+
+        Ok((artifact, user_id))
+    })
+        .await;
+
+    let (artifact, user_id) = match res {
+        Ok(res) => res,
+        Err(e) => {
+            // the content is removed with its row missing, otherwise it could not be uploaded again
+            if let Err(e) = storage.delete(key.as_str()).await {
+                error!("Error while deleting the artifact {} without a row, {:?}", key, e);
+            }
+
+            return Err(e.into());
+        }
+    };
+
+    webhook_server.do_send(Event { user_id, kind: EventKind::JobArtifact, data: artifact });
+
+    Ok(())
+}
+
+async fn load_artifacts(pool: web::Data<DBPool>, job_id: ModelId, user_id: ModelId) -> Result<Vec<JobArtifact>> {
+    let conn = pool.get().unwrap();
+
+    web::block(move || -> Result<Vec<JobArtifact>> {
+        check_job_access(&conn, job_id, user_id, Access::Viewer)?;
+
+        job_artifacts::table
+            .filter(job_artifacts::job_id.eq(job_id))
+            .order_by(job_artifacts::name.asc())
+            .load::<JobArtifact>(&conn)
+            .map_err(|e| e.into())
+    })
+        .await
+        .map_err(|e| e.into())
+}
+
+/// Streams the object through the application or redirects the client to the storage
+fn download_response(download: Download, file_name: &str, content_type: &str) -> HttpResponse {
+    match download {
+        Download::Stream(stream) => HttpResponse::Ok()
+            .content_type(content_type)
+            .set(ContentDisposition {
+                disposition: DispositionType::Attachment,
+                parameters: vec![DispositionParam::Filename(String::from(file_name))],
+            })
+            .streaming(stream.map(|chunk| chunk.map_err(|e| Box::new(e) as Box<dyn ErrorMessaging>))),
+        Download::Redirect(url) => HttpResponse::TemporaryRedirect()
+            .header(header::LOCATION, url)
+            .finish()
+    }
 }
@@ -0,0 +1,250 @@
+use actix_web::{delete, get, post, put, web, web::Json};
+use diesel::prelude::*;
+
+use core::responses::SuccessResponse;
+use core::sanitized::{SanitizedJson, SanitizedQuery};
+use core::schema::{experiments, template_versions, templates};
+use core::types::{DBPool, ModelId, Result};
+use derive::operation;
+use user::models::user::User;
+
+use crate::gallery;
+use crate::models::experiment::Experiment;
+use crate::models::template::{Template, TemplateVersion, TemplateWithVersion};
+use crate::requests::{CreateTemplateRequest, TemplateExperimentRequest, TemplateFilterRequest, UpdateTemplateRequest};
+
+/// Published templates with their latest versions, optionally only the ones having the tag
+#[operation]
+#[get("templates")]
+pub async fn fetch_templates(
+    pool: web::Data<DBPool>,
+    _user: User,
+    filter: SanitizedQuery<TemplateFilterRequest>,
+) -> Result<Json<Vec<TemplateWithVersion>>> {
+    let conn = pool.get().unwrap();
+    let filter = filter.into_inner();
+
+    let templates = web::block(move || {
+        let mut query = templates::table
+            .filter(templates::published.eq(true))
+            .order_by(templates::name.asc())
+            .into_boxed();
+
+        if let Some(tag) = filter.tag {
+            query = query.filter(templates::tags.contains(vec![tag]));
+        }
+
+        let templates = query.load::<Template>(&conn)?;
+
+        gallery::with_latest_versions(&conn, templates)
+    })
+    .await?;
+
+    Ok(Json(templates))
+}
+
+#[operation]
+#[get("template/{id}")]
+pub async fn fetch_template(
+    pool: web::Data<DBPool>,
+    template_id: web::Path<ModelId>,
+    _user: User,
+) -> Result<Json<TemplateWithVersion>> {
+    let conn = pool.get().unwrap();
+
+    let template = web::block(move || -> QueryResult<TemplateWithVersion> {
+        let template = templates::table
+            .find(template_id.into_inner())
+            .filter(templates::published.eq(true))
+            .first::<Template>(&conn)?;
+
+        let latest_version = gallery::latest_version(&conn, template.id)?;
+
+        Ok(TemplateWithVersion { template, latest_version })
+    })
+    .await?;
+
+    Ok(Json(template))
+}
+
+/// Creates a new experiment of the user with the code and parameters of the template
+#[operation]
+#[post("template/{id}/experiment")]
+pub async fn create_experiment_from_template(
+    pool: web::Data<DBPool>,
+    template_id: web::Path<ModelId>,
+    user: User,
+    request: SanitizedJson<TemplateExperimentRequest>,
+) -> Result<Json<Experiment>> {
+    let conn = pool.get().unwrap();
+    let request = request.into_inner();
+
+    let experiment = web::block(move || -> QueryResult<Experiment> {
+        let mut query = template_versions::table
+            .inner_join(templates::table)
+            .filter(templates::id.eq(template_id.into_inner()))
+            .filter(templates::published.eq(true))
+            .order_by(template_versions::version.desc())
+            .select((template_versions::id, template_versions::code, template_versions::parameters))
+            .into_boxed();
+
+        if let Some(version) = request.version {
+            query = query.filter(template_versions::version.eq(version));
+        }
+
+        let (version_id, code, parameters) = query.first::<(ModelId, String, serde_json::Value)>(&conn)?;
+
+        diesel::insert_into(experiments::table)
+            .values((
+                experiments::user_id.eq(user.id),
+                experiments::name.eq(request.name),
+                experiments::code.eq(code),
+                experiments::parameters.eq(parameters),
+                experiments::template_version_id.eq(version_id),
+            ))
+            .get_result::<Experiment>(&conn)
+    })
+    .await?;
+
+    Ok(Json(experiment))
+}
+
+/// All templates including the unpublished ones, only for admins
+#[operation]
+#[get("templates/all")]
+pub async fn fetch_all_templates(
+    pool: web::Data<DBPool>,
+) -> Result<Json<Vec<TemplateWithVersion>>> {
+    let conn = pool.get().unwrap();
+
+    let templates = web::block(move || {
+        let templates = templates::table
+            .order_by(templates::name.asc())
+            .load::<Template>(&conn)?;
+
+        gallery::with_latest_versions(&conn, templates)
+    })
+    .await?;
+
+    Ok(Json(templates))
+}
+
+#[operation]
+#[get("template/{id}/versions")]
+pub async fn fetch_template_versions(
+    pool: web::Data<DBPool>,
+    template_id: web::Path<ModelId>,
+) -> Result<Json<Vec<TemplateVersion>>> {
+    let conn = pool.get().unwrap();
+
+    let versions = web::block(move || {
+        template_versions::table
+            .filter(template_versions::template_id.eq(template_id.into_inner()))
+            .order_by(template_versions::version.desc())
+            .load::<TemplateVersion>(&conn)
+    })
+    .await?;
+
+    Ok(Json(versions))
+}
+
+#[operation]
+#[post("templates")]
+pub async fn create_template(
+    pool: web::Data<DBPool>,
+    request: SanitizedJson<CreateTemplateRequest>,
+) -> Result<Json<TemplateWithVersion>> {
+    let conn = pool.get().unwrap();
+    let request = request.into_inner();
+
+    request.parameters.validate()?;
+
+    let template = web::block(move || {
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let template = diesel::insert_into(templates::table)
+                .values((
+                    templates::name.eq(request.name),
+                    templates::description.eq(request.description),
+                    templates::tags.eq(request.tags),
+                    templates::published.eq(request.published),
+                ))
+                .get_result::<Template>(&conn)?;
+
+            let latest_version = gallery::add_version(&conn, template.id, request.code, request.parameters)?;
+
+            Ok(TemplateWithVersion { template, latest_version })
+        })
+    })
+    .await?;
+
+    Ok(Json(template))
+}
+
+#[operation]
+#[put("template/{id}")]
+pub async fn update_template(
+    pool: web::Data<DBPool>,
+    template_id: web::Path<ModelId>,
+    request: SanitizedJson<UpdateTemplateRequest>,
+) -> Result<Json<TemplateWithVersion>> {
+    let conn = pool.get().unwrap();
+    let request = request.into_inner();
+
+    if let Some(parameters) = &request.parameters {
+        parameters.validate()?;
+    }
+
+    let template = web::block(move || {
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            let template_id = template_id.into_inner();
+            let template = templates::table
+                .find(template_id)
+                .first::<Template>(&conn)?;
+
+            let template = diesel::update(templates::table.find(template_id))
+                .set((
+                    templates::name.eq(request.name.unwrap_or(template.name)),
+                    templates::description.eq(request.description.unwrap_or(template.description)),
+                    templates::tags.eq(request.tags.unwrap_or(template.tags)),
+                    templates::published.eq(request.published.unwrap_or(template.published)),
+                ))
+                .get_result::<Template>(&conn)?;
+
+            let latest_version = if request.code.is_some() || request.parameters.is_some() {
+                let latest = gallery::latest_version(&conn, template_id)?;
+
+                gallery::add_version(
+                    &conn,
+                    template_id,
+                    request.code.unwrap_or(latest.code),
+                    request.parameters.unwrap_or(latest.parameters),
+                )?
+            } else {
+                gallery::latest_version(&conn, template_id)?
+            };
+
+            Ok(TemplateWithVersion { template, latest_version })
+        })
+    })
+    .await?;
+
+    Ok(Json(template))
+}
+
+/// Experiments created from the template are kept, they only lose the link to it
+#[operation]
+#[delete("template/{id}")]
+pub async fn delete_template(
+    pool: web::Data<DBPool>,
+    template_id: web::Path<ModelId>,
+) -> Result<Json<SuccessResponse>> {
+    let conn = pool.get().unwrap();
+
+    web::block(move || {
+        diesel::delete(templates::table.find(template_id.into_inner()))
+            .execute(&conn)
+    })
+    .await?;
+
+    Ok(Json(SuccessResponse::default()))
+}
@@ -1,46 +1,86 @@
+#[macro_use]
+extern crate diesel;
+
 use actix_web::http::StatusCode;
-use actix_web::web;
 
 pub use connection::server::ExperimentServer;
+pub use gallery::import_templates;
 use core::error::{ErrorMessaging, HttpError};
-use user::middlewares::auth::Auth;
-use user::middlewares::role::AdminUser;
+use core::openapi::Access;
+use user::router::Router;
 
+mod access;
+mod analysis;
+mod archive;
 mod handlers;
 mod connection;
+mod gallery;
 pub mod models;
+pub mod parameters;
 mod requests;
+mod scheduler;
+mod watchdog;
+
+pub fn register<R: Router>(router: &mut R) {
+    router.scope("/experiment", Access::Public, |router| {
+        router
+            .service(handlers::join_server)
+            .service(handlers::storage::store_job_output)
+            .service(handlers::storage::store_job_artifact);
+
+        router.scope("", Access::User, |router| {
+            router
+                .service(handlers::fetch_controllers)
+                .service(handlers::fetch_controller)
+                .service(handlers::fetch_experiments)
+                .service(handlers::share::fetch_shared_experiments)
+                .service(handlers::fetch_experiment)
+                .service(handlers::fetch_experiment_jobs)
+                .service(handlers::fetch_job)
+                .service(handlers::abort_running_job)
+                .service(handlers::cancel_job)
+                .service(handlers::rerun_job)
+                .service(handlers::clone_job)
+                .service(handlers::fetch_job_queue_position)
+                .service(handlers::fetch_controller_queue)
+                .service(handlers::storage::download_job_output)
+                .service(handlers::storage::fetch_job_artifacts)
+                .service(handlers::storage::download_job_artifacts_zip)
+                .service(handlers::storage::download_job_artifact)
+                .service(handlers::analysis::fetch_job_analysis)
+                .service(handlers::analysis::analyze_job)
+                .service(handlers::create_new_experiment)
+                .service(handlers::update_experiment_name)
+                .service(handlers::update_experiment_code)
+                .service(handlers::update_experiment_parameters)
+                .service(handlers::run_experiment)
+                .service(handlers::fetch_experiment_sweeps)
+                .service(handlers::fetch_sweep)
+                .service(handlers::delete_experiment)
+                .service(handlers::share::fetch_experiment_shares)
+                .service(handlers::share::share_experiment)
+                .service(handlers::share::unshare_experiment)
+                .service(handlers::share::fork_experiment)
+                .service(handlers::archive::export_experiment)
+                .service(handlers::archive::import_experiment)
+                .service(handlers::template::fetch_templates)
+                .service(handlers::template::fetch_template)
+                .service(handlers::template::create_experiment_from_template);
 
-pub fn register(config: &mut web::ServiceConfig) {
-    config
-        .service(
-            web::scope("/api/experiment")
-                .service(handlers::join_server)
-                .service(handlers::storage::store_job_output)
-                .service(
-                    web::scope("")
-                        .wrap(Auth)
-                        .service(handlers::fetch_controllers)
-                        .service(handlers::fetch_controller)
-                        .service(handlers::fetch_experiments)
-                        .service(handlers::fetch_experiment)
-                        .service(handlers::fetch_experiment_jobs)
-                        .service(handlers::fetch_job)
-                        .service(handlers::abort_running_job)
-                        .service(handlers::storage::download_job_output)
-                        .service(handlers::create_new_experiment)
-                        .service(handlers::update_experiment_name)
-                        .service(handlers::update_experiment_code)
-                        .service(handlers::run_experiment)
-                        .service(handlers::delete_experiment)
-                        .service(
-                            web::scope("")
-                                .wrap(AdminUser)
-                                .service(handlers::controller_receiver_values)
-                                .service(handlers::controller_token)
-                        )
-                )
-        );
+            router.scope("", Access::Admin, |router| {
+                router
+                    .service(handlers::controller_receiver_values)
+                    .service(handlers::controller_token)
+                    .service(handlers::update_job_priority)
+                    .service(handlers::fetch_jobs)
+                    .service(handlers::template::fetch_all_templates)
+                    .service(handlers::template::fetch_template_versions)
+                    .service(handlers::template::create_template)
+                    .service(handlers::template::update_template)
+                    .service(handlers::template::delete_template);
+            });
+        });
+    });
 }
 
 #[derive(Debug)]
@@ -48,6 +88,13 @@ pub enum ErrorMessage {
     UnknownController,
     NotAllowedToRunForSlot,
     OutputAlreadyExist,
+    InvalidParameters,
+    SweepTooLarge,
+    InvalidArtifact,
+    InvalidPriority,
+    InvalidShare,
+    InvalidArchive,
+    InvalidAnalysis,
 }
 
 impl ErrorMessaging for ErrorMessage {
@@ -67,7 +114,42 @@ impl ErrorMessaging for ErrorMessage {
                 code: StatusCode::CONFLICT,
                 error_code: 102,
                 message: String::from("output_already_exist"),
-            }
+            },
+            ErrorMessage::InvalidParameters => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 103,
+                message: String::from("invalid_parameters"),
+            },
+            ErrorMessage::SweepTooLarge => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 104,
+                message: String::from("sweep_too_large"),
+            },
+            ErrorMessage::InvalidArtifact => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 105,
+                message: String::from("invalid_artifact"),
+            },
+            ErrorMessage::InvalidPriority => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 106,
+                message: String::from("invalid_priority"),
+            },
+            ErrorMessage::InvalidShare => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 107,
+                message: String::from("invalid_share"),
+            },
+            ErrorMessage::InvalidArchive => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 108,
+                message: String::from("invalid_archive"),
+            },
+            ErrorMessage::InvalidAnalysis => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 109,
+                message: String::from("invalid_analysis"),
+            },
         }
     }
 }
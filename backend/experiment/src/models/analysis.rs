@@ -0,0 +1,25 @@
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Queryable};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use core::schema::job_analyses;
+use core::types::ModelId;
+
+/// Result of the latest analysis of the receiver output of a job, receivers hold a `ReceiverAnalysis` per receiver.
+/// Bit error rate and latency are the ones of the receiver with the lowest bit error rate.
+#[derive(Identifiable, Queryable, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[table_name = "job_analyses"]
+pub struct JobAnalysis {
+    pub id: ModelId,
+    pub job_id: ModelId,
+    pub sample_frequency: f64,
+    pub threshold: Option<f64>,
+    pub max_latency: i32,
+    pub transmitted: Vec<bool>,
+    pub receivers: serde_json::Value,
+    pub bit_error_rate: f64,
+    pub latency: Option<f64>,
+    pub analyzed_at: NaiveDateTime,
+}
@@ -0,0 +1,27 @@
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Queryable};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use core::schema::job_artifacts;
+use core::types::ModelId;
+
+pub use shared::artifact::MAX_NAME_LENGTH;
+
+#[derive(Identifiable, Queryable, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JobArtifact {
+    pub id: ModelId,
+    pub job_id: ModelId,
+    pub name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub created_at: NaiveDateTime,
+}
+
+impl JobArtifact {
+    /// Names are used both in urls and as the file name of downloads, hence only a safe subset is accepted.
+    pub fn is_valid_name(name: &str) -> bool {
+        shared::artifact::is_valid_name(name)
+    }
+}
@@ -1,5 +1,6 @@
 use chrono::NaiveDateTime;
 use diesel::Queryable;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use core::schema::controllers;
@@ -13,7 +14,7 @@ pub struct Controller {
     pub created_at: NaiveDateTime,
 }
 
-#[derive(Queryable, Serialize)]
+#[derive(Queryable, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SlimController {
     pub id: ModelId,
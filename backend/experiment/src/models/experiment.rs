@@ -1,11 +1,14 @@
 use chrono::NaiveDateTime;
 use diesel::{Identifiable, Queryable};
+use schemars::JsonSchema;
 use serde::Serialize;
 
 use core::schema::experiments;
 use core::types::ModelId;
 
-#[derive(Identifiable, Queryable, Serialize)]
+use crate::parameters::ParameterDefinitions;
+
+#[derive(Identifiable, Queryable, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Experiment {
     pub id: ModelId,
@@ -14,9 +17,14 @@ pub struct Experiment {
     pub code: String,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub parameters: ParameterDefinitions,
+    /// Experiment this one is forked from, None once the original is deleted
+    pub forked_from_id: Option<ModelId>,
+    /// Version of the template this experiment is created from
+    pub template_version_id: Option<ModelId>,
 }
 
-#[derive(Queryable, Serialize)]
+#[derive(Queryable, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SlimExperiment {
     pub id: ModelId,
@@ -2,13 +2,14 @@ use chrono::NaiveDateTime;
 use diesel::{Identifiable, Queryable};
 use diesel::pg::Pg;
 use diesel::sql_types::VarChar;
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use core::db::DieselEnum;
 use core::schema::jobs;
 use core::types::ModelId;
 
-#[derive(Identifiable, Queryable, Serialize, Clone)]
+#[derive(Identifiable, Queryable, Serialize, Clone, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Job {
     pub id: ModelId,
@@ -18,9 +19,17 @@ pub struct Job {
     pub status: JobStatus,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub parameters: serde_json::Value,
+    pub sweep_id: Option<ModelId>,
+    pub priority: i32,
+    pub priority_override: Option<i32>,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+    pub failure_kind: Option<String>,
+    pub failure_cause: Option<FailureCause>,
 }
 
-#[derive(Queryable, Serialize)]
+#[derive(Queryable, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SlimJob {
     pub id: ModelId,
@@ -29,19 +38,57 @@ pub struct SlimJob {
     pub status: JobStatus,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub parameters: serde_json::Value,
+    pub sweep_id: Option<ModelId>,
+    pub priority: i32,
+    pub priority_override: Option<i32>,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+    pub failure_kind: Option<String>,
+    pub failure_cause: Option<FailureCause>,
 }
 
-pub const SLIM_JOB_COLUMNS: (jobs::id, jobs::experiment_id, jobs::controller_id, jobs::status, jobs::created_at, jobs::updated_at) = (
-    jobs::id, jobs::experiment_id, jobs::controller_id, jobs::status, jobs::created_at, jobs::updated_at
+pub type SlimJobColumns = (
+    jobs::id, jobs::experiment_id, jobs::controller_id, jobs::status, jobs::created_at, jobs::updated_at, jobs::parameters,
+    jobs::sweep_id, jobs::priority, jobs::priority_override, jobs::started_at, jobs::finished_at, jobs::failure_kind,
+    jobs::failure_cause
 );
 
+pub const SLIM_JOB_COLUMNS: SlimJobColumns = (
+    jobs::id, jobs::experiment_id, jobs::controller_id, jobs::status, jobs::created_at, jobs::updated_at, jobs::parameters, jobs::sweep_id, jobs::priority, jobs::priority_override, jobs::started_at, jobs::finished_at, jobs::failure_kind, jobs::failure_cause
+);
+
+impl From<Job> for SlimJob {
+    fn from(job: Job) -> Self {
+        SlimJob {
+            id: job.id,
+            experiment_id: job.experiment_id,
+            controller_id: job.controller_id,
+            status: job.status,
+            created_at: job.created_at,
+            updated_at: job.updated_at,
+            parameters: job.parameters,
+            sweep_id: job.sweep_id,
+            priority: job.priority,
+            priority_override: job.priority_override,
+            started_at: job.started_at,
+            finished_at: job.finished_at,
+            failure_kind: job.failure_kind,
+            failure_cause: job.failure_cause,
+        }
+    }
+}
 
-#[derive(Debug, Deserialize, Serialize, PartialEq, Clone)]
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, JsonSchema)]
 pub enum JobStatus {
     Pending,
     Running,
     Successful,
     Failed,
+    Aborted,
+    TimedOut,
+    Cancelled,
 }
 
 impl Default for JobStatus {
@@ -50,6 +97,13 @@ impl Default for JobStatus {
     }
 }
 
+impl JobStatus {
+    /// Whether the job is ended, successfully or not
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, JobStatus::Pending | JobStatus::Running)
+    }
+}
+
 impl Queryable<VarChar, Pg> for JobStatus {
     type Row = String;
 
@@ -57,3 +111,52 @@ impl Queryable<VarChar, Pg> for JobStatus {
         Self::build_from_string(row)
     }
 }
+
+/// Party responsible for the failure of a job
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, JsonSchema, Default)]
+pub enum FailureCause {
+    User,
+    Abort,
+    #[default]
+    Internal,
+}
+
+impl Queryable<VarChar, Pg> for FailureCause {
+    type Row = String;
+
+    fn build(row: Self::Row) -> Self {
+        Self::build_from_string(row)
+    }
+}
+
+impl From<shared::websocket_messages::server::FailureCause> for FailureCause {
+    fn from(cause: shared::websocket_messages::server::FailureCause) -> Self {
+        match cause {
+            shared::websocket_messages::server::FailureCause::User => FailureCause::User,
+            shared::websocket_messages::server::FailureCause::Abort => FailureCause::Abort,
+            shared::websocket_messages::server::FailureCause::Internal => FailureCause::Internal,
+        }
+    }
+}
+
+/// Failure kinds set by the backend itself, the rest are reported by controllers
+pub const TIMED_OUT: &str = "TimedOut";
+pub const CONTROLLER_DISCONNECTED: &str = "ControllerDisconnected";
+pub const JOB_LOST: &str = "JobLost";
+pub const DISPATCH_FAILED: &str = "DispatchFailed";
+
+/// Reason of a job that ended without success
+#[derive(Debug, Clone)]
+pub struct Failure {
+    pub kind: String,
+    pub cause: FailureCause,
+}
+
+impl Failure {
+    pub fn new(kind: &str, cause: FailureCause) -> Self {
+        Failure {
+            kind: String::from(kind),
+            cause,
+        }
+    }
+}
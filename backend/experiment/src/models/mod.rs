@@ -1,3 +1,8 @@
 pub mod experiment;
 pub mod job;
 pub mod controller;
+pub mod sweep;
+pub mod artifact;
+pub mod share;
+pub mod template;
+pub mod analysis;
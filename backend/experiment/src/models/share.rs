@@ -0,0 +1,37 @@
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Queryable};
+use diesel::pg::Pg;
+use diesel::sql_types::VarChar;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use core::db::DieselEnum;
+use core::schema::experiment_shares;
+use core::types::ModelId;
+
+#[derive(Identifiable, Queryable, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExperimentShare {
+    pub id: ModelId,
+    pub experiment_id: ModelId,
+    pub user_id: ModelId,
+    pub role: ShareRole,
+    pub created_at: NaiveDateTime,
+}
+
+/// Viewers can see the code and the jobs of the experiment, editors can also change its name, code and parameters.
+/// Only the owner can run, share or delete the experiment.
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, JsonSchema, Default)]
+pub enum ShareRole {
+    #[default]
+    Viewer,
+    Editor,
+}
+
+impl Queryable<VarChar, Pg> for ShareRole {
+    type Row = String;
+
+    fn build(row: Self::Row) -> Self {
+        Self::build_from_string(row)
+    }
+}
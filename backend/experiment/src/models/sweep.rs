@@ -0,0 +1,99 @@
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Queryable};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use core::schema::sweeps;
+use core::types::ModelId;
+
+use crate::models::job::{JobStatus, SlimJob};
+
+#[derive(Identifiable, Queryable, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Sweep {
+    pub id: ModelId,
+    pub experiment_id: ModelId,
+    pub controller_id: ModelId,
+    pub definition: serde_json::Value,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Serialize, PartialEq, JsonSchema)]
+pub enum SweepStatus {
+    Pending,
+    Running,
+    Successful,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Default, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JobCounts {
+    pub pending: i64,
+    pub running: i64,
+    pub successful: i64,
+    pub failed: i64,
+    pub cancelled: i64,
+}
+
+impl JobCounts {
+    pub fn add(&mut self, status: &JobStatus) {
+        match status {
+            JobStatus::Pending => self.pending += 1,
+            JobStatus::Running => self.running += 1,
+            JobStatus::Successful => self.successful += 1,
+            JobStatus::Failed | JobStatus::Aborted | JobStatus::TimedOut => self.failed += 1,
+            JobStatus::Cancelled => self.cancelled += 1,
+        }
+    }
+
+    fn total(&self) -> i64 {
+        self.pending + self.running + self.successful + self.failed + self.cancelled
+    }
+
+    pub fn status(&self) -> SweepStatus {
+        if self.pending == self.total() {
+            SweepStatus::Pending
+        } else if self.pending + self.running > 0 {
+            SweepStatus::Running
+        } else if self.failed > 0 {
+            SweepStatus::Failed
+        } else if self.cancelled > 0 {
+            SweepStatus::Cancelled
+        } else {
+            SweepStatus::Successful
+        }
+    }
+}
+
+/// Sweep with the aggregate status of the jobs it created
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepSummary {
+    #[serde(flatten)]
+    pub sweep: Sweep,
+    pub status: SweepStatus,
+    pub job_counts: JobCounts,
+}
+
+impl SweepSummary {
+    pub fn new<'a>(sweep: Sweep, statuses: impl Iterator<Item=&'a JobStatus>) -> Self {
+        let mut job_counts = JobCounts::default();
+        statuses.for_each(|status| job_counts.add(status));
+
+        SweepSummary {
+            sweep,
+            status: job_counts.status(),
+            job_counts,
+        }
+    }
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SweepWithJobs {
+    #[serde(flatten)]
+    pub summary: SweepSummary,
+    pub jobs: Vec<SlimJob>,
+}
@@ -0,0 +1,42 @@
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Queryable};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use core::schema::{template_versions, templates};
+use core::types::ModelId;
+
+use crate::parameters::ParameterDefinitions;
+
+/// Starting point for new experiments, only published templates are listed to users
+#[derive(Identifiable, Queryable, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Template {
+    pub id: ModelId,
+    pub name: String,
+    pub description: String,
+    pub tags: Vec<String>,
+    pub published: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Versions are never changed once created, experiments keep pointing to the version they are created from
+#[derive(Identifiable, Queryable, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateVersion {
+    pub id: ModelId,
+    pub template_id: ModelId,
+    pub version: i32,
+    pub code: String,
+    pub parameters: ParameterDefinitions,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateWithVersion {
+    #[serde(flatten)]
+    pub template: Template,
+    pub latest_version: TemplateVersion,
+}
@@ -0,0 +1,249 @@
+use std::collections::{BTreeMap, HashSet};
+
+use diesel::pg::Pg;
+use diesel::Queryable;
+use diesel::sql_types::Jsonb;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use core::sanitized::Sanitize;
+
+use crate::ErrorMessage;
+
+/// Upper bound of jobs that a single sweep can create
+pub const MAX_SWEEP_SIZE: usize = 100;
+
+const MAX_NAME_LENGTH: usize = 64;
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, JsonSchema)]
+pub enum ParameterKind {
+    Integer,
+    Float,
+    Boolean,
+    String,
+}
+
+impl ParameterKind {
+    fn accepts(&self, value: &Value) -> bool {
+        match self {
+            ParameterKind::Integer => value.is_i64(),
+            ParameterKind::Float => value.is_number(),
+            ParameterKind::Boolean => value.is_boolean(),
+            ParameterKind::String => value.is_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ParameterDefinition {
+    pub name: String,
+    pub kind: ParameterKind,
+    pub default: Option<Value>,
+}
+
+/// Typed parameters an experiment declares, stored as jsonb in experiments.parameters
+#[derive(Debug, Deserialize, Serialize, Clone, Default, JsonSchema)]
+#[serde(transparent)]
+pub struct ParameterDefinitions(pub Vec<ParameterDefinition>);
+
+impl Queryable<Jsonb, Pg> for ParameterDefinitions {
+    type Row = Value;
+
+    fn build(row: Self::Row) -> Self {
+        serde_json::from_value(row).unwrap_or_default()
+    }
+}
+
+// names are validated and defaults are only passed to jobs as JSON, hence nothing to sanitize
+impl Sanitize for ParameterDefinitions {
+    fn sanitize(self) -> Self {
+        self
+    }
+}
+
+/// Values of the parameters a job is run with, keyed by parameter name
+pub type ParameterValues = Map<String, Value>;
+
+#[derive(Debug, Deserialize, Serialize, Clone, JsonSchema)]
+#[serde(tag = "kind")]
+pub enum SweepDefinition {
+    /// Every combination of the given values, later parameters vary fastest
+    Grid { parameters: BTreeMap<String, Vec<Value>> },
+    /// Explicitly listed value sets
+    List { values: Vec<ParameterValues> },
+}
+
+impl ParameterDefinitions {
+    pub fn validate(&self) -> Result<(), ErrorMessage> {
+        let mut names = HashSet::new();
+
+        for definition in &self.0 {
+            if !is_valid_name(definition.name.as_str()) || !names.insert(definition.name.as_str()) {
+                return Err(ErrorMessage::InvalidParameters);
+            }
+
+            if let Some(default) = &definition.default {
+                if !definition.kind.accepts(default) {
+                    return Err(ErrorMessage::InvalidParameters);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks given values against the definitions and fills the missing ones with their defaults.
+    pub fn resolve(&self, values: &ParameterValues) -> Result<ParameterValues, ErrorMessage> {
+        if values.keys().any(|name| !self.0.iter().any(|definition| &definition.name == name)) {
+            return Err(ErrorMessage::InvalidParameters);
+        }
+
+        let mut resolved = Map::new();
+
+        for definition in &self.0 {
+            let value = values.get(&definition.name)
+                .or(definition.default.as_ref())
+                .ok_or(ErrorMessage::InvalidParameters)?;
+
+            if !definition.kind.accepts(value) {
+                return Err(ErrorMessage::InvalidParameters);
+            }
+
+            resolved.insert(definition.name.clone(), value.clone());
+        }
+
+        Ok(resolved)
+    }
+
+    /// Expands the sweep into resolved value sets, one for each job. `base` values are shared by all of them.
+    pub fn expand(&self, base: &ParameterValues, sweep: &SweepDefinition) -> Result<Vec<ParameterValues>, ErrorMessage> {
+        let combinations = match sweep {
+            SweepDefinition::Grid { parameters } => {
+                let size = parameters.values()
+                    .try_fold(1usize, |size, values| size.checked_mul(values.len()))
+                    .ok_or(ErrorMessage::SweepTooLarge)?;
+
+                if size > MAX_SWEEP_SIZE {
+                    return Err(ErrorMessage::SweepTooLarge);
+                }
+
+                parameters.iter()
+                    .fold(vec![base.clone()], |combinations, (name, values)| {
+                        combinations.iter()
+                            .flat_map(|combination| values.iter().map(move |value| {
+                                let mut combination = combination.clone();
+                                combination.insert(name.clone(), value.clone());
+                                combination
+                            }))
+                            .collect()
+                    })
+            }
+            SweepDefinition::List { values } => {
+                if values.len() > MAX_SWEEP_SIZE {
+                    return Err(ErrorMessage::SweepTooLarge);
+                }
+
+                values.iter()
+                    .map(|values| {
+                        let mut combination = base.clone();
+                        combination.extend(values.clone());
+                        combination
+                    })
+                    .collect()
+            }
+        };
+
+        if combinations.is_empty() {
+            return Err(ErrorMessage::InvalidParameters);
+        }
+
+        combinations.iter()
+            .map(|combination| self.resolve(combination))
+            .collect()
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+
+    name.len() <= MAX_NAME_LENGTH && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn definitions() -> ParameterDefinitions {
+        serde_json::from_value(json!([
+            { "name": "spray_duration", "kind": "Integer", "default": 20 },
+            { "name": "pause_duration", "kind": "Integer" },
+            { "name": "encoder", "kind": "String", "default": "word" },
+        ])).unwrap()
+    }
+
+    fn values(value: Value) -> ParameterValues {
+        value.as_object().unwrap().clone()
+    }
+
+    #[test]
+    fn resolve_fills_defaults_and_checks_kinds() {
+        let definitions = definitions();
+
+        let resolved = definitions.resolve(&values(json!({ "pause_duration": 25 }))).unwrap();
+        assert_eq!(Value::Object(resolved), json!({ "spray_duration": 20, "pause_duration": 25, "encoder": "word" }));
+
+        assert!(definitions.resolve(&values(json!({}))).is_err());
+        assert!(definitions.resolve(&values(json!({ "pause_duration": 2.5 }))).is_err());
+        assert!(definitions.resolve(&values(json!({ "pause_duration": 25, "unknown": 1 }))).is_err());
+    }
+
+    #[test]
+    fn grid_expands_into_every_combination() {
+        let sweep: SweepDefinition = serde_json::from_value(json!({
+            "kind": "Grid",
+            "parameters": { "spray_duration": [10, 20, 30], "pause_duration": [25, 50] }
+        })).unwrap();
+
+        let combinations = definitions().expand(&Map::new(), &sweep).unwrap();
+
+        assert_eq!(combinations.len(), 6);
+        assert_eq!(combinations[0]["pause_duration"], json!(25));
+        assert_eq!(combinations[0]["spray_duration"], json!(10));
+        assert_eq!(combinations[1]["spray_duration"], json!(20));
+        assert_eq!(combinations[5]["pause_duration"], json!(50));
+    }
+
+    #[test]
+    fn list_is_merged_with_base_values() {
+        let sweep: SweepDefinition = serde_json::from_value(json!({
+            "kind": "List",
+            "values": [{ "spray_duration": 10 }, { "spray_duration": 15, "pause_duration": 30 }]
+        })).unwrap();
+
+        let combinations = definitions().expand(&values(json!({ "pause_duration": 25 })), &sweep).unwrap();
+
+        assert_eq!(combinations[0]["pause_duration"], json!(25));
+        assert_eq!(combinations[1]["pause_duration"], json!(30));
+    }
+
+    #[test]
+    fn oversized_sweep_is_rejected() {
+        let sweep = SweepDefinition::Grid {
+            parameters: vec![
+                ("spray_duration".to_string(), (0..20).map(Value::from).collect()),
+                ("pause_duration".to_string(), (0..20).map(Value::from).collect()),
+            ].into_iter().collect()
+        };
+
+        assert!(matches!(definitions().expand(&Map::new(), &sweep), Err(ErrorMessage::SweepTooLarge)));
+    }
+}
@@ -1,14 +1,135 @@
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 use core::sanitized::Sanitize;
+use core::types::ModelId;
 use derive::Sanitize;
 
-#[derive(Deserialize, Sanitize)]
+use crate::models::job::{FailureCause, JobStatus};
+use crate::models::share::ShareRole;
+use crate::parameters::{ParameterDefinitions, ParameterValues, SweepDefinition};
+
+#[derive(Deserialize, Sanitize, JsonSchema)]
 pub struct ExperimentNameRequest {
     pub name: String,
 }
 
-#[derive(Deserialize, Sanitize)]
+#[derive(Deserialize, Sanitize, JsonSchema)]
 pub struct ExperimentCodeRequest {
     pub code: String,
-}
\ No newline at end of file
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ExperimentParametersRequest {
+    pub parameters: ParameterDefinitions,
+}
+
+/// Body of run_experiment, either a single value set or a sweep. Missing values are filled with defaults.
+#[derive(Deserialize, Default, JsonSchema)]
+pub struct RunExperimentRequest {
+    #[serde(default)]
+    pub parameters: ParameterValues,
+    pub sweep: Option<SweepDefinition>,
+    /// Jobs with higher priority are picked first, in the range of 0 to `scheduler::MAX_USER_PRIORITY`
+    #[serde(default)]
+    pub priority: i32,
+}
+
+/// Priority set by admins, None removes the override
+#[derive(Deserialize, JsonSchema)]
+pub struct JobPriorityRequest {
+    pub priority: Option<i32>,
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct JobFilterRequest {
+    pub status: Option<JobStatus>,
+    pub failure_cause: Option<FailureCause>,
+    pub controller_id: Option<ModelId>,
+}
+
+#[derive(Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum JobSortField {
+    #[default]
+    CreatedAt,
+    StartedAt,
+    FinishedAt,
+    Priority,
+}
+
+/// Search matches the experiments whose name or code contains all the words given
+#[derive(Deserialize, JsonSchema)]
+pub struct ExperimentFilterRequest {
+    pub search: Option<String>,
+}
+
+#[derive(Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum ExperimentSortField {
+    #[default]
+    CreatedAt,
+    UpdatedAt,
+    Name,
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct ShareExperimentRequest {
+    pub email: String,
+    pub role: ShareRole,
+}
+
+#[derive(Deserialize, Sanitize, JsonSchema)]
+pub struct TemplateFilterRequest {
+    pub tag: Option<String>,
+}
+
+#[derive(Deserialize, Sanitize, JsonSchema)]
+pub struct CreateTemplateRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default = "published_default")]
+    pub published: bool,
+    pub code: String,
+    #[serde(default)]
+    pub parameters: ParameterDefinitions,
+}
+
+fn published_default() -> bool {
+    true
+}
+
+/// Only the given fields are changed, a new version is added if the code or parameters are given
+#[derive(Deserialize, Sanitize, JsonSchema)]
+pub struct UpdateTemplateRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub published: Option<bool>,
+    pub code: Option<String>,
+    pub parameters: Option<ParameterDefinitions>,
+}
+
+/// Creates the experiment from the given version of the template, from the latest one if it is not given
+#[derive(Deserialize, Sanitize, JsonSchema)]
+pub struct TemplateExperimentRequest {
+    pub name: String,
+    pub version: Option<i32>,
+}
+
+/// Ids of the jobs to export separated by commas, the latest finished jobs are exported if it is not given
+#[derive(Deserialize, JsonSchema)]
+pub struct ExportExperimentRequest {
+    pub jobs: Option<String>,
+}
+
+/// Controller to assign the imported jobs whose controller does not exist in this deployment
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportExperimentRequest {
+    pub controller_id: Option<ModelId>,
+}
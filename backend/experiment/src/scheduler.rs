@@ -0,0 +1,297 @@
+use std::cmp::max;
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDateTime};
+use diesel::prelude::*;
+use diesel::sql_types::{Integer, Nullable};
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use core::db::DieselEnum;
+use core::schema::{experiments, jobs, slots};
+use core::types::ModelId;
+
+use crate::models::job::JobStatus;
+
+/// Upper bound of the priority users can give to their jobs, admins can override it with any value
+pub const MAX_USER_PRIORITY: i32 = 10;
+
+// in seconds, used for experiments without any successful job yet
+const DEFAULT_EXPECTED_DURATION: i64 = 5 * 60;
+// number of latest successful jobs that the expected duration of an experiment is averaged over
+const DURATION_SAMPLE_SIZE: i64 = 10;
+
+sql_function!(fn coalesce(x: Nullable<Integer>, y: Integer) -> Integer);
+
+#[derive(Queryable)]
+pub struct QueuedJob {
+    pub job_id: ModelId,
+    pub experiment_id: ModelId,
+    pub user_id: ModelId,
+    pub priority: i32,
+}
+
+pub struct SlotWindow {
+    pub user_id: ModelId,
+    pub start_at: NaiveDateTime,
+    pub end_at: NaiveDateTime,
+}
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuePosition {
+    pub job_id: ModelId,
+    /// 1 based position in the queue of the controller
+    pub position: usize,
+    pub priority: i32,
+    pub estimated_start_at: Option<NaiveDateTime>,
+}
+
+/// Queued job as seen by any user, ids are only revealed to the owner of the job
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueEntry {
+    pub position: usize,
+    pub job_id: Option<ModelId>,
+    pub own: bool,
+    pub priority: i32,
+    // in seconds
+    pub expected_duration: i64,
+    pub estimated_start_at: Option<NaiveDateTime>,
+}
+
+pub struct QueueEstimate {
+    pub queue: Vec<QueuedJob>,
+    pub start_times: Vec<Option<NaiveDateTime>>,
+    pub expected_durations: HashMap<ModelId, Duration>,
+}
+
+impl QueueEstimate {
+    pub fn position(&self, job_id: ModelId) -> Option<QueuePosition> {
+        self.queue.iter()
+            .position(|job| job.job_id == job_id)
+            .map(|index| QueuePosition {
+                job_id,
+                position: index + 1,
+                priority: self.queue[index].priority,
+                estimated_start_at: self.start_times[index],
+            })
+    }
+
+    pub fn entries(&self, user_id: ModelId) -> Vec<QueueEntry> {
+        self.queue.iter()
+            .zip(&self.start_times)
+            .enumerate()
+            .map(|(index, (job, start_at))| QueueEntry {
+                position: index + 1,
+                job_id: if job.user_id == user_id { Some(job.job_id) } else { None },
+                own: job.user_id == user_id,
+                priority: job.priority,
+                expected_duration: self.expected_durations[&job.experiment_id].num_seconds(),
+                estimated_start_at: *start_at,
+            })
+            .collect()
+    }
+}
+
+/// Pending jobs of the controller in the order they are picked. Jobs with higher priority come first, the override
+/// given by admins takes precedence over the priority given by the user. Jobs with the same priority are first come
+/// first served.
+pub fn queue(conn: &PgConnection, controller_id: ModelId) -> QueryResult<Vec<QueuedJob>> {
+    jobs::table
+        .inner_join(experiments::table)
+        .filter(jobs::controller_id.eq(controller_id))
+        .filter(jobs::status.eq(JobStatus::Pending.value()))
+        .order_by((coalesce(jobs::priority_override, jobs::priority).desc(), jobs::id.asc()))
+        .select((jobs::id, jobs::experiment_id, experiments::user_id, coalesce(jobs::priority_override, jobs::priority)))
+        .load::<QueuedJob>(conn)
+}
+
+/// Next job to run on the controller. Only jobs of the user who owns the current slot of the controller can run.
+pub fn next_job(conn: &PgConnection, controller_id: ModelId, now: NaiveDateTime) -> QueryResult<Option<ModelId>> {
+    let slot_owner_id = slots::table
+        .filter(slots::controller_id.eq(controller_id))
+        .filter(slots::start_at.le(now).and(slots::end_at.ge(now)))
+        .select(slots::user_id)
+        .first::<ModelId>(conn)
+        .optional()?;
+
+    let slot_owner_id = match slot_owner_id {
+        Some(user_id) => user_id,
+        None => return Ok(None)
+    };
+
+    jobs::table
+        .inner_join(experiments::table)
+        .filter(jobs::controller_id.eq(controller_id))
+        .filter(jobs::status.eq(JobStatus::Pending.value()))
+        .filter(experiments::user_id.eq(slot_owner_id))
+        .order_by((coalesce(jobs::priority_override, jobs::priority).desc(), jobs::id.asc()))
+        .select(jobs::id)
+        .first::<ModelId>(conn)
+        .optional()
+}
+
+pub fn estimate(conn: &PgConnection, controller_id: ModelId, now: NaiveDateTime) -> QueryResult<QueueEstimate> {
+    let queue = queue(conn, controller_id)?;
+
+    let running = jobs::table
+        .filter(jobs::controller_id.eq(controller_id))
+        .filter(jobs::status.eq(JobStatus::Running.value()))
+        .select((jobs::experiment_id, jobs::started_at))
+        .first::<(ModelId, Option<NaiveDateTime>)>(conn)
+        .optional()?;
+
+    let mut experiment_ids = queue.iter()
+        .map(|job| job.experiment_id)
+        .chain(running.iter().map(|(experiment_id, _)| *experiment_id))
+        .collect::<Vec<ModelId>>();
+    experiment_ids.sort_unstable();
+    experiment_ids.dedup();
+
+    let expected_durations = expected_durations(conn, experiment_ids)?;
+
+    // controller is free once the running job is expected to end
+    let free_at = running
+        .map(|(experiment_id, started_at)| max(now, started_at.unwrap_or(now) + expected_durations[&experiment_id]))
+        .unwrap_or(now);
+
+    let slots = slots::table
+        .filter(slots::controller_id.eq(controller_id))
+        .filter(slots::end_at.gt(now))
+        .order_by(slots::start_at.asc())
+        .select((slots::user_id, slots::start_at, slots::end_at))
+        .load::<(ModelId, NaiveDateTime, NaiveDateTime)>(conn)?
+        .into_iter()
+        .map(|(user_id, start_at, end_at)| SlotWindow { user_id, start_at, end_at })
+        .collect::<Vec<SlotWindow>>();
+
+    let start_times = estimate_start_times(&queue, &slots, &expected_durations, free_at);
+
+    Ok(QueueEstimate {
+        queue,
+        start_times,
+        expected_durations,
+    })
+}
+
+/// Measured durations of the experiments, the default is used for the ones without any successful job yet
+pub fn expected_durations(conn: &PgConnection, experiment_ids: Vec<ModelId>) -> QueryResult<HashMap<ModelId, Duration>> {
+    let measured = measured_durations(conn, experiment_ids.clone())?;
+
+    Ok(experiment_ids.into_iter()
+        .map(|experiment_id| {
+            let duration = measured.get(&experiment_id)
+                .cloned()
+                .unwrap_or_else(|| Duration::seconds(DEFAULT_EXPECTED_DURATION));
+
+            (experiment_id, duration)
+        })
+        .collect())
+}
+
+/// Average duration of the latest successful jobs of the experiments, experiments without any are left out
+pub fn measured_durations(conn: &PgConnection, experiment_ids: Vec<ModelId>) -> QueryResult<HashMap<ModelId, Duration>> {
+    let mut durations = HashMap::new();
+
+    for experiment_id in experiment_ids {
+        let samples = jobs::table
+            .filter(jobs::experiment_id.eq(experiment_id))
+            .filter(jobs::status.eq(JobStatus::Successful.value()))
+            .filter(jobs::started_at.is_not_null().and(jobs::finished_at.is_not_null()))
+            .order_by(jobs::id.desc())
+            .limit(DURATION_SAMPLE_SIZE)
+            .select((jobs::started_at, jobs::finished_at))
+            .load::<(Option<NaiveDateTime>, Option<NaiveDateTime>)>(conn)?
+            .into_iter()
+            .filter_map(|(started_at, finished_at)| Some(finished_at? - started_at?))
+            .collect::<Vec<Duration>>();
+
+        if !samples.is_empty() {
+            durations.insert(experiment_id, samples.iter().fold(Duration::zero(), |sum, duration| sum + *duration) / samples.len() as i32);
+        }
+    }
+
+    Ok(durations)
+}
+
+/// Simulates the controller to find when each queued job starts. Whenever the controller is free, the first queued
+/// job of the current slot owner runs, if there is none controller waits for the next slot whose owner has queued jobs.
+/// Jobs whose owner has no upcoming slot are not going to start, their estimation is None.
+pub fn estimate_start_times(
+    queue: &[QueuedJob],
+    slots: &[SlotWindow],
+    expected_durations: &HashMap<ModelId, Duration>,
+    free_at: NaiveDateTime,
+) -> Vec<Option<NaiveDateTime>> {
+    let mut start_times = vec![None; queue.len()];
+    let mut remaining = (0..queue.len()).collect::<Vec<usize>>();
+    let mut now = free_at;
+
+    while !remaining.is_empty() {
+        let current_slot = slots.iter()
+            .find(|slot| slot.start_at <= now && now < slot.end_at);
+
+        if let Some(slot) = current_slot {
+            match remaining.iter().position(|index| queue[*index].user_id == slot.user_id) {
+                Some(position) => {
+                    let index = remaining.remove(position);
+                    start_times[index] = Some(now);
+                    now += expected_durations[&queue[index].experiment_id];
+                }
+                None => now = slot.end_at
+            }
+
+            continue;
+        }
+
+        let next_slot = slots.iter()
+            .filter(|slot| slot.start_at > now)
+            .find(|slot| remaining.iter().any(|index| queue[*index].user_id == slot.user_id));
+
+        match next_slot {
+            Some(slot) => now = slot.start_at,
+            None => break
+        }
+    }
+
+    start_times
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes: i64) -> NaiveDateTime {
+        NaiveDateTime::from_timestamp(0, 0) + Duration::minutes(minutes)
+    }
+
+    fn job(job_id: ModelId, user_id: ModelId) -> QueuedJob {
+        QueuedJob { job_id, experiment_id: 1, user_id, priority: 0 }
+    }
+
+    #[test]
+    fn jobs_start_in_slots_of_their_owners() {
+        let queue = vec![job(1, 10), job(2, 20), job(3, 10), job(4, 30)];
+        let slots = vec![
+            SlotWindow { user_id: 20, start_at: at(0), end_at: at(50) },
+            SlotWindow { user_id: 10, start_at: at(60), end_at: at(110) },
+        ];
+        let durations = vec![(1, Duration::minutes(20))].into_iter().collect();
+
+        let start_times = estimate_start_times(&queue, &slots, &durations, at(5));
+
+        assert_eq!(start_times, vec![Some(at(60)), Some(at(5)), Some(at(80)), None]);
+    }
+
+    #[test]
+    fn running_job_delays_the_queue() {
+        let queue = vec![job(1, 10), job(2, 10)];
+        let slots = vec![SlotWindow { user_id: 10, start_at: at(0), end_at: at(50) }];
+        let durations = vec![(1, Duration::minutes(10))].into_iter().collect();
+
+        let start_times = estimate_start_times(&queue, &slots, &durations, at(15));
+
+        assert_eq!(start_times, vec![Some(at(15)), Some(at(25))]);
+    }
+}
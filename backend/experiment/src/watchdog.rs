@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDateTime};
+use diesel::prelude::*;
+
+use core::db::DieselEnum;
+use core::schema::{experiments, jobs};
+use core::types::ModelId;
+
+use crate::models::job::{self, Failure, FailureCause, JobStatus};
+use crate::scheduler;
+
+/// How often running jobs are checked, in seconds
+pub const CHECK_INTERVAL: u64 = 60;
+// in seconds, time given to a job on top of its expected duration before it is considered stuck
+const GRACE_PERIOD: i64 = 10 * 60;
+// in seconds, time a controller can stay disconnected before its running job is considered lost
+const DISCONNECT_THRESHOLD: i64 = 5 * 60;
+
+#[derive(Debug, PartialEq)]
+pub enum StaleReason {
+    TimedOut,
+    ControllerDisconnected,
+}
+
+impl StaleReason {
+    pub fn status(&self) -> JobStatus {
+        match self {
+            StaleReason::TimedOut => JobStatus::TimedOut,
+            StaleReason::ControllerDisconnected => JobStatus::Failed,
+        }
+    }
+
+    pub fn failure(&self) -> Failure {
+        match self {
+            // most likely the code of the experiment hangs
+            StaleReason::TimedOut => Failure::new(job::TIMED_OUT, FailureCause::User),
+            StaleReason::ControllerDisconnected => Failure::new(job::CONTROLLER_DISCONNECTED, FailureCause::Internal),
+        }
+    }
+}
+
+pub struct StaleJob {
+    pub job_id: ModelId,
+    pub controller_id: ModelId,
+    pub user_id: ModelId,
+    pub reason: StaleReason,
+}
+
+struct RunningJob {
+    job_id: ModelId,
+    experiment_id: ModelId,
+    controller_id: ModelId,
+    user_id: ModelId,
+    started_at: Option<NaiveDateTime>,
+}
+
+/// Time a job can run before it is considered stuck. Experiments that have not succeeded yet have no measured
+/// duration, they are given the maximum run time instead of a guess.
+pub fn time_limit(measured_duration: Option<Duration>, max_run_time: Duration) -> Duration {
+    match measured_duration {
+        Some(duration) => duration + Duration::seconds(GRACE_PERIOD),
+        None => max_run_time
+    }
+}
+
+/// Decides whether a running job is stuck. `disconnected_since` is None if the controller of the job is connected.
+pub fn stale_reason(
+    now: NaiveDateTime,
+    started_at: NaiveDateTime,
+    time_limit: Duration,
+    disconnected_since: Option<NaiveDateTime>,
+) -> Option<StaleReason> {
+    match disconnected_since {
+        Some(since) if now - since > Duration::seconds(DISCONNECT_THRESHOLD) => Some(StaleReason::ControllerDisconnected),
+        _ if now - started_at > time_limit => Some(StaleReason::TimedOut),
+        _ => None
+    }
+}
+
+/// Running jobs that are stuck. Controllers that are not in `connected` and not in `disconnected_since` have not
+/// connected since the server is started at `started_at`.
+pub fn stale_jobs(
+    conn: &PgConnection,
+    now: NaiveDateTime,
+    started_at: NaiveDateTime,
+    connected: &[ModelId],
+    disconnected_since: &HashMap<ModelId, NaiveDateTime>,
+    max_run_time: Duration,
+) -> QueryResult<Vec<StaleJob>> {
+    let running = jobs::table
+        .inner_join(experiments::table)
+        .filter(jobs::status.eq(JobStatus::Running.value()))
+        .select((jobs::id, jobs::experiment_id, jobs::controller_id, experiments::user_id, jobs::started_at))
+        .load::<(ModelId, ModelId, ModelId, ModelId, Option<NaiveDateTime>)>(conn)?
+        .into_iter()
+        .map(|(job_id, experiment_id, controller_id, user_id, started_at)| RunningJob {
+            job_id,
+            experiment_id,
+            controller_id,
+            user_id,
+            started_at,
+        })
+        .collect::<Vec<RunningJob>>();
+
+    let mut experiment_ids = running.iter()
+        .map(|job| job.experiment_id)
+        .collect::<Vec<ModelId>>();
+    experiment_ids.sort_unstable();
+    experiment_ids.dedup();
+
+    let measured_durations = scheduler::measured_durations(conn, experiment_ids)?;
+
+    Ok(running.into_iter()
+        .filter_map(|job| {
+            let disconnected_since = if connected.contains(&job.controller_id) {
+                None
+            } else {
+                Some(*disconnected_since.get(&job.controller_id).unwrap_or(&started_at))
+            };
+
+            // jobs that were started before started_at was recorded are measured from the server start
+            let reason = stale_reason(
+                now,
+                job.started_at.unwrap_or(started_at),
+                time_limit(measured_durations.get(&job.experiment_id).cloned(), max_run_time),
+                disconnected_since,
+            )?;
+
+            Some(StaleJob {
+                job_id: job.job_id,
+                controller_id: job.controller_id,
+                user_id: job.user_id,
+                reason,
+            })
+        })
+        .collect())
+}
+
+/// Ends the jobs with the failure unless they are finished in the meantime, returns the ones that are ended
+pub fn fail_jobs(
+    conn: &PgConnection,
+    now: NaiveDateTime,
+    job_ids: Vec<ModelId>,
+    status: JobStatus,
+    failure: Failure,
+) -> QueryResult<Vec<ModelId>> {
+    diesel::update(
+        jobs::table
+            .filter(jobs::id.eq_any(job_ids))
+            .filter(jobs::status.eq(JobStatus::Running.value()))
+    )
+        .set((
+            jobs::status.eq(status.value()),
+            jobs::finished_at.eq(now),
+            jobs::failure_kind.eq(failure.kind),
+            jobs::failure_cause.eq(failure.cause.value()),
+        ))
+        .returning(jobs::id)
+        .get_results(conn)
+}
+
+/// Fails the jobs that are Running on the controller other than the one it reports to be running while joining,
+/// returns the failed jobs with their owners
+pub fn reconcile(
+    conn: &PgConnection,
+    now: NaiveDateTime,
+    controller_id: ModelId,
+    running_job_id: Option<ModelId>,
+) -> QueryResult<Vec<(ModelId, ModelId)>> {
+    let lost = jobs::table
+        .inner_join(experiments::table)
+        .filter(jobs::controller_id.eq(controller_id))
+        .filter(jobs::status.eq(JobStatus::Running.value()))
+        .filter(jobs::id.ne(running_job_id.unwrap_or(0)))
+        .select((jobs::id, experiments::user_id))
+        .load::<(ModelId, ModelId)>(conn)?;
+
+    let failed = fail_jobs(
+        conn,
+        now,
+        lost.iter().map(|(job_id, _)| *job_id).collect(),
+        JobStatus::Failed,
+        Failure::new(job::JOB_LOST, FailureCause::Internal),
+    )?;
+
+    Ok(lost.into_iter()
+        .filter(|(job_id, _)| failed.contains(job_id))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(minutes: i64) -> NaiveDateTime {
+        NaiveDateTime::from_timestamp(0, 0) + Duration::minutes(minutes)
+    }
+
+    #[test]
+    fn job_times_out_after_grace_period() {
+        let limit = time_limit(Some(Duration::minutes(5)), Duration::minutes(120));
+
+        assert_eq!(stale_reason(at(14), at(0), limit, None), None);
+        assert_eq!(stale_reason(at(16), at(0), limit, None), Some(StaleReason::TimedOut));
+    }
+
+    #[test]
+    fn first_run_is_given_the_maximum_run_time() {
+        let limit = time_limit(None, Duration::minutes(120));
+
+        assert_eq!(stale_reason(at(60), at(0), limit, None), None);
+        assert_eq!(stale_reason(at(121), at(0), limit, None), Some(StaleReason::TimedOut));
+    }
+
+    #[test]
+    fn job_is_lost_once_controller_stays_disconnected() {
+        assert_eq!(stale_reason(at(8), at(0), Duration::minutes(40), Some(at(4))), None);
+        assert_eq!(stale_reason(at(10), at(0), Duration::minutes(40), Some(at(4))), Some(StaleReason::ControllerDisconnected));
+    }
+}
@@ -82,169 +82,6 @@ execute procedure update_timestamp();
 insert into controllers (name, access_key)
 values ('NanoNetworking Testbed', 'controller_1');
 
-INSERT INTO public.experiments (user_id, name, code, created_at, updated_at)
-VALUES (1, 'transmitter crash', 'import sys
-from random import random
-from receiver import Receiver
-from transmitter import State, WordEncoder, Spray
-
-def run_transmitter():
-    raise Exception
-
-def run_receiver(device_paths):
-    pass
-
-if __name__ == ''__main__'':
-    if sys.argv[1] == ''--receiver'':
-        run_receiver(sys.argv[2:])
-    else:
-        run_transmitter()', '2021-04-25 11:58:41.265656', '2021-04-25 11:58:41.265656');
-INSERT INTO public.experiments (user_id, name, code, created_at, updated_at)
-VALUES (1, 'transmitter ddos', 'import sys
-from random import random
-from receiver import Receiver
-from transmitter import State, WordEncoder, Spray
-import time
-
-def run_transmitter():
-    time.sleep(15)
-
-
-def run_receiver(device_paths):
-    pass
-
-if __name__ == ''__main__'':
-    if sys.argv[1] == ''--receiver'':
-        run_receiver(sys.argv[2:])
-    else:
-        run_transmitter()', '2021-04-18 16:09:36.646962', '2021-04-18 16:09:36.646962');
-INSERT INTO public.experiments (user_id, name, code, created_at, updated_at)
-VALUES (1, 'output xss hacking', 'import sys
-from random import random
-from receiver import Receiver
-from transmitter import State, WordEncoder, Spray
-
-
-def run_transmitter():
-    state = State(WordEncoder())
-    state.execute()
-    print("""
-      <script>alert(''Hacked'')</script>
-  """)
-    raise Exception
-
-
-def run_receiver(device_paths):
-    pass
-
-
-if __name__ == ''__main__'':
-    if sys.argv[1] == ''--receiver'':
-        run_receiver(sys.argv[2:])
-    else:
-        run_transmitter()', '2021-04-18 16:04:44.018911', '2021-04-18 16:04:44.018911');
-INSERT INTO public.experiments (user_id, name, code, created_at, updated_at)
-VALUES (1, 'receiver crash', 'import sys
-from random import random
-from receiver import Receiver
-from transmitter import State, WordEncoder, Spray
-
-def run_transmitter():
-    state = State(WordEncoder())
-    state.wait(5000)
-    state.execute()
-
-
-def run_receiver(device_paths):
-    raise Exception
-
-
-if __name__ == ''__main__'':
-    if sys.argv[1] == ''--receiver'':
-        run_receiver(sys.argv[2:])
-    else:
-        run_transmitter()', '2021-04-25 11:58:01.943044', '2021-04-25 11:58:01.943044');
-INSERT INTO public.experiments (user_id, name, code, created_at, updated_at)
-VALUES (1, 'receiver exit without waiting experiment end', 'import sys
-from random import random
-from receiver import Receiver
-from transmitter import State, WordEncoder, Spray
-
-def run_transmitter():
-    spray_duration = 20  # ms
-    pause_duration = 25  # ms
-
-    state = State(WordEncoder())
-    for i in range(0, 25):
-        state.emit([Spray.Spray_1], spray_duration * i)
-        state.wait(pause_duration * i)
-
-    state.execute()
-
-
-def run_receiver(device_paths):
-    return
-
-
-if __name__ == ''__main__'':
-    if sys.argv[1] == ''--receiver'':
-        run_receiver(sys.argv[2:])
-    else:
-        run_transmitter()', '2021-04-25 11:54:01.737986', '2021-04-25 11:54:01.737986');
-INSERT INTO public.experiments (user_id, name, code, created_at, updated_at)
-VALUES (1, 'normal', 'import sys
-from random import random
-from receiver import Receiver
-from transmitter import State, WordEncoder, Spray
-
-
-def run_transmitter():
-    spray_duration = 20  # ms
-    pause_duration = 25  # ms
-
-    state = State(WordEncoder())
-    for i in range(0, 25):
-        state.emit([Spray.Spray_1], spray_duration * i)
-        state.wait(pause_duration)
-    state.execute()
-
-
-def run_receiver(device_paths):
-    receiver = Receiver(device_paths)
-    ended, rx = receiver.next()
-    while not ended:
-       print(rx)
-       ended, rx = receiver.next()
-
-if __name__ == &#x27;__main__&#x27;:
-    if sys.argv[1] == &#x27;--receiver&#x27;:
-        run_receiver(sys.argv[2:])
-    else:
-        run_transmitter()', '2021-03-24 13:16:35.128303', '2021-03-24 13:16:35.128303');
-INSERT INTO public.experiments (user_id, name, code, created_at, updated_at)
-VALUES (1, 'receiver ddos', 'import sys
-from random import random
-from receiver import Receiver
-from transmitter import State, WordEncoder, Spray
-import time
-
-def run_transmitter():
-    state = State(WordEncoder())
-    state.wait(5000)
-    state.execute()
-
-
-def run_receiver(device_paths):
-    receiver = Receiver(device_paths)
-    time.sleep(20)
-
-
-if __name__ == ''__main__'':
-    if sys.argv[1] == ''--receiver'':
-        run_receiver(sys.argv[2:])
-    else:
-        run_transmitter()', '2021-04-25 11:52:50.821539', '2021-04-25 11:52:50.821539');
-
 create table slots
 (
     id         serial PRIMARY KEY NOT NULL,
@@ -0,0 +1,11 @@
+drop index jobs_sweep_id;
+
+alter table jobs
+    drop constraint job_sweep_id,
+    drop column sweep_id,
+    drop column parameters;
+
+drop table sweeps;
+
+alter table experiments
+    drop column parameters;
@@ -0,0 +1,20 @@
+alter table experiments
+    add column parameters jsonb NOT NULL DEFAULT '[]';
+
+create table sweeps
+(
+    id            serial PRIMARY KEY NOT NULL,
+    experiment_id integer            NOT NULL,
+    controller_id integer            NOT NULL,
+    definition    jsonb              NOT NULL,
+    created_at    timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT sweep_experiment_id FOREIGN KEY (experiment_id) REFERENCES experiments (id) ON DELETE CASCADE ON UPDATE NO ACTION,
+    CONSTRAINT sweep_controller_id FOREIGN KEY (controller_id) REFERENCES controllers (id) ON DELETE NO ACTION ON UPDATE NO ACTION
+);
+
+alter table jobs
+    add column parameters jsonb   NOT NULL DEFAULT '{}',
+    add column sweep_id   integer NULL,
+    add CONSTRAINT job_sweep_id FOREIGN KEY (sweep_id) REFERENCES sweeps (id) ON DELETE SET NULL ON UPDATE NO ACTION;
+
+create index jobs_sweep_id on jobs (sweep_id);
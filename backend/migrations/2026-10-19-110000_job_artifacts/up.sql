@@ -0,0 +1,11 @@
+create table job_artifacts
+(
+    id           serial PRIMARY KEY NOT NULL,
+    job_id       integer            NOT NULL,
+    name         varchar(255)       NOT NULL,
+    content_type varchar(255)       NOT NULL,
+    size         bigint             NOT NULL,
+    created_at   timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT job_artifact_job_id FOREIGN KEY (job_id) REFERENCES jobs (id) ON DELETE CASCADE ON UPDATE NO ACTION,
+    CONSTRAINT job_artifact_name UNIQUE (job_id, name)
+);
@@ -0,0 +1,7 @@
+drop index jobs_controller_id_status;
+
+alter table jobs
+    drop column priority,
+    drop column priority_override,
+    drop column started_at,
+    drop column finished_at;
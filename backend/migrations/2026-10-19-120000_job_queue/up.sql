@@ -0,0 +1,7 @@
+alter table jobs
+    add column priority          integer   NOT NULL DEFAULT 0,
+    add column priority_override integer   NULL,
+    add column started_at        timestamp NULL,
+    add column finished_at       timestamp NULL;
+
+create index jobs_controller_id_status on jobs (controller_id, status);
@@ -0,0 +1,5 @@
+alter table jobs
+    drop column failure_kind,
+    drop column failure_cause,
+    drop constraint jobs_status_check,
+    add constraint jobs_status_check CHECK ( status in ('Pending', 'Running', 'Successful', 'Failed', 'Cancelled') );
@@ -0,0 +1,5 @@
+alter table jobs
+    add column failure_kind  varchar(255) NULL,
+    add column failure_cause varchar(255) NULL,
+    drop constraint jobs_status_check,
+    add constraint jobs_status_check CHECK ( status in ('Pending', 'Running', 'Successful', 'Failed', 'Cancelled', 'Aborted', 'TimedOut') );
@@ -0,0 +1,4 @@
+alter table experiments
+    drop column forked_from_id;
+
+drop table experiment_shares;
@@ -0,0 +1,17 @@
+create table experiment_shares
+(
+    id            serial PRIMARY KEY NOT NULL,
+    experiment_id integer            NOT NULL,
+    user_id       integer            NOT NULL,
+    role          varchar(255)       NOT NULL,
+    created_at    timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT experiment_share_experiment_id FOREIGN KEY (experiment_id) REFERENCES experiments (id) ON DELETE CASCADE ON UPDATE NO ACTION,
+    CONSTRAINT experiment_share_user_id FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE ON UPDATE NO ACTION,
+    CONSTRAINT experiment_share_user UNIQUE (experiment_id, user_id)
+);
+
+create index experiment_shares_user_id on experiment_shares (user_id);
+
+alter table experiments
+    add column forked_from_id integer NULL,
+    add CONSTRAINT experiment_forked_from_id FOREIGN KEY (forked_from_id) REFERENCES experiments (id) ON DELETE SET NULL ON UPDATE NO ACTION;
@@ -0,0 +1,6 @@
+alter table experiments
+    drop column template_version_id;
+
+drop table template_versions;
+
+drop table templates;
@@ -0,0 +1,33 @@
+create table templates
+(
+    id          serial PRIMARY KEY NOT NULL,
+    name        varchar(255)       NOT NULL,
+    description text               NOT NULL DEFAULT '',
+    tags        text[]             NOT NULL DEFAULT '{}',
+    published   boolean            NOT NULL DEFAULT true,
+    created_at  timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at  timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT template_name UNIQUE (name)
+);
+
+create trigger templates_updated_at
+    before update
+    on templates
+    for each row
+execute procedure update_timestamp();
+
+create table template_versions
+(
+    id          serial PRIMARY KEY NOT NULL,
+    template_id integer            NOT NULL,
+    version     integer            NOT NULL,
+    code        text               NOT NULL,
+    parameters  jsonb              NOT NULL DEFAULT '[]',
+    created_at  timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT template_version_template_id FOREIGN KEY (template_id) REFERENCES templates (id) ON DELETE CASCADE ON UPDATE NO ACTION,
+    CONSTRAINT template_version_version UNIQUE (template_id, version)
+);
+
+alter table experiments
+    add column template_version_id integer NULL,
+    add CONSTRAINT experiment_template_version_id FOREIGN KEY (template_version_id) REFERENCES template_versions (id) ON DELETE SET NULL ON UPDATE NO ACTION;
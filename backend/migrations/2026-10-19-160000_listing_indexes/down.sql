@@ -0,0 +1,6 @@
+drop index jobs_created_at;
+drop index jobs_experiment_id_created_at;
+
+drop index experiments_code_search;
+drop index experiments_name_search;
+drop index experiments_user_id_created_at;
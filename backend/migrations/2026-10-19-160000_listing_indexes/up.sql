@@ -0,0 +1,6 @@
+create index experiments_user_id_created_at on experiments (user_id, created_at);
+create index experiments_name_search on experiments using gin (to_tsvector('simple', name));
+create index experiments_code_search on experiments using gin (to_tsvector('simple', code));
+
+create index jobs_experiment_id_created_at on jobs (experiment_id, created_at);
+create index jobs_created_at on jobs (created_at);
@@ -0,0 +1,10 @@
+drop index jobs_created_at_id;
+drop index jobs_experiment_id_created_at_id;
+
+drop index experiments_user_id_name_id;
+drop index experiments_user_id_updated_at_id;
+drop index experiments_user_id_created_at_id;
+
+create index experiments_user_id_created_at on experiments (user_id, created_at);
+create index jobs_experiment_id_created_at on jobs (experiment_id, created_at);
+create index jobs_created_at on jobs (created_at);
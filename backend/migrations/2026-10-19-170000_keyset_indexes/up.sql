@@ -0,0 +1,10 @@
+drop index experiments_user_id_created_at;
+drop index jobs_experiment_id_created_at;
+drop index jobs_created_at;
+
+create index experiments_user_id_created_at_id on experiments (user_id, created_at, id);
+create index experiments_user_id_updated_at_id on experiments (user_id, updated_at, id);
+create index experiments_user_id_name_id on experiments (user_id, name, id);
+
+create index jobs_experiment_id_created_at_id on jobs (experiment_id, created_at, id);
+create index jobs_created_at_id on jobs (created_at, id);
@@ -0,0 +1,15 @@
+create table job_analyses
+(
+    id               serial PRIMARY KEY NOT NULL,
+    job_id           integer            NOT NULL,
+    sample_frequency double precision   NOT NULL,
+    threshold        double precision,
+    max_latency      integer            NOT NULL,
+    transmitted      boolean[]          NOT NULL,
+    receivers        jsonb              NOT NULL,
+    bit_error_rate   double precision   NOT NULL,
+    latency          double precision,
+    analyzed_at      timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT job_analysis_job_id FOREIGN KEY (job_id) REFERENCES jobs (id) ON DELETE CASCADE ON UPDATE NO ACTION,
+    CONSTRAINT job_analysis_job_id_unique UNIQUE (job_id)
+);
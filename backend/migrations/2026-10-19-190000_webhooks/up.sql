@@ -0,0 +1,39 @@
+create table webhooks
+(
+    id         serial PRIMARY KEY NOT NULL,
+    user_id    integer            NOT NULL,
+    url        varchar(2048)      NOT NULL,
+    secret     varchar(255)       NOT NULL,
+    events     text[]             NOT NULL,
+    active     boolean            NOT NULL DEFAULT TRUE,
+    created_at timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT webhook_user_id FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE ON UPDATE NO ACTION
+);
+
+create trigger webhooks_updated_at
+    before update
+    on webhooks
+    for each row
+execute procedure update_timestamp();
+
+create index webhooks_user_id on webhooks (user_id);
+
+create table webhook_deliveries
+(
+    id              serial PRIMARY KEY NOT NULL,
+    webhook_id      integer            NOT NULL,
+    event           varchar(32)        NOT NULL,
+    payload         jsonb              NOT NULL,
+    status          varchar(16)        NOT NULL DEFAULT 'Pending',
+    attempts        integer            NOT NULL DEFAULT 0,
+    next_attempt_at timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    response_status integer,
+    error           text,
+    created_at      timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    delivered_at    timestamp,
+    CONSTRAINT webhook_delivery_webhook_id FOREIGN KEY (webhook_id) REFERENCES webhooks (id) ON DELETE CASCADE ON UPDATE NO ACTION
+);
+
+create index webhook_deliveries_webhook_id_id on webhook_deliveries (webhook_id, id);
+create index webhook_deliveries_pending on webhook_deliveries (next_attempt_at) where status = 'Pending';
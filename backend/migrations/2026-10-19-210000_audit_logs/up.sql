@@ -0,0 +1,27 @@
+-- actor and target are not foreign keys so that the entries outlive the users, slots and jobs they refer to
+create table audit_logs
+(
+    id          serial PRIMARY KEY NOT NULL,
+    actor_id    integer,
+    action      varchar(32)        NOT NULL,
+    target_kind varchar(16)        NOT NULL,
+    target_id   integer            NOT NULL,
+    ip          varchar(64),
+    created_at  timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+create index audit_logs_actor_id on audit_logs (actor_id, id);
+create index audit_logs_target on audit_logs (target_kind, target_id, id);
+
+create function reject_audit_log_change() returns trigger as
+$$
+begin
+    raise exception 'audit logs are append only';
+end;
+$$ language plpgsql;
+
+create trigger audit_logs_append_only
+    before update or delete
+    on audit_logs
+    for each row
+execute procedure reject_audit_log_change();
@@ -0,0 +1,3 @@
+drop trigger controllers_slot_policy on controllers;
+drop function create_slot_policy();
+drop table slot_policies;
@@ -0,0 +1,44 @@
+-- defaults of the columns are the hourly slots used before policies
+create table slot_policies
+(
+    controller_id  integer PRIMARY KEY NOT NULL,
+    slot_minutes   integer             NOT NULL DEFAULT 50,
+    gap_minutes    integer             NOT NULL DEFAULT 10,
+    horizon_days   integer             NOT NULL DEFAULT 30,
+    earliest_hour  integer             NOT NULL DEFAULT 0,
+    latest_hour    integer             NOT NULL DEFAULT 24,
+    notice_minutes integer             NOT NULL DEFAULT 0,
+    created_at     timestamp           NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at     timestamp           NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT slot_policy_controller_id FOREIGN KEY (controller_id) REFERENCES controllers (id) ON DELETE CASCADE ON UPDATE NO ACTION,
+    CONSTRAINT slot_policy_slot_minutes CHECK (slot_minutes BETWEEN 1 AND 1440),
+    CONSTRAINT slot_policy_gap_minutes CHECK (gap_minutes BETWEEN 0 AND 1440),
+    CONSTRAINT slot_policy_horizon_days CHECK (horizon_days BETWEEN 1 AND 365),
+    CONSTRAINT slot_policy_hours CHECK (0 <= earliest_hour AND earliest_hour < latest_hour AND latest_hour <= 24),
+    CONSTRAINT slot_policy_notice_minutes CHECK (notice_minutes BETWEEN 0 AND 10080)
+);
+
+create trigger slot_policies_updated_at
+    before update
+    on slot_policies
+    for each row
+execute procedure update_timestamp();
+
+insert into slot_policies (controller_id)
+select id
+from controllers;
+
+-- every controller has a policy, so that the defaults are only given here
+create function create_slot_policy() returns trigger as
+$$
+begin
+    insert into slot_policies (controller_id) values (new.id);
+    return new;
+end;
+$$ language plpgsql;
+
+create trigger controllers_slot_policy
+    after insert
+    on controllers
+    for each row
+execute procedure create_slot_policy();
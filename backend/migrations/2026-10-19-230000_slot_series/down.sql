@@ -0,0 +1,4 @@
+alter table slots
+    drop column series_id;
+
+drop table slot_series;
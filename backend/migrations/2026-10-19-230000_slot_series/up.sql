@@ -0,0 +1,30 @@
+create table slot_series
+(
+    id            serial PRIMARY KEY NOT NULL,
+    user_id       integer            NOT NULL,
+    controller_id integer            NOT NULL,
+    frequency     varchar(16)        NOT NULL,
+    start_at      timestamp          NOT NULL,
+    until         timestamp,
+    count         integer,
+    created_at    timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at    timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT slot_series_user_id FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE ON UPDATE NO ACTION,
+    CONSTRAINT slot_series_controller_id FOREIGN KEY (controller_id) REFERENCES controllers (id) ON DELETE CASCADE ON UPDATE NO ACTION,
+    CONSTRAINT slot_series_end CHECK (until IS NOT NULL OR count IS NOT NULL)
+);
+
+create trigger slot_series_updated_at
+    before update
+    on slot_series
+    for each row
+execute procedure update_timestamp();
+
+create index slot_series_user_id on slot_series (user_id);
+
+-- occurrences that have already taken place are kept when their series is cancelled
+alter table slots
+    add column series_id integer,
+    add CONSTRAINT slot_series_id FOREIGN KEY (series_id) REFERENCES slot_series (id) ON DELETE SET NULL ON UPDATE NO ACTION;
+
+create index slots_series_id on slots (series_id);
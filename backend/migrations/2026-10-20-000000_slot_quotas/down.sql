@@ -0,0 +1,2 @@
+drop table user_slot_quotas;
+drop table role_slot_quotas;
@@ -0,0 +1,42 @@
+-- limits left empty are not enforced
+create table role_slot_quotas
+(
+    role_id            integer PRIMARY KEY NOT NULL,
+    max_future_slots   integer,
+    max_hours_per_week integer,
+    max_horizon_days   integer,
+    created_at         timestamp           NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at         timestamp           NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT role_slot_quota_role_id FOREIGN KEY (role_id) REFERENCES roles (id) ON DELETE CASCADE ON UPDATE NO ACTION,
+    CONSTRAINT role_slot_quota_limits CHECK (max_future_slots >= 0 AND max_hours_per_week >= 0 AND max_horizon_days >= 0)
+);
+
+create trigger role_slot_quotas_updated_at
+    before update
+    on role_slot_quotas
+    for each row
+execute procedure update_timestamp();
+
+-- limits left empty are taken from the quota of the role of the user
+create table user_slot_quotas
+(
+    user_id            integer PRIMARY KEY NOT NULL,
+    max_future_slots   integer,
+    max_hours_per_week integer,
+    max_horizon_days   integer,
+    created_at         timestamp           NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at         timestamp           NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT user_slot_quota_user_id FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE ON UPDATE NO ACTION,
+    CONSTRAINT user_slot_quota_limits CHECK (max_future_slots >= 0 AND max_hours_per_week >= 0 AND max_horizon_days >= 0)
+);
+
+create trigger user_slot_quotas_updated_at
+    before update
+    on user_slot_quotas
+    for each row
+execute procedure update_timestamp();
+
+insert into role_slot_quotas (role_id, max_future_slots, max_hours_per_week, max_horizon_days)
+select id, 10, 10, 30
+from roles
+where name = 'user';
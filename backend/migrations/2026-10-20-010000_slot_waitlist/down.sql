@@ -0,0 +1 @@
+drop table slot_waitlist_entries;
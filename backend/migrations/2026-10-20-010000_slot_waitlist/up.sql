@@ -0,0 +1,28 @@
+-- a released slot that falls into the window is offered to the waiters of the controller one by one, in the order
+-- they have joined, until one of them claims it before the offer expires
+create table slot_waitlist_entries
+(
+    id               serial PRIMARY KEY NOT NULL,
+    user_id          integer            NOT NULL,
+    controller_id    integer            NOT NULL,
+    window_start     timestamp          NOT NULL,
+    window_end       timestamp          NOT NULL,
+    status           varchar(16)        NOT NULL DEFAULT 'Waiting',
+    offered_start_at timestamp,
+    offered_end_at   timestamp,
+    offer_expires_at timestamp,
+    created_at       timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at       timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT slot_waitlist_entry_user_id FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE ON UPDATE NO ACTION,
+    CONSTRAINT slot_waitlist_entry_controller_id FOREIGN KEY (controller_id) REFERENCES controllers (id) ON DELETE CASCADE ON UPDATE NO ACTION,
+    CONSTRAINT slot_waitlist_entry_window CHECK (window_start < window_end)
+);
+
+create trigger slot_waitlist_entries_updated_at
+    before update
+    on slot_waitlist_entries
+    for each row
+execute procedure update_timestamp();
+
+create index slot_waitlist_entries_controller_id on slot_waitlist_entries (controller_id, status, id);
+create index slot_waitlist_entries_user_id on slot_waitlist_entries (user_id);
@@ -0,0 +1,15 @@
+-- secret of the calendar feed of the user, the feed is read by calendar apps without authentication
+create table calendar_tokens
+(
+    user_id    integer PRIMARY KEY NOT NULL,
+    token      varchar(64)         NOT NULL UNIQUE,
+    created_at timestamp           NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at timestamp           NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT calendar_token_user_id FOREIGN KEY (user_id) REFERENCES users (id) ON DELETE CASCADE ON UPDATE NO ACTION
+);
+
+create trigger calendar_tokens_updated_at
+    before update
+    on calendar_tokens
+    for each row
+execute procedure update_timestamp();
@@ -0,0 +1,23 @@
+-- controllers cannot be reserved during their maintenance windows
+create table maintenance_windows
+(
+    id            serial PRIMARY KEY NOT NULL,
+    controller_id integer            NOT NULL,
+    start_at      timestamp          NOT NULL,
+    end_at        timestamp          NOT NULL,
+    reason        varchar(255),
+    created_by    integer,
+    created_at    timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at    timestamp          NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    CONSTRAINT maintenance_window_controller_id FOREIGN KEY (controller_id) REFERENCES controllers (id) ON DELETE CASCADE ON UPDATE NO ACTION,
+    CONSTRAINT maintenance_window_created_by FOREIGN KEY (created_by) REFERENCES users (id) ON DELETE SET NULL ON UPDATE NO ACTION,
+    CONSTRAINT maintenance_window_interval CHECK (start_at < end_at)
+);
+
+create trigger maintenance_windows_updated_at
+    before update
+    on maintenance_windows
+    for each row
+execute procedure update_timestamp();
+
+create index maintenance_windows_controller_id on maintenance_windows (controller_id, end_at);
@@ -0,0 +1,3 @@
+alter table slots
+    drop CONSTRAINT slots_no_overlap,
+    drop CONSTRAINT slots_interval;
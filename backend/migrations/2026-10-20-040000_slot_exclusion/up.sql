@@ -0,0 +1,32 @@
+-- integer equality in gist indexes
+create extension if not exists btree_gist;
+
+-- slots reserved before the constraint could be invalid or overlap, of overlapping ones the first reserved is kept and
+-- the later ones are deleted and recorded in the audit log
+do $$
+declare
+    slot record;
+begin
+    for slot in select * from slots order by created_at, id loop
+        if slot.start_at >= slot.end_at or exists(
+            select 1 from slots kept
+            where kept.controller_id = slot.controller_id
+              and (kept.created_at, kept.id) < (slot.created_at, slot.id)
+              and kept.start_at < slot.end_at
+              and slot.start_at < kept.end_at
+        ) then
+            delete from slots where id = slot.id;
+            insert into audit_logs (action, target_kind, target_id) values ('DeleteSlot', 'Slot', slot.id);
+            raise notice 'Deleted slot % of user % on controller % from % to %, it is empty or overlaps an earlier slot',
+                slot.id, slot.user_id, slot.controller_id, slot.start_at, slot.end_at;
+        end if;
+    end loop;
+end
+$$;
+
+-- slots of a controller cannot overlap even if they are reserved concurrently, the gap between them is left to the
+-- policy checks since it can change over time. The range is an expression rather than a column so that start_at and
+-- end_at stay the only place the times of a slot are written.
+alter table slots
+    add CONSTRAINT slots_interval CHECK (start_at < end_at),
+    add CONSTRAINT slots_no_overlap EXCLUDE USING gist (controller_id WITH =, tsrange(start_at, end_at, '[)') WITH &&);
@@ -7,6 +7,8 @@ pub use crate::ws::messages::{CreateSession, Notification, NotificationKind, Not
 
 pub mod mail;
 mod notification;
+pub mod sse;
+pub mod storage;
 mod ws;
 
 #[derive(Clone)]
@@ -1,24 +1,45 @@
 use std::collections::HashMap;
+use std::time::Instant;
 
 use actix::prelude::*;
-use log::info;
+use actix_web::web::Bytes;
+use async_std::channel::Sender;
+use chrono::Utc;
+use log::{error, info};
 use serde::Serialize;
 
 use core::types::ModelId;
 
 use crate::Notification;
+use crate::sse::{self, ConnectStream, EventBuffer, EventId};
 use crate::ws::messages::{internal, outgoing};
 use crate::ws::session::{Session, SessionId};
 
 pub struct NotificationServer {
     // user_id -> (session_id -> session)
     users: HashMap<ModelId, HashMap<SessionId, Addr<Session>>>,
+    // user_id -> event streams, closed ones are removed once writing to them fails
+    streams: HashMap<ModelId, Vec<Sender<Bytes>>>,
+    buffer: EventBuffer,
 }
 
 impl NotificationServer {
     pub fn new() -> Self {
         NotificationServer {
-            users: HashMap::new()
+            users: HashMap::new(),
+            streams: HashMap::new(),
+            buffer: EventBuffer::new(Utc::now().timestamp_millis() as EventId),
+        }
+    }
+
+    /// Writes the event to the streams of the user, dropping the ones that are closed or too slow to keep up
+    fn write_streams(&mut self, user_id: ModelId, event: Bytes) {
+        if let Some(streams) = self.streams.get_mut(&user_id) {
+            streams.retain(|stream| stream.try_send(event.clone()).is_ok());
+
+            if streams.is_empty() {
+                self.streams.remove(&user_id);
+            }
         }
     }
 }
@@ -26,8 +47,18 @@ impl NotificationServer {
 impl Actor for NotificationServer {
     type Context = Context<Self>;
 
-    fn started(&mut self, _ctx: &mut Context<Self>) {
+    fn started(&mut self, ctx: &mut Context<Self>) {
         info!("NotificationServer is started!");
+
+        ctx.run_interval(sse::HEARTBEAT_INTERVAL, |act, _| {
+            let user_ids = act.streams.keys().cloned().collect::<Vec<ModelId>>();
+
+            for user_id in user_ids {
+                act.write_streams(user_id, sse::heartbeat());
+            }
+
+            act.buffer.prune(Instant::now());
+        });
     }
 
     fn stopped(&mut self, _ctx: &mut Context<Self>) {
@@ -60,10 +91,38 @@ impl Handler<internal::server::DisconnectServer> for NotificationServer {
     }
 }
 
+impl Handler<ConnectStream> for NotificationServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: ConnectStream, _: &mut Self::Context) -> Self::Result {
+        let mut events = vec![sse::retry()];
+
+        if let Some(last_event_id) = msg.last_event_id {
+            events.extend(self.buffer.since(msg.user_id, last_event_id).map(|event| sse::event(event.id, event.data.as_str())));
+        }
+
+        for event in events {
+            if msg.sender.try_send(event).is_err() {
+                return;
+            }
+        }
+
+        self.streams.entry(msg.user_id).or_default().push(msg.sender);
+    }
+}
+
 impl<T> Handler<Notification<T>> for NotificationServer where T: Serialize + Send + Clone + 'static {
     type Result = ();
 
     fn handle(&mut self, msg: Notification<T>, _: &mut Self::Context) -> Self::Result {
+        match serde_json::to_string(&msg.message) {
+            Ok(data) => {
+                let event = sse::event(self.buffer.push(msg.user_id, data.clone(), Instant::now()), data.as_str());
+                self.write_streams(msg.user_id, event);
+            }
+            Err(e) => error!("Error while serializing notification, {:?}", e)
+        }
+
         if let Some(sessions) = self.users.get(&msg.user_id) {
             for (_, addr) in sessions {
                 addr.do_send(outgoing::NotifyUser {
@@ -0,0 +1,142 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use actix::Message;
+use actix_web::web::Bytes;
+use async_std::channel::{self, Receiver, Sender};
+use futures_util::stream::{Stream, StreamExt};
+
+use core::types::ModelId;
+
+/// Notifications kept per user for resuming streams, the older ones are dropped
+pub const BUFFER_SIZE: usize = 100;
+pub const BUFFER_AGE: Duration = Duration::from_secs(5 * 60);
+/// Comment sent to every stream periodically so that idle connections are not closed by proxies
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+// in milliseconds, how long clients wait before reconnecting
+const RETRY: u64 = 3000;
+// events waiting to be written to a stream, slow clients are disconnected and resume once they reconnect
+const STREAM_CAPACITY: usize = 64;
+
+pub type EventId = u64;
+
+/// Opens a Server-Sent Events stream of the notifications of the user, the ones after the last event id are replayed
+/// first if they are still in the buffer
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct ConnectStream {
+    pub user_id: ModelId,
+    pub last_event_id: Option<EventId>,
+    pub sender: Sender<Bytes>,
+}
+
+/// Channel whose receiving side is the body of the event stream response
+pub fn channel() -> (Sender<Bytes>, impl Stream<Item=Result<Bytes, actix_web::Error>> + Unpin) {
+    let (sender, receiver): (Sender<Bytes>, Receiver<Bytes>) = channel::bounded(STREAM_CAPACITY);
+
+    (sender, receiver.map(Ok))
+}
+
+/// Opening of every stream, sets how long clients wait before reconnecting
+pub fn retry() -> Bytes {
+    Bytes::from(format!("retry: {}\n\n", RETRY))
+}
+
+pub fn heartbeat() -> Bytes {
+    Bytes::from_static(b": heartbeat\n\n")
+}
+
+pub fn event(id: EventId, data: &str) -> Bytes {
+    Bytes::from(format!("id: {}\ndata: {}\n\n", id, data))
+}
+
+pub struct BufferedEvent {
+    pub id: EventId,
+    pub data: String,
+    created_at: Instant,
+}
+
+/// Latest notifications of each user, ids are increasing across users
+pub struct EventBuffer {
+    next_id: EventId,
+    users: HashMap<ModelId, VecDeque<BufferedEvent>>,
+}
+
+impl EventBuffer {
+    /// Ids continue from the given one, starting from the current time keeps them increasing across restarts so that
+    /// clients do not skip new events with a stale last event id
+    pub fn new(first_id: EventId) -> Self {
+        EventBuffer {
+            next_id: first_id,
+            users: HashMap::new(),
+        }
+    }
+
+    pub fn push(&mut self, user_id: ModelId, data: String, now: Instant) -> EventId {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let events = self.users.entry(user_id).or_default();
+
+        if events.len() == BUFFER_SIZE {
+            events.pop_front();
+        }
+
+        events.push_back(BufferedEvent { id, data, created_at: now });
+
+        id
+    }
+
+    /// Events of the user after the given one
+    pub fn since(&self, user_id: ModelId, last_id: EventId) -> impl Iterator<Item=&BufferedEvent> {
+        self.users.get(&user_id)
+            .into_iter()
+            .flatten()
+            .filter(move |event| event.id > last_id)
+    }
+
+    pub fn prune(&mut self, now: Instant) {
+        for events in self.users.values_mut() {
+            while events.front().iter().any(|event| now.duration_since(event.created_at) > BUFFER_AGE) {
+                events.pop_front();
+            }
+        }
+
+        self.users.retain(|_, events| !events.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_after_last_id_are_replayed() {
+        let now = Instant::now();
+        let mut buffer = EventBuffer::new(10);
+
+        buffer.push(1, String::from("a"), now);
+        buffer.push(2, String::from("b"), now);
+        buffer.push(1, String::from("c"), now);
+
+        assert_eq!(buffer.since(1, 10).map(|event| event.data.as_str()).collect::<Vec<&str>>(), vec!["c"]);
+        assert_eq!(buffer.since(1, 0).map(|event| event.id).collect::<Vec<EventId>>(), vec![10, 12]);
+        assert_eq!(buffer.since(3, 0).count(), 0);
+    }
+
+    #[test]
+    fn old_events_are_dropped() {
+        let now = Instant::now();
+        let mut buffer = EventBuffer::new(0);
+
+        for _ in 0..BUFFER_SIZE + 1 {
+            buffer.push(1, String::new(), now);
+        }
+
+        assert_eq!(buffer.since(1, 0).count(), BUFFER_SIZE);
+
+        buffer.prune(now + BUFFER_AGE + Duration::from_secs(1));
+
+        assert_eq!(buffer.since(1, 0).count(), 0);
+    }
+}
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use async_std::fs::{File, OpenOptions};
+use futures_util::future::{FutureExt, LocalBoxFuture};
+use futures_util::{AsyncReadExt, AsyncWriteExt};
+use futures_util::stream::{self, StreamExt};
+use log::error;
+
+use crate::storage::{ByteStream, Download, Error, Storage};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Keeps the objects on the local filesystem under `root`
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: String) -> Self {
+        LocalStorage {
+            root: PathBuf::from(root)
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+}
+
+impl Storage for LocalStorage {
+    fn put<'a>(&'a self, key: &'a str, mut body: ByteStream) -> LocalBoxFuture<'a, Result<(), Error>> {
+        async move {
+            let path = self.path(key);
+
+            if let Some(parent) = path.parent() {
+                async_std::fs::create_dir_all(parent).await?;
+            }
+
+            let mut file = OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+                .await?;
+
+            while let Some(chunk) = body.next().await {
+                let written = match chunk {
+                    Ok(bytes) => file.write_all(&bytes).await.map_err(Error::from),
+                    Err(e) => Err(e)
+                };
+
+                if let Err(e) = written {
+                    // do not leave partially written objects behind
+                    if let Err(e) = async_std::fs::remove_file(&path).await {
+                        error!("failed to remove partially written object {:?}, {:?}", path, e);
+                    }
+
+                    return Err(e);
+                }
+            }
+
+            file.flush().await?;
+
+            Ok(())
+        }.boxed_local()
+    }
+
+    fn read<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<ByteStream, Error>> {
+        async move {
+            let file = File::open(self.path(key)).await?;
+
+            let stream = stream::unfold(Some(file), |file| async move {
+                let mut file = file?;
+                let mut buffer = vec![0; CHUNK_SIZE];
+
+                match file.read(&mut buffer).await {
+                    Ok(0) => None,
+                    Ok(size) => {
+                        buffer.truncate(size);
+                        Some((Ok(buffer.into()), Some(file)))
+                    }
+                    Err(e) => Some((Err(Error::IO(e)), None))
+                }
+            });
+
+            Ok(stream.boxed_local())
+        }.boxed_local()
+    }
+
+    fn get<'a>(&'a self, key: &'a str, _file_name: &'a str, _content_type: &'a str) -> LocalBoxFuture<'a, Result<Download, Error>> {
+        async move {
+            Ok(Download::Stream(self.read(key).await?))
+        }.boxed_local()
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<(), Error>> {
+        async move {
+            async_std::fs::remove_file(self.path(key))
+                .await
+                .map_err(Error::from)
+        }.boxed_local()
+    }
+}
@@ -0,0 +1,63 @@
+use actix_web::web::Bytes;
+use futures_util::future::LocalBoxFuture;
+use futures_util::stream::LocalBoxStream;
+
+use core::error::{ErrorMessaging, HttpError};
+use core::ErrorMessage;
+
+pub use crate::storage::local::LocalStorage;
+pub use crate::storage::s3::{S3Config, S3Storage};
+
+mod local;
+mod s3;
+
+pub type ByteStream = LocalBoxStream<'static, Result<Bytes, Error>>;
+
+pub enum Download {
+    /// Content of the object, served by the application itself
+    Stream(ByteStream),
+    /// Short lived url that serves the object directly from the storage
+    Redirect(String),
+}
+
+/// Storage of job artifacts. Objects are addressed by keys in the form of `{job_id}/{file_name}`.
+pub trait Storage: Send + Sync {
+    /// Stores the object, fails with `Error::AlreadyExists` if there is already an object with the same key.
+    fn put<'a>(&'a self, key: &'a str, body: ByteStream) -> LocalBoxFuture<'a, Result<(), Error>>;
+
+    /// Content of the object, always streamed through the application
+    fn read<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<ByteStream, Error>>;
+
+    /// Download of the object to be returned to a client, `file_name` and `content_type` are the ones it is served with.
+    fn get<'a>(&'a self, key: &'a str, file_name: &'a str, content_type: &'a str) -> LocalBoxFuture<'a, Result<Download, Error>>;
+
+    fn delete<'a>(&'a self, key: &'a str) -> LocalBoxFuture<'a, Result<(), Error>>;
+}
+
+#[derive(Debug)]
+pub enum Error {
+    NotFound,
+    AlreadyExists,
+    IO(std::io::Error),
+    Request(String),
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::NotFound => Error::NotFound,
+            std::io::ErrorKind::AlreadyExists => Error::AlreadyExists,
+            _ => Error::IO(e)
+        }
+    }
+}
+
+impl ErrorMessaging for Error {
+    fn value(&self) -> HttpError {
+        match self {
+            Error::NotFound => ErrorMessage::ItemNotFound,
+            Error::AlreadyExists => ErrorMessage::UniqueViolation,
+            Error::IO(_) | Error::Request(_) => ErrorMessage::IOError
+        }.value()
+    }
+}
@@ -25,7 +25,9 @@ pub enum OutgoingMessageKind {
 
 #[derive(Serialize, Clone)]
 pub enum NotificationKind {
-    JobUpdate
+    JobUpdate,
+    SlotOffer,
+    SlotCancelled,
 }
 
 pub trait WebSocketMessaging: Message<Result=()> + Send {
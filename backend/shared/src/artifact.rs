@@ -0,0 +1,10 @@
+/// Maximum length of an artifact name, limited by the column size
+pub const MAX_NAME_LENGTH: usize = 255;
+
+/// Names are used both in urls and as the file name of downloads, hence only a safe subset is accepted.
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() &&
+        name.len() <= MAX_NAME_LENGTH &&
+        !name.starts_with('.') &&
+        name.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '_' || c == '-')
+}
@@ -1,5 +1,8 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+pub mod artifact;
+pub mod state;
 pub mod websocket_messages;
 
 
@@ -9,7 +12,7 @@ pub enum ControllerState {
     Running(i32),
 }
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct JoinServerRequest {
     pub token: String,
@@ -5,7 +5,7 @@ pub const END_DELIMITER_NEW_LINE: &str = "end_delimiter\n";
 pub const START_DELIMITER: &str = "start_delimiter";
 pub const END_DELIMITER: &str = "end_delimiter";
 
-const NUM_SPRAY: usize = 2;
+pub const NUM_SPRAY: usize = 2;
 
 trait Encode {
     fn encode(&self) -> String;
@@ -60,6 +60,15 @@ pub struct State {
     order: Vec<Command>,
 }
 
+/// Period of the execution in which the sprays are kept in the same state, each emit or wait makes up one slot
+#[derive(Debug, Clone, PartialEq)]
+pub struct Slot {
+    /// Milliseconds since the start of the execution
+    pub start: u32,
+    pub duration: u32,
+    pub sprays: [bool; NUM_SPRAY],
+}
+
 impl State {
     pub fn emit_time(&self) -> u32 {
         self.emits
@@ -72,6 +81,33 @@ impl State {
             .iter()
             .fold(self.emit_time(), |time, wait| time + wait.duration)
     }
+
+    /// Emits and waits in the execution order, fan commands are left out since they take no time
+    pub fn slots(&self) -> Vec<Slot> {
+        let mut emits = self.emits.iter();
+        let mut waits = self.waits.iter();
+        let mut slots = Vec::with_capacity(self.emits.len() + self.waits.len());
+        let mut start = 0;
+
+        for command in &self.order {
+            let (duration, sprays) = match command {
+                Command::Emit => match emits.next() {
+                    Some(emit) => (emit.duration, emit.sprays),
+                    None => break
+                },
+                Command::Wait => match waits.next() {
+                    Some(wait) => (wait.duration, [false; NUM_SPRAY]),
+                    None => break
+                },
+                Command::SetFanRPM => continue
+            };
+
+            slots.push(Slot { start, duration, sprays });
+            start += duration;
+        }
+
+        slots
+    }
 }
 
 impl<'a> IntoIterator for &'a State {
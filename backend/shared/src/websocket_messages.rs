@@ -26,6 +26,24 @@ pub mod server {
     pub struct RunResult {
         pub job_id: ModelId,
         pub successful: bool,
+        #[serde(default)]
+        pub failure: Option<Failure>,
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub enum FailureCause {
+        /// Code of the experiment failed
+        User,
+        /// Job is aborted while running
+        Abort,
+        /// Controller failed to run the job
+        Internal,
+    }
+
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    pub struct Failure {
+        pub kind: String,
+        pub cause: FailureCause,
     }
 
     #[derive(Deserialize, Serialize)]
@@ -35,6 +53,8 @@ pub mod server {
 }
 
 pub mod client {
+    use serde_json::{Map, Value};
+
     use super::{Deserialize, ModelId, Serialize};
 
     #[derive(Deserialize, Serialize)]
@@ -58,6 +78,8 @@ pub mod client {
     pub struct RunExperiment {
         pub job_id: ModelId,
         pub code: String,
+        #[serde(default)]
+        pub parameters: Map<String, Value>,
     }
 
     #[derive(Deserialize, Serialize)]
@@ -0,0 +1,113 @@
+use chrono::NaiveDateTime;
+
+/// Slots that ended within this many days are kept in the feeds, together with all upcoming ones
+pub const PAST_DAYS: i64 = 30;
+/// Calendar apps are asked to refresh the feeds this often, so that moved and deleted slots are reflected
+const REFRESH_INTERVAL: &str = "PT15M";
+// lines longer than this many octets are folded
+const MAX_LINE_LENGTH: usize = 75;
+
+pub struct Event {
+    pub uid: String,
+    pub summary: String,
+    pub start_at: NaiveDateTime,
+    pub end_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Renders the events as an iCalendar feed. Times of the slots are in UTC, so they are given with the UTC designator
+/// and calendar apps show them in the local time of the user.
+pub fn render(name: &str, events: &[Event]) -> String {
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//Testbed//Slots//EN"),
+        String::from("CALSCALE:GREGORIAN"),
+        String::from("METHOD:PUBLISH"),
+        format!("X-WR-CALNAME:{}", escape(name)),
+        format!("REFRESH-INTERVAL;VALUE=DURATION:{}", REFRESH_INTERVAL),
+        format!("X-PUBLISHED-TTL:{}", REFRESH_INTERVAL),
+    ];
+
+    for event in events {
+        lines.extend(vec![
+            String::from("BEGIN:VEVENT"),
+            format!("UID:{}", escape(event.uid.as_str())),
+            format!("DTSTAMP:{}", utc(event.updated_at)),
+            format!("LAST-MODIFIED:{}", utc(event.updated_at)),
+            format!("DTSTART:{}", utc(event.start_at)),
+            format!("DTEND:{}", utc(event.end_at)),
+            format!("SUMMARY:{}", escape(event.summary.as_str())),
+            String::from("END:VEVENT"),
+        ]);
+    }
+
+    lines.push(String::from("END:VCALENDAR"));
+
+    lines.iter()
+        .map(|line| fold(line.as_str()) + "\r\n")
+        .collect()
+}
+
+fn utc(at: NaiveDateTime) -> String {
+    at.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Splits the line into lines of at most 75 octets, without splitting characters, continuation lines start with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut length = 0;
+
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+
+        folded.push(c);
+        length += c.len_utf8();
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn events_are_rendered_in_utc() {
+        let at = NaiveDate::from_ymd(2026, 10, 21).and_hms(10, 0, 0);
+
+        let feed = render("Slots, mine", &[Event {
+            uid: String::from("slot-1"),
+            summary: String::from("Controller; 1"),
+            start_at: at,
+            end_at: at + chrono::Duration::minutes(50),
+            updated_at: at,
+        }]);
+
+        assert!(feed.contains("X-WR-CALNAME:Slots\\, mine\r\n"));
+        assert!(feed.contains("DTSTART:20261021T100000Z\r\nDTEND:20261021T105000Z\r\n"));
+        assert!(feed.contains("SUMMARY:Controller\\; 1\r\n"));
+        assert!(feed.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+    }
+
+    #[test]
+    fn long_lines_are_folded_at_character_boundaries() {
+        let line = format!("SUMMARY:{}", "ö".repeat(40));
+        let folded = fold(line.as_str());
+
+        assert!(folded.split("\r\n").all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use actix_web::{get, HttpResponse, post, web, web::Json};
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::prelude::*;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use core::client_ip::ClientIp;
+use core::Config;
+use core::models::audit::{self, AuditAction, AuditEntry, TargetKind};
+use core::schema::{calendar_tokens, controllers, slots};
+use core::types::{DBPool, ModelId, Result};
+use derive::operation;
+use user::models::user::User;
+
+use crate::calendar::{self, Event};
+
+const TOKEN_LENGTH: usize = 32;
+
+#[derive(Serialize, JsonSchema)]
+pub struct CalendarFeedResponse {
+    /// Secret url of the feed, anyone knowing it can see the slots of the user
+    pub url: String,
+}
+
+/// Url of the calendar feed of the user's slots, its token is created on the first request
+#[operation]
+#[get("calendar")]
+pub async fn fetch_calendar_feed(pool: web::Data<DBPool>, config: web::Data<Arc<Config>>, user: User) -> Result<Json<CalendarFeedResponse>> {
+    let conn = pool.get().unwrap();
+
+    let token = web::block(move || {
+        diesel::insert_into(calendar_tokens::table)
+            .values((calendar_tokens::user_id.eq(user.id), calendar_tokens::token.eq(generate_token())))
+            .on_conflict_do_nothing()
+            .execute(&conn)?;
+
+        calendar_tokens::table
+            .find(user.id)
+            .select(calendar_tokens::token)
+            .first::<String>(&conn)
+    })
+        .await?;
+
+    Ok(Json(CalendarFeedResponse { url: feed_url(&config, token.as_str()) }))
+}
+
+/// Replaces the token of the calendar feed, the previous url stops working
+#[operation]
+#[post("calendar/reset")]
+pub async fn reset_calendar_token(
+    pool: web::Data<DBPool>,
+    config: web::Data<Arc<Config>>,
+    user: User,
+    ip: ClientIp,
+) -> Result<Json<CalendarFeedResponse>> {
+    let conn = pool.get().unwrap();
+    let token = generate_token();
+    let url = feed_url(&config, token.as_str());
+
+    web::block(move || {
+        conn.transaction(|| {
+            diesel::insert_into(calendar_tokens::table)
+                .values((calendar_tokens::user_id.eq(user.id), calendar_tokens::token.eq(&token)))
+                .on_conflict(calendar_tokens::user_id)
+                .do_update()
+                .set(calendar_tokens::token.eq(&token))
+                .execute(&conn)?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::ResetCalendarToken,
+                target_kind: TargetKind::User,
+                target_id: user.id,
+                ip: ip.into_inner(),
+            })
+        })
+    })
+        .await?;
+
+    Ok(Json(CalendarFeedResponse { url }))
+}
+
+/// Slots of the user owning the token, for calendar apps
+#[operation]
+#[get("calendar/user/{token}")]
+pub async fn fetch_user_calendar(pool: web::Data<DBPool>, token: web::Path<String>) -> Result<HttpResponse> {
+    let conn = pool.get().unwrap();
+
+    let events = web::block(move || -> QueryResult<Vec<Event>> {
+        let user_id = calendar_tokens::table
+            .filter(calendar_tokens::token.eq(token.into_inner()))
+            .select(calendar_tokens::user_id)
+            .first::<ModelId>(&conn)?;
+
+        let slots = slots::table
+            .filter(slots::user_id.eq(user_id))
+            .filter(slots::end_at.gt(feed_start()))
+            .inner_join(controllers::table)
+            .order_by(slots::start_at.asc())
+            .select((slots::id, controllers::name, slots::start_at, slots::end_at, slots::updated_at))
+            .load::<(ModelId, String, NaiveDateTime, NaiveDateTime, NaiveDateTime)>(&conn)?;
+
+        Ok(slots.into_iter()
+            .map(|(id, controller_name, start_at, end_at, updated_at)| Event {
+                uid: slot_uid(id),
+                summary: format!("Testbed slot on {}", controller_name),
+                start_at,
+                end_at,
+                updated_at,
+            })
+            .collect())
+    })
+        .await?;
+
+    Ok(calendar_response(calendar::render("Testbed slots", &events)))
+}
+
+/// Reserved slots of the controller without the users reserving them
+#[operation]
+#[get("calendar/controller/{controller_id}")]
+pub async fn fetch_controller_calendar(pool: web::Data<DBPool>, controller_id: web::Path<ModelId>) -> Result<HttpResponse> {
+    let conn = pool.get().unwrap();
+    let controller_id = controller_id.into_inner();
+
+    let (controller_name, events) = web::block(move || -> QueryResult<(String, Vec<Event>)> {
+        let controller_name = controllers::table
+            .find(controller_id)
+            .select(controllers::name)
+            .first::<String>(&conn)?;
+
+        let slots = slots::table
+            .filter(slots::controller_id.eq(controller_id))
+            .filter(slots::end_at.gt(feed_start()))
+            .order_by(slots::start_at.asc())
+            .select((slots::id, slots::start_at, slots::end_at, slots::updated_at))
+            .load::<(ModelId, NaiveDateTime, NaiveDateTime, NaiveDateTime)>(&conn)?;
+
+        let events = slots.into_iter()
+            .map(|(id, start_at, end_at, updated_at)| Event {
+                uid: slot_uid(id),
+                summary: String::from("Reserved"),
+                start_at,
+                end_at,
+                updated_at,
+            })
+            .collect();
+
+        Ok((controller_name, events))
+    })
+        .await?;
+
+    Ok(calendar_response(calendar::render(format!("Testbed reservations of {}", controller_name).as_str(), &events)))
+}
+
+fn generate_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LENGTH)
+        .map(char::from)
+        .collect()
+}
+
+fn feed_url(config: &Config, token: &str) -> String {
+    format!("{}/api/v1/slot/calendar/user/{}", config.app_url, token)
+}
+
+fn feed_start() -> NaiveDateTime {
+    Utc::now().naive_utc() - Duration::days(calendar::PAST_DAYS)
+}
+
+// the same slot has the same uid in both feeds, so that it is not shown twice when both are subscribed
+fn slot_uid(slot_id: ModelId) -> String {
+    format!("testbed-slot-{}", slot_id)
+}
+
+fn calendar_response(feed: String) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("text/calendar; charset=utf-8")
+        .body(feed)
+}
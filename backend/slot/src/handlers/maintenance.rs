@@ -0,0 +1,203 @@
+use actix_web::{delete, get, post, web, web::Json};
+use askama::Template;
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use log::error;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use core::client_ip::ClientIp;
+use core::models::audit::{self, AuditAction, AuditEntry, TargetKind};
+use core::responses::SuccessResponse;
+use core::schema::{maintenance_windows, slot_policies, slots, users};
+use core::types::{DBPool, ModelId, Result};
+use derive::operation;
+use service::{ClientServices, Notification, NotificationKind, NotificationMessage, Servers};
+use user::models::user::User;
+
+use crate::models::{MaintenanceWindow, Slot};
+use crate::requests::MaintenanceWindowRequest;
+use crate::templates::SlotCancelledMailTemplate;
+
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceWindowResponse {
+    pub window: MaintenanceWindow,
+    /// Reservations overlapping the window that have not ended yet
+    pub affected_slots: Vec<Slot>,
+    /// Whether the affected reservations are cancelled
+    pub cancelled: bool,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SlotCancelled {
+    slot_id: ModelId,
+    controller_id: ModelId,
+    start_at: NaiveDateTime,
+    end_at: NaiveDateTime,
+    reason: Option<String>,
+}
+
+/// Maintenance windows of the controller that have not ended yet
+#[operation]
+#[get("maintenance/controller/{controller_id}")]
+pub async fn fetch_maintenance_windows(pool: web::Data<DBPool>, controller_id: web::Path<ModelId>) -> Result<Json<Vec<MaintenanceWindow>>> {
+    let conn = pool.get().unwrap();
+
+    let windows = web::block(move ||
+        maintenance_windows::table
+            .filter(maintenance_windows::controller_id.eq(controller_id.into_inner()))
+            .filter(maintenance_windows::end_at.gt(Utc::now().naive_utc()))
+            .order_by(maintenance_windows::start_at.asc())
+            .load::<MaintenanceWindow>(&conn)
+    )
+        .await?;
+
+    Ok(Json(windows))
+}
+
+/// Blocks the controller during the window. Reservations overlapping it are reported, and cancelled with their owners
+/// notified if asked.
+#[operation]
+#[post("maintenance")]
+pub async fn create_maintenance_window(
+    pool: web::Data<DBPool>,
+    servers: web::Data<Servers>,
+    client_services: web::Data<ClientServices>,
+    user: User,
+    ip: ClientIp,
+    request: web::Json<MaintenanceWindowRequest>,
+) -> Result<Json<MaintenanceWindowResponse>> {
+    let request = request.into_inner();
+
+    request.validate(Utc::now().naive_utc())?;
+
+    let conn = pool.get().unwrap();
+    let cancelled = request.cancel_reservations;
+
+    let (window, affected) = web::block(move || {
+        conn.transaction(|| -> QueryResult<(MaintenanceWindow, Vec<(Slot, User)>)> {
+            // waits for the reservations that are checked against the windows without this one
+            slot_policies::table
+                .find(request.controller_id)
+                .select(slot_policies::controller_id)
+                .for_update()
+                .first::<ModelId>(&conn)
+                .optional()?;
+
+            let window = diesel::insert_into(maintenance_windows::table)
+                .values((
+                    maintenance_windows::controller_id.eq(request.controller_id),
+                    maintenance_windows::start_at.eq(request.start_at),
+                    maintenance_windows::end_at.eq(request.end_at),
+                    maintenance_windows::reason.eq(request.reason),
+                    maintenance_windows::created_by.eq(user.id),
+                ))
+                .get_result::<MaintenanceWindow>(&conn)?;
+
+            let affected = slots::table
+                .filter(slots::controller_id.eq(window.controller_id))
+                .filter(slots::start_at.lt(window.end_at).and(slots::end_at.gt(window.start_at)))
+                .filter(slots::end_at.gt(Utc::now().naive_utc()))
+                .inner_join(users::table)
+                .order_by(slots::start_at.asc())
+                .select((slots::all_columns, users::all_columns))
+                .load::<(Slot, User)>(&conn)?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::CreateMaintenanceWindow,
+                target_kind: TargetKind::Maintenance,
+                target_id: window.id,
+                ip: ip.0.clone(),
+            })?;
+
+            if request.cancel_reservations {
+                diesel::delete(slots::table.filter(slots::id.eq_any(affected.iter().map(|(slot, _)| slot.id).collect::<Vec<ModelId>>())))
+                    .execute(&conn)?;
+
+                for (slot, _) in &affected {
+                    audit::record(&conn, AuditEntry {
+                        actor_id: Some(user.id),
+                        action: AuditAction::DeleteSlot,
+                        target_kind: TargetKind::Slot,
+                        target_id: slot.id,
+                        ip: ip.0.clone(),
+                    })?;
+                }
+            }
+
+            Ok((window, affected))
+        })
+    })
+        .await?;
+
+    if cancelled {
+        for (slot, owner) in &affected {
+            notify_cancelled(&servers, &client_services, &window, slot, owner);
+        }
+    }
+
+    Ok(Json(MaintenanceWindowResponse {
+        window,
+        affected_slots: affected.into_iter().map(|(slot, _)| slot).collect(),
+        cancelled,
+    }))
+}
+
+#[operation]
+#[delete("maintenance/{id}")]
+pub async fn delete_maintenance_window(pool: web::Data<DBPool>, user: User, ip: ClientIp, window_id: web::Path<ModelId>) -> Result<Json<SuccessResponse>> {
+    let conn = pool.get().unwrap();
+
+    web::block(move || {
+        conn.transaction(|| {
+            let window = diesel::delete(maintenance_windows::table.find(window_id.into_inner()))
+                .get_result::<MaintenanceWindow>(&conn)?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::DeleteMaintenanceWindow,
+                target_kind: TargetKind::Maintenance,
+                target_id: window.id,
+                ip: ip.into_inner(),
+            })
+        })
+    })
+        .await?;
+
+    Ok(Json(SuccessResponse::default()))
+}
+
+fn notify_cancelled(servers: &Servers, client_services: &ClientServices, window: &MaintenanceWindow, slot: &Slot, owner: &User) {
+    servers.notification.do_send(Notification {
+        user_id: owner.id,
+        message: NotificationMessage {
+            kind: NotificationKind::SlotCancelled,
+            data: SlotCancelled {
+                slot_id: slot.id,
+                controller_id: slot.controller_id,
+                start_at: slot.start_at,
+                end_at: slot.end_at,
+                reason: window.reason.clone(),
+            },
+        },
+    });
+
+    let full_name = owner.full_name();
+
+    let text = SlotCancelledMailTemplate {
+        full_name: full_name.as_str(),
+        controller_id: slot.controller_id,
+        start_at: slot.start_at,
+        end_at: slot.end_at,
+        reason: window.reason.as_deref().unwrap_or_default(),
+    }
+        .render();
+
+    match text {
+        Ok(text) => client_services.mail.send_mail(owner.email.clone(), String::from("Slot Cancelled"), text),
+        Err(e) => error!("Error while rendering slot cancelled mail, {:?}", e)
+    }
+}
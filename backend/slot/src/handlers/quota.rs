@@ -0,0 +1,157 @@
+use actix_web::{delete, get, put, web, web::Json};
+use chrono::Utc;
+use diesel::prelude::*;
+
+use core::client_ip::ClientIp;
+use core::models::audit::{self, AuditAction, AuditEntry, TargetKind};
+use core::responses::SuccessResponse;
+use core::schema::{role_slot_quotas, user_slot_quotas};
+use core::types::{DBPool, ModelId, Result};
+use derive::operation;
+use user::models::user::User;
+
+use crate::quota::{Quota, QuotaResponse, Usage};
+use crate::requests::QuotaRequest;
+
+/// Quota of the user together with its usage in the current week
+#[operation]
+#[get("quota")]
+pub async fn fetch_quota(pool: web::Data<DBPool>, user: User) -> Result<Json<QuotaResponse>> {
+    let conn = pool.get().unwrap();
+
+    let response = web::block(move || {
+        conn.transaction(|| -> QueryResult<QuotaResponse> {
+            let now = Utc::now().naive_utc();
+
+            let quota = Quota::of_user(&conn, &user)?;
+            let usage = Usage::of(&conn, user.id, now, None, now)?;
+
+            Ok(QuotaResponse::new(quota, usage))
+        })
+    })
+        .await?;
+
+    Ok(Json(response))
+}
+
+/// Replaces the quota of the role, applies to the users of the role apart from the limits given for them
+#[operation]
+#[put("quota/role/{role_id}")]
+pub async fn update_role_quota(
+    pool: web::Data<DBPool>,
+    user: User,
+    ip: ClientIp,
+    role_id: web::Path<ModelId>,
+    request: web::Json<QuotaRequest>,
+) -> Result<Json<Quota>> {
+    let request = request.into_inner();
+
+    request.validate()?;
+
+    let conn = pool.get().unwrap();
+    let role_id = role_id.into_inner();
+
+    let quota = web::block(move || {
+        conn.transaction(|| -> QueryResult<Quota> {
+            let limits = (
+                role_slot_quotas::max_future_slots.eq(request.max_future_slots),
+                role_slot_quotas::max_hours_per_week.eq(request.max_hours_per_week),
+                role_slot_quotas::max_horizon_days.eq(request.max_horizon_days),
+            );
+
+            diesel::insert_into(role_slot_quotas::table)
+                .values((role_slot_quotas::role_id.eq(role_id), limits))
+                .on_conflict(role_slot_quotas::role_id)
+                .do_update()
+                .set(limits)
+                .execute(&conn)?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::UpdateSlotQuota,
+                target_kind: TargetKind::Role,
+                target_id: role_id,
+                ip: ip.into_inner(),
+            })?;
+
+            Ok(request.into_quota())
+        })
+    })
+        .await?;
+
+    Ok(Json(quota))
+}
+
+/// Replaces the limits given for the user, the missing ones are taken from the quota of the role of the user
+#[operation]
+#[put("quota/user/{user_id}")]
+pub async fn update_user_quota(
+    pool: web::Data<DBPool>,
+    user: User,
+    ip: ClientIp,
+    user_id: web::Path<ModelId>,
+    request: web::Json<QuotaRequest>,
+) -> Result<Json<Quota>> {
+    let request = request.into_inner();
+
+    request.validate()?;
+
+    let conn = pool.get().unwrap();
+    let user_id = user_id.into_inner();
+
+    let quota = web::block(move || {
+        conn.transaction(|| -> QueryResult<Quota> {
+            let limits = (
+                user_slot_quotas::max_future_slots.eq(request.max_future_slots),
+                user_slot_quotas::max_hours_per_week.eq(request.max_hours_per_week),
+                user_slot_quotas::max_horizon_days.eq(request.max_horizon_days),
+            );
+
+            diesel::insert_into(user_slot_quotas::table)
+                .values((user_slot_quotas::user_id.eq(user_id), limits))
+                .on_conflict(user_slot_quotas::user_id)
+                .do_update()
+                .set(limits)
+                .execute(&conn)?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::UpdateSlotQuota,
+                target_kind: TargetKind::User,
+                target_id: user_id,
+                ip: ip.into_inner(),
+            })?;
+
+            Ok(request.into_quota())
+        })
+    })
+        .await?;
+
+    Ok(Json(quota))
+}
+
+/// Removes the limits given for the user, so that the quota of the role of the user applies
+#[operation]
+#[delete("quota/user/{user_id}")]
+pub async fn delete_user_quota(pool: web::Data<DBPool>, user: User, ip: ClientIp, user_id: web::Path<ModelId>) -> Result<Json<SuccessResponse>> {
+    let conn = pool.get().unwrap();
+    let user_id = user_id.into_inner();
+
+    web::block(move || {
+        conn.transaction(|| {
+            diesel::delete(user_slot_quotas::table.find(user_id))
+                .execute(&conn)?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::UpdateSlotQuota,
+                target_kind: TargetKind::User,
+                target_id: user_id,
+                ip: ip.into_inner(),
+            })
+        })
+    })
+        .await?;
+
+    Ok(Json(SuccessResponse::default()))
+}
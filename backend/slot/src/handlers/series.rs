@@ -0,0 +1,334 @@
+use actix::Addr;
+use actix_web::{delete, get, post, put, web, web::Json};
+use chrono::{NaiveDateTime, Utc};
+use diesel::prelude::*;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use core::client_ip::ClientIp;
+use core::db::DieselEnum;
+use core::models::audit::{self, AuditAction, AuditEntry, TargetKind};
+use core::responses::SuccessResponse;
+use core::schema::{slot_series, slots};
+use core::types::{DBPool, ModelId, Result};
+use derive::operation;
+use user::models::user::User;
+
+use crate::ErrorMessage;
+use crate::handlers::{insert_slot, is_reserved};
+use crate::maintenance::{is_under_maintenance, lock_policy};
+use crate::models::{occurrences, Slot, SlotPolicy, SlotSeries};
+use crate::quota::Quota;
+use crate::server::{SlotReleased, WaitlistServer};
+use crate::waitlist::is_held;
+use crate::requests::{RecurrenceRequest, SlotSeriesRequest};
+
+#[derive(Serialize, JsonSchema)]
+pub enum ConflictReason {
+    /// Occurrence is outside of the slots or the booking horizon of the controller
+    NotReservable,
+    AlreadyReserved,
+    /// Occurrence would exceed the reservation quota of the user
+    QuotaExceeded,
+    /// Occurrence falls into a maintenance window of the controller
+    UnderMaintenance,
+}
+
+/// Occurrence of a series that could not be reserved
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct OccurrenceConflict {
+    pub requested_at: NaiveDateTime,
+    pub reason: ConflictReason,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct SlotSeriesWithSlots {
+    pub series: SlotSeries,
+    pub slots: Vec<Slot>,
+}
+
+#[derive(Serialize, JsonSchema)]
+pub struct SlotSeriesResponse {
+    pub series: SlotSeries,
+    pub slots: Vec<Slot>,
+    pub conflicts: Vec<OccurrenceConflict>,
+}
+
+#[operation]
+#[get("series")]
+pub async fn fetch_slot_series(pool: web::Data<DBPool>, user: User) -> Result<Json<Vec<SlotSeries>>> {
+    let conn = pool.get().unwrap();
+
+    let series = web::block(move ||
+        slot_series::table
+            .filter(slot_series::user_id.eq(user.id))
+            .order_by(slot_series::id.desc())
+            .load::<SlotSeries>(&conn)
+    )
+        .await?;
+
+    Ok(Json(series))
+}
+
+/// Series together with its occurrences, including the moved ones
+#[operation]
+#[get("series/{id}")]
+pub async fn fetch_series(pool: web::Data<DBPool>, user: User, series_id: web::Path<ModelId>) -> Result<Json<SlotSeriesWithSlots>> {
+    let conn = pool.get().unwrap();
+
+    let series = web::block(move || -> QueryResult<SlotSeriesWithSlots> {
+        let series = find_series(&conn, series_id.into_inner(), user.id)?;
+
+        let slots = slots::table
+            .filter(slots::series_id.eq(series.id))
+            .order_by(slots::start_at.asc())
+            .load::<Slot>(&conn)?;
+
+        Ok(SlotSeriesWithSlots { series, slots })
+    })
+        .await?;
+
+    Ok(Json(series))
+}
+
+/// Reserves the slots of each occurrence of the recurrence, the occurrences that cannot be reserved are reported as
+/// conflicts
+#[operation]
+#[post("series")]
+pub async fn reserve_slot_series(
+    pool: web::Data<DBPool>,
+    user: User,
+    ip: ClientIp,
+    request: web::Json<SlotSeriesRequest>,
+) -> Result<Json<SlotSeriesResponse>> {
+    let request = request.into_inner();
+
+    request.recurrence.validate()?;
+
+    let conn = pool.get().unwrap();
+
+    let response = web::block(move || {
+        conn.transaction(|| -> Result<SlotSeriesResponse> {
+            let policy = lock_policy(&conn, request.controller_id)?;
+
+            let recurrence = request.recurrence;
+
+            let series = diesel::insert_into(slot_series::table)
+                .values((
+                    slot_series::user_id.eq(user.id),
+                    slot_series::controller_id.eq(request.controller_id),
+                    slot_series::frequency.eq(recurrence.frequency.value()),
+                    slot_series::start_at.eq(recurrence.start_at),
+                    slot_series::until.eq(recurrence.until),
+                    slot_series::count.eq(recurrence.count),
+                ))
+                .get_result::<SlotSeries>(&conn)?;
+
+            let (slots, conflicts) = reserve_occurrences(&conn, &user, &policy, &series, None)?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::ReserveSlotSeries,
+                target_kind: TargetKind::SlotSeries,
+                target_id: series.id,
+                ip: ip.into_inner(),
+            })?;
+
+            Ok(SlotSeriesResponse { series, slots, conflicts })
+        })
+    })
+        .await?;
+
+    Ok(Json(response))
+}
+
+/// Changes the recurrence of the series, its upcoming occurrences are replaced by the ones of the new recurrence
+/// while the ones that have started are kept
+#[operation]
+#[put("series/{id}")]
+pub async fn update_slot_series(
+    pool: web::Data<DBPool>,
+    waitlist_server: web::Data<Addr<WaitlistServer>>,
+    user: User,
+    ip: ClientIp,
+    series_id: web::Path<ModelId>,
+    request: web::Json<RecurrenceRequest>,
+) -> Result<Json<SlotSeriesResponse>> {
+    let recurrence = request.into_inner();
+
+    recurrence.validate()?;
+
+    let conn = pool.get().unwrap();
+
+    let (response, released) = web::block(move || {
+        conn.transaction(|| -> Result<(SlotSeriesResponse, Vec<Slot>)> {
+            let now = Utc::now().naive_utc();
+
+            let series = find_series(&conn, series_id.into_inner(), user.id)?;
+
+            let policy = lock_policy(&conn, series.controller_id)?;
+
+            let removed = diesel::delete(
+                slots::table
+                    .filter(slots::series_id.eq(series.id))
+                    .filter(slots::start_at.gt(now))
+            )
+                .get_results::<Slot>(&conn)?;
+
+            let series = diesel::update(&series)
+                .set((
+                    slot_series::frequency.eq(recurrence.frequency.value()),
+                    slot_series::start_at.eq(recurrence.start_at),
+                    slot_series::until.eq(recurrence.until),
+                    slot_series::count.eq(recurrence.count),
+                ))
+                .get_result::<SlotSeries>(&conn)?;
+
+            let (slots, conflicts) = reserve_occurrences(&conn, &user, &policy, &series, Some(now))?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::UpdateSlotSeries,
+                target_kind: TargetKind::SlotSeries,
+                target_id: series.id,
+                ip: ip.into_inner(),
+            })?;
+
+            // the times of the removed occurrences that the new ones do not cover are free for the waitlist
+            let released = removed.into_iter()
+                .filter(|removed| !slots.iter().any(|slot| slot.start_at <= removed.start_at && slot.end_at >= removed.end_at))
+                .collect();
+
+            Ok((SlotSeriesResponse { series, slots, conflicts }, released))
+        })
+    })
+        .await?;
+
+    for slot in released {
+        waitlist_server.do_send(SlotReleased {
+            controller_id: slot.controller_id,
+            start_at: slot.start_at,
+            end_at: slot.end_at,
+        });
+    }
+
+    Ok(Json(response))
+}
+
+/// Cancels the upcoming occurrences of the series, the ones that have started are kept as single slots
+#[operation]
+#[delete("series/{id}")]
+pub async fn cancel_slot_series(
+    pool: web::Data<DBPool>,
+    waitlist_server: web::Data<Addr<WaitlistServer>>,
+    user: User,
+    ip: ClientIp,
+    series_id: web::Path<ModelId>,
+) -> Result<Json<SuccessResponse>> {
+    let conn = pool.get().unwrap();
+
+    let released = web::block(move || {
+        conn.transaction(|| -> QueryResult<Vec<Slot>> {
+            let series = find_series(&conn, series_id.into_inner(), user.id)?;
+
+            let released = diesel::delete(
+                slots::table
+                    .filter(slots::series_id.eq(series.id))
+                    .filter(slots::start_at.gt(Utc::now().naive_utc()))
+            )
+                .get_results::<Slot>(&conn)?;
+
+            diesel::delete(&series).execute(&conn)?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::CancelSlotSeries,
+                target_kind: TargetKind::SlotSeries,
+                target_id: series.id,
+                ip: ip.into_inner(),
+            })?;
+
+            Ok(released)
+        })
+    })
+        .await?;
+
+    for slot in released {
+        waitlist_server.do_send(SlotReleased {
+            controller_id: slot.controller_id,
+            start_at: slot.start_at,
+            end_at: slot.end_at,
+        });
+    }
+
+    Ok(Json(SuccessResponse::default()))
+}
+
+fn find_series(conn: &PgConnection, series_id: ModelId, user_id: ModelId) -> QueryResult<SlotSeries> {
+    slot_series::table
+        .filter(slot_series::user_id.eq(user_id))
+        .find(series_id)
+        .for_update()
+        .first::<SlotSeries>(conn)
+}
+
+/// Reserves the slots of the occurrences of the series, the ones whose slots start until `kept_until` are skipped
+/// since they are kept from before. Fails if none of them can be reserved.
+fn reserve_occurrences(
+    conn: &PgConnection,
+    user: &User,
+    policy: &SlotPolicy,
+    series: &SlotSeries,
+    kept_until: Option<NaiveDateTime>,
+) -> Result<(Vec<Slot>, Vec<OccurrenceConflict>)> {
+    let now = Utc::now().naive_utc();
+    let quota = Quota::of_user(conn, user)?;
+    let mut slots = Vec::new();
+    let mut conflicts = Vec::new();
+
+    for requested_at in occurrences(series.start_at, series.frequency, series.until, series.count) {
+        let kept = policy.slot_start(requested_at)
+            .zip(kept_until)
+            .iter()
+            .any(|(start_at, kept_until)| start_at <= kept_until);
+
+        if kept {
+            continue;
+        }
+
+        let interval = match policy.interval(requested_at, now) {
+            Ok(interval) => interval,
+            Err(_) => {
+                conflicts.push(OccurrenceConflict { requested_at, reason: ConflictReason::NotReservable });
+                continue;
+            }
+        };
+
+        if is_under_maintenance(conn, policy.controller_id, interval)? {
+            conflicts.push(OccurrenceConflict { requested_at, reason: ConflictReason::UnderMaintenance });
+            continue;
+        }
+
+        if is_reserved(conn, policy, interval, None)? || is_held(conn, policy, interval, Some(user.id), now)? {
+            conflicts.push(OccurrenceConflict { requested_at, reason: ConflictReason::AlreadyReserved });
+            continue;
+        }
+
+        if !quota.allows(conn, user.id, interval, None, now)? {
+            conflicts.push(OccurrenceConflict { requested_at, reason: ConflictReason::QuotaExceeded });
+            continue;
+        }
+
+        match insert_slot(conn, user.id, policy, interval, Some(series.id))? {
+            Some(slot) => slots.push(slot),
+            None => conflicts.push(OccurrenceConflict { requested_at, reason: ConflictReason::AlreadyReserved })
+        }
+    }
+
+    if slots.is_empty() {
+        return Err(Box::new(ErrorMessage::NoReservableOccurrence));
+    }
+
+    Ok((slots, conflicts))
+}
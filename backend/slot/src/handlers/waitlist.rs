@@ -0,0 +1,155 @@
+use actix::Addr;
+use actix_web::{delete, get, post, web, web::Json};
+use chrono::Utc;
+use diesel::prelude::*;
+
+use core::client_ip::ClientIp;
+use core::db::DieselEnum;
+use core::models::audit::{self, AuditAction, AuditEntry, TargetKind};
+use core::responses::SuccessResponse;
+use core::schema::slot_waitlist_entries;
+use core::types::{DBPool, ModelId, Result};
+use derive::operation;
+use user::models::user::User;
+
+use crate::ErrorMessage;
+use crate::handlers::{insert_slot, is_reserved};
+use crate::maintenance::{is_under_maintenance, lock_policy};
+use crate::models::{Slot, WaitlistEntry, WaitlistStatus};
+use crate::quota::Quota;
+use crate::requests::WaitlistRequest;
+use crate::server::{SlotReleased, WaitlistServer};
+
+/// Entries of the user whose windows have not ended yet
+#[operation]
+#[get("waitlist")]
+pub async fn fetch_waitlist(pool: web::Data<DBPool>, user: User) -> Result<Json<Vec<WaitlistEntry>>> {
+    let conn = pool.get().unwrap();
+
+    let entries = web::block(move ||
+        slot_waitlist_entries::table
+            .filter(slot_waitlist_entries::user_id.eq(user.id))
+            .filter(slot_waitlist_entries::window_end.gt(Utc::now().naive_utc()))
+            .order_by(slot_waitlist_entries::id.asc())
+            .load::<WaitlistEntry>(&conn)
+    )
+        .await?;
+
+    Ok(Json(entries))
+}
+
+#[operation]
+#[post("waitlist")]
+pub async fn join_waitlist(pool: web::Data<DBPool>, user: User, request: web::Json<WaitlistRequest>) -> Result<Json<WaitlistEntry>> {
+    let request = request.into_inner();
+
+    request.validate(Utc::now().naive_utc())?;
+
+    let conn = pool.get().unwrap();
+
+    let entry = web::block(move ||
+        diesel::insert_into(slot_waitlist_entries::table)
+            .values((
+                slot_waitlist_entries::user_id.eq(user.id),
+                slot_waitlist_entries::controller_id.eq(request.controller_id),
+                slot_waitlist_entries::window_start.eq(request.window_start),
+                slot_waitlist_entries::window_end.eq(request.window_end),
+            ))
+            .get_result::<WaitlistEntry>(&conn)
+    )
+        .await?;
+
+    Ok(Json(entry))
+}
+
+/// Reserves the slot offered to the entry, which must be claimed before the offer expires
+#[operation]
+#[post("waitlist/{id}/claim")]
+pub async fn claim_offer(pool: web::Data<DBPool>, user: User, ip: ClientIp, entry_id: web::Path<ModelId>) -> Result<Json<Slot>> {
+    let conn = pool.get().unwrap();
+
+    let slot = web::block(move || {
+        conn.transaction(|| -> Result<Slot> {
+            let now = Utc::now().naive_utc();
+
+            let entry = slot_waitlist_entries::table
+                .filter(slot_waitlist_entries::user_id.eq(user.id))
+                .find(entry_id.into_inner())
+                .for_update()
+                .first::<WaitlistEntry>(&conn)?;
+
+            let interval = match (entry.status, entry.offered_start_at, entry.offered_end_at, entry.offer_expires_at) {
+                (WaitlistStatus::Offered, Some(start_at), Some(end_at), Some(expires_at)) if now < expires_at => (start_at, end_at),
+                _ => return Err(Box::new(ErrorMessage::OfferNotAvailable))
+            };
+
+            let policy = lock_policy(&conn, entry.controller_id)?;
+
+            if is_under_maintenance(&conn, entry.controller_id, interval)? {
+                return Err(Box::new(ErrorMessage::UnderMaintenance));
+            }
+
+            let quota = Quota::of_user(&conn, &user)?;
+
+            if !quota.allows(&conn, user.id, interval, None, now)? {
+                return Err(Box::new(ErrorMessage::QuotaExceeded));
+            }
+
+            if is_reserved(&conn, &policy, interval, None)? {
+                return Err(Box::new(ErrorMessage::AlreadyReserved));
+            }
+
+            let slot = insert_slot(&conn, user.id, &policy, interval, None)?
+                .ok_or(ErrorMessage::AlreadyReserved)?;
+
+            diesel::update(&entry)
+                .set(slot_waitlist_entries::status.eq(WaitlistStatus::Claimed.value()))
+                .execute(&conn)?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::ClaimSlotOffer,
+                target_kind: TargetKind::Slot,
+                target_id: slot.id,
+                ip: ip.into_inner(),
+            })?;
+
+            Ok(slot)
+        })
+    })
+        .await?;
+
+    Ok(Json(slot))
+}
+
+/// Removes the entry from the waitlist, a slot offered to it is passed to the next waiter
+#[operation]
+#[delete("waitlist/{id}")]
+pub async fn leave_waitlist(
+    pool: web::Data<DBPool>,
+    waitlist_server: web::Data<Addr<WaitlistServer>>,
+    user: User,
+    entry_id: web::Path<ModelId>,
+) -> Result<Json<SuccessResponse>> {
+    let conn = pool.get().unwrap();
+
+    let entry = web::block(move ||
+        diesel::delete(
+            slot_waitlist_entries::table
+                .filter(slot_waitlist_entries::user_id.eq(user.id))
+                .find(entry_id.into_inner())
+        )
+            .get_result::<WaitlistEntry>(&conn)
+    )
+        .await?;
+
+    if let (WaitlistStatus::Offered, Some(start_at), Some(end_at)) = (entry.status, entry.offered_start_at, entry.offered_end_at) {
+        waitlist_server.do_send(SlotReleased {
+            controller_id: entry.controller_id,
+            start_at,
+            end_at,
+        });
+    }
+
+    Ok(Json(SuccessResponse::default()))
+}
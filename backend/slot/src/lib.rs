@@ -1,30 +1,76 @@
 use actix_web::http::StatusCode;
-use actix_web::web;
 
 use core::error::{ErrorMessaging, HttpError};
-use user::middlewares::auth::Auth;
+use core::openapi::Access;
+use user::router::Router;
 
+pub use crate::server::{SlotReleased, WaitlistServer};
+
+mod calendar;
 mod handlers;
+mod maintenance;
 mod models;
+mod quota;
 mod requests;
+mod server;
+mod templates;
+mod waitlist;
+
+pub fn register<R: Router>(router: &mut R) {
+    router.scope("/slot", Access::Public, |router| {
+        router
+            .service(handlers::calendar::fetch_user_calendar)
+            .service(handlers::calendar::fetch_controller_calendar);
 
-pub fn register(config: &mut web::ServiceConfig) {
-    config
-        .service(
-            web::scope("/api/slot")
-                .wrap(Auth)
+        router.scope("", Access::User, |router| {
+            router
                 .service(handlers::fetch_slots)
                 .service(handlers::fetch_reserved_slots)
                 .service(handlers::fetch_slot)
                 .service(handlers::reserve_slot)
+                .service(handlers::move_slot)
                 .service(handlers::delete_slot)
-        );
+                .service(handlers::series::fetch_slot_series)
+                .service(handlers::series::fetch_series)
+                .service(handlers::series::reserve_slot_series)
+                .service(handlers::series::update_slot_series)
+                .service(handlers::series::cancel_slot_series)
+                .service(handlers::fetch_slot_policy)
+                .service(handlers::maintenance::fetch_maintenance_windows)
+                .service(handlers::quota::fetch_quota)
+                .service(handlers::waitlist::fetch_waitlist)
+                .service(handlers::waitlist::join_waitlist)
+                .service(handlers::waitlist::claim_offer)
+                .service(handlers::waitlist::leave_waitlist)
+                .service(handlers::calendar::fetch_calendar_feed)
+                .service(handlers::calendar::reset_calendar_token);
+
+            router.scope("", Access::Admin, |router| {
+                router
+                    .service(handlers::update_slot_policy)
+                    .service(handlers::quota::update_role_quota)
+                    .service(handlers::quota::update_user_quota)
+                    .service(handlers::quota::delete_user_quota)
+                    .service(handlers::maintenance::create_maintenance_window)
+                    .service(handlers::maintenance::delete_maintenance_window);
+            });
+        });
+    });
 }
 
 #[derive(Debug)]
 pub enum ErrorMessage {
     InvalidSlotInterval,
     AlreadyReserved,
+    InvalidSlotPolicy,
+    NoReservableOccurrence,
+    InvalidRecurrence,
+    QuotaExceeded,
+    InvalidQuota,
+    InvalidWaitlistWindow,
+    OfferNotAvailable,
+    UnderMaintenance,
+    InvalidMaintenanceWindow,
 }
 
 impl ErrorMessaging for ErrorMessage {
@@ -40,6 +86,51 @@ impl ErrorMessaging for ErrorMessage {
                 error_code: 101,
                 message: String::from("already_reserved"),
             },
+            ErrorMessage::InvalidSlotPolicy => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 102,
+                message: String::from("invalid_slot_policy"),
+            },
+            ErrorMessage::NoReservableOccurrence => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 103,
+                message: String::from("no_reservable_occurrence"),
+            },
+            ErrorMessage::InvalidRecurrence => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 104,
+                message: String::from("invalid_recurrence"),
+            },
+            ErrorMessage::QuotaExceeded => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 105,
+                message: String::from("quota_exceeded"),
+            },
+            ErrorMessage::InvalidQuota => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 106,
+                message: String::from("invalid_quota"),
+            },
+            ErrorMessage::InvalidWaitlistWindow => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 107,
+                message: String::from("invalid_waitlist_window"),
+            },
+            ErrorMessage::OfferNotAvailable => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 108,
+                message: String::from("offer_not_available"),
+            },
+            ErrorMessage::UnderMaintenance => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 109,
+                message: String::from("under_maintenance"),
+            },
+            ErrorMessage::InvalidMaintenanceWindow => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 110,
+                message: String::from("invalid_maintenance_window"),
+            },
         }
     }
 }
@@ -0,0 +1,37 @@
+use chrono::NaiveDateTime;
+use diesel::prelude::*;
+
+use core::schema::{maintenance_windows, slot_policies};
+use core::types::ModelId;
+
+use crate::models::{MaintenanceWindow, SlotPolicy};
+
+/// Policy of the controller, shared locked until the transaction ends. Reservations take it before checking the
+/// maintenance windows, and new windows lock it exclusively, so that a window cannot be created between the check and
+/// the reservation.
+pub fn lock_policy(conn: &PgConnection, controller_id: ModelId) -> QueryResult<SlotPolicy> {
+    slot_policies::table
+        .find(controller_id)
+        .for_share()
+        .first::<SlotPolicy>(conn)
+}
+
+/// Whether a maintenance window of the controller overlaps the interval
+pub fn is_under_maintenance(conn: &PgConnection, controller_id: ModelId, (start_at, end_at): (NaiveDateTime, NaiveDateTime)) -> QueryResult<bool> {
+    diesel::dsl::select(diesel::dsl::exists(overlapping_query(controller_id, (start_at, end_at))))
+        .get_result(conn)
+}
+
+/// Maintenance windows of the controller overlapping the interval
+pub fn overlapping_windows(conn: &PgConnection, controller_id: ModelId, (start_at, end_at): (NaiveDateTime, NaiveDateTime)) -> QueryResult<Vec<MaintenanceWindow>> {
+    overlapping_query(controller_id, (start_at, end_at))
+        .order_by(maintenance_windows::start_at.asc())
+        .load::<MaintenanceWindow>(conn)
+}
+
+fn overlapping_query(controller_id: ModelId, (start_at, end_at): (NaiveDateTime, NaiveDateTime)) -> maintenance_windows::BoxedQuery<'static, diesel::pg::Pg> {
+    maintenance_windows::table
+        .filter(maintenance_windows::controller_id.eq(controller_id))
+        .filter(maintenance_windows::start_at.lt(end_at).and(maintenance_windows::end_at.gt(start_at)))
+        .into_boxed()
+}
@@ -1,11 +1,17 @@
-use chrono::NaiveDateTime;
-use diesel::{Identifiable, Queryable};
-use serde::Serialize;
+use chrono::{Duration, NaiveDateTime};
+use diesel::{AsChangeset, Identifiable, Queryable};
+use diesel::pg::Pg;
+use diesel::sql_types::VarChar;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
 
-use core::schema::slots;
+use core::db::DieselEnum;
+use core::schema::{maintenance_windows, slot_policies, slot_series, slot_waitlist_entries, slots};
 use core::types::ModelId;
 
-#[derive(Identifiable, Queryable, Serialize)]
+use crate::ErrorMessage;
+
+#[derive(Identifiable, Queryable, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Slot {
     pub id: ModelId,
@@ -15,4 +21,297 @@ pub struct Slot {
     pub end_at: NaiveDateTime,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    pub series_id: Option<ModelId>,
+}
+
+/// Rules of reserving the slots of a controller. Slots of a day start at the earliest hour and follow each other
+/// separated by the gap, the last one starts before the latest hour. Hours are in UTC.
+#[derive(Identifiable, Queryable, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[primary_key(controller_id)]
+#[table_name = "slot_policies"]
+pub struct SlotPolicy {
+    pub controller_id: ModelId,
+    pub slot_minutes: i32,
+    pub gap_minutes: i32,
+    pub horizon_days: i32,
+    pub earliest_hour: i32,
+    pub latest_hour: i32,
+    pub notice_minutes: i32,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+impl SlotPolicy {
+    pub fn length(&self) -> Duration {
+        Duration::minutes(self.slot_minutes as i64)
+    }
+
+    pub fn gap(&self) -> Duration {
+        Duration::minutes(self.gap_minutes as i64)
+    }
+
+    /// Time between the starts of consecutive slots
+    pub fn period(&self) -> Duration {
+        self.length() + self.gap()
+    }
+
+    /// Start of the slot the time falls into, none if no slot of its day starts before it
+    pub fn slot_start(&self, at: NaiveDateTime) -> Option<NaiveDateTime> {
+        let first_start = at.date().and_hms(0, 0, 0) + Duration::hours(self.earliest_hour as i64);
+        let last_start = at.date().and_hms(0, 0, 0) + Duration::hours(self.latest_hour as i64);
+
+        if at < first_start {
+            return None;
+        }
+
+        let period = self.period().num_seconds();
+        let offset = (at - first_start).num_seconds();
+        let start = first_start + Duration::seconds(offset - offset % period);
+
+        if start < last_start {
+            Some(start)
+        } else {
+            None
+        }
+    }
+
+    /// Starts of the slots overlapping the interval
+    pub fn starts_overlapping(&self, start_at: NaiveDateTime, end_at: NaiveDateTime) -> Vec<NaiveDateTime> {
+        let mut starts = Vec::new();
+        let mut day = (start_at - self.length()).date();
+
+        while day <= end_at.date() {
+            let last_start = day.and_hms(0, 0, 0) + Duration::hours(self.latest_hour as i64);
+            let mut slot_start = day.and_hms(0, 0, 0) + Duration::hours(self.earliest_hour as i64);
+
+            while slot_start < last_start && slot_start < end_at {
+                if slot_start + self.length() > start_at {
+                    starts.push(slot_start);
+                }
+
+                slot_start += self.period();
+            }
+
+            day = day.succ();
+        }
+
+        starts
+    }
+
+    /// Start and end of the slot the requested time falls into, if it can be reserved at `now`
+    pub fn interval(&self, requested: NaiveDateTime, now: NaiveDateTime) -> Result<(NaiveDateTime, NaiveDateTime), ErrorMessage> {
+        let start_at = self.slot_start(requested)
+            .ok_or(ErrorMessage::InvalidSlotInterval)?;
+
+        self.check_reservable(start_at, now)?;
+
+        Ok((start_at, start_at + self.length()))
+    }
+
+    /// Checks that the slot starting at the given time can be reserved at `now`. Without a notice, the slot in
+    /// progress can be reserved as well.
+    pub fn check_reservable(&self, start_at: NaiveDateTime, now: NaiveDateTime) -> Result<(), ErrorMessage> {
+        let too_soon = if self.notice_minutes == 0 {
+            start_at + self.length() <= now
+        } else {
+            start_at < now + Duration::minutes(self.notice_minutes as i64)
+        };
+
+        if too_soon || start_at > now + Duration::days(self.horizon_days as i64) {
+            return Err(ErrorMessage::InvalidSlotInterval);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy, JsonSchema, Default)]
+pub enum Frequency {
+    Daily,
+    #[default]
+    Weekly,
+}
+
+impl Queryable<VarChar, Pg> for Frequency {
+    type Row = String;
+
+    fn build(row: Self::Row) -> Self {
+        Self::build_from_string(row)
+    }
+}
+
+impl Frequency {
+    pub fn step(&self) -> Duration {
+        match self {
+            Frequency::Daily => Duration::days(1),
+            Frequency::Weekly => Duration::weeks(1),
+        }
+    }
+}
+
+/// Slots reserved recurrently, from the start until the given time or for the given number of occurrences, whichever
+/// comes first. Occurrences are kept as slots, which can be moved or cancelled one by one.
+#[derive(Identifiable, Queryable, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[table_name = "slot_series"]
+pub struct SlotSeries {
+    pub id: ModelId,
+    pub user_id: ModelId,
+    pub controller_id: ModelId,
+    pub frequency: Frequency,
+    pub start_at: NaiveDateTime,
+    pub until: Option<NaiveDateTime>,
+    pub count: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Upper bound of the occurrences of a series, a year of weekly slots
+pub const MAX_OCCURRENCES: i32 = 52;
+
+/// Requested times of the occurrences of a recurrence, they are placed into slots by the policy of the controller
+pub fn occurrences(start_at: NaiveDateTime, frequency: Frequency, until: Option<NaiveDateTime>, count: Option<i32>) -> Vec<NaiveDateTime> {
+    let count = count.unwrap_or(MAX_OCCURRENCES).min(MAX_OCCURRENCES);
+
+    (0..count)
+        .map(|index| start_at + frequency.step() * index)
+        .take_while(|at| until.iter().all(|until| at <= until))
+        .collect()
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy, JsonSchema, Default)]
+pub enum WaitlistStatus {
+    #[default]
+    Waiting,
+    /// A released slot is held for the user until the offer expires
+    Offered,
+    Claimed,
+    /// Offer expired without being claimed, the slot is passed to the next waiter
+    Expired,
+}
+
+impl Queryable<VarChar, Pg> for WaitlistStatus {
+    type Row = String;
+
+    fn build(row: Self::Row) -> Self {
+        Self::build_from_string(row)
+    }
+}
+
+/// User waiting for a slot of the controller within the window. Offered slot is given while the entry is offered or
+/// once it is claimed or expired.
+#[derive(Identifiable, Queryable, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[table_name = "slot_waitlist_entries"]
+pub struct WaitlistEntry {
+    pub id: ModelId,
+    pub user_id: ModelId,
+    pub controller_id: ModelId,
+    pub window_start: NaiveDateTime,
+    pub window_end: NaiveDateTime,
+    pub status: WaitlistStatus,
+    pub offered_start_at: Option<NaiveDateTime>,
+    pub offered_end_at: Option<NaiveDateTime>,
+    pub offer_expires_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Period in which the controller cannot be reserved, such as while it is being repaired
+#[derive(Identifiable, Queryable, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceWindow {
+    pub id: ModelId,
+    pub controller_id: ModelId,
+    pub start_at: NaiveDateTime,
+    pub end_at: NaiveDateTime,
+    pub reason: Option<String>,
+    pub created_by: Option<ModelId>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(AsChangeset)]
+#[table_name = "slot_policies"]
+pub struct SlotPolicyChangeset {
+    pub slot_minutes: i32,
+    pub gap_minutes: i32,
+    pub horizon_days: i32,
+    pub earliest_hour: i32,
+    pub latest_hour: i32,
+    pub notice_minutes: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    fn policy(slot_minutes: i32, gap_minutes: i32, earliest_hour: i32, latest_hour: i32, notice_minutes: i32) -> SlotPolicy {
+        let now = NaiveDate::from_ymd(2026, 10, 19).and_hms(0, 0, 0);
+
+        SlotPolicy {
+            controller_id: 1,
+            slot_minutes,
+            gap_minutes,
+            horizon_days: 7,
+            earliest_hour,
+            latest_hour,
+            notice_minutes,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd(2026, 10, day).and_hms(hour, minute, 0)
+    }
+
+    #[test]
+    fn times_are_placed_into_slots_of_their_day() {
+        let hourly = policy(50, 10, 0, 24, 0);
+        assert_eq!(hourly.slot_start(at(19, 14, 35)), Some(at(19, 14, 0)));
+
+        let debugging = policy(15, 0, 9, 17, 0);
+        assert_eq!(debugging.slot_start(at(19, 9, 44)), Some(at(19, 9, 30)));
+        assert_eq!(debugging.slot_start(at(19, 8, 59)), None);
+        assert_eq!(debugging.slot_start(at(19, 17, 0)), None);
+
+        let overnight = policy(600, 0, 20, 21, 0);
+        assert_eq!(overnight.slot_start(at(19, 20, 30)), Some(at(19, 20, 0)));
+        assert_eq!(overnight.slot_start(at(20, 2, 0)), None);
+    }
+
+    #[test]
+    fn slots_overlapping_an_interval_are_found_across_days() {
+        let hourly = policy(50, 10, 0, 24, 0);
+        assert_eq!(hourly.starts_overlapping(at(19, 14, 55), at(19, 16, 0)), vec![at(19, 15, 0)]);
+        assert_eq!(hourly.starts_overlapping(at(19, 14, 30), at(19, 15, 1)), vec![at(19, 14, 0), at(19, 15, 0)]);
+
+        let overnight = policy(600, 0, 20, 21, 0);
+        assert_eq!(overnight.starts_overlapping(at(20, 5, 0), at(20, 21, 0)), vec![at(19, 20, 0), at(20, 20, 0)]);
+    }
+
+    #[test]
+    fn occurrences_end_at_until_or_count() {
+        assert_eq!(occurrences(at(19, 10, 0), Frequency::Weekly, Some(at(31, 10, 0)), None), vec![at(19, 10, 0), at(26, 10, 0)]);
+        assert_eq!(occurrences(at(19, 10, 0), Frequency::Daily, Some(at(31, 10, 0)), Some(3)), vec![at(19, 10, 0), at(20, 10, 0), at(21, 10, 0)]);
+        assert_eq!(occurrences(at(19, 10, 0), Frequency::Daily, None, Some(1000)).len(), MAX_OCCURRENCES as usize);
+    }
+
+    #[test]
+    fn slots_are_reservable_within_notice_and_horizon() {
+        let now = at(19, 14, 35);
+
+        let hourly = policy(50, 10, 0, 24, 0);
+        assert!(hourly.check_reservable(at(19, 14, 0), now).is_ok());
+        assert!(hourly.check_reservable(at(19, 13, 0), now).is_err());
+        assert!(hourly.check_reservable(at(27, 14, 0), now).is_err());
+
+        let noticed = policy(50, 10, 0, 24, 60);
+        assert!(noticed.check_reservable(at(19, 15, 0), now).is_err());
+        assert!(noticed.check_reservable(at(19, 16, 0), now).is_ok());
+    }
 }
@@ -0,0 +1,165 @@
+use chrono::{Datelike, Duration, NaiveDateTime};
+use diesel::prelude::*;
+use schemars::JsonSchema;
+use serde::Serialize;
+
+use core::schema::{role_slot_quotas, slots, user_slot_quotas, users};
+use core::types::ModelId;
+use user::models::user::User;
+
+type Limits = (Option<i32>, Option<i32>, Option<i32>);
+
+/// Limits of reserving slots, the ones that are missing are not enforced. Weeks start on Monday in UTC.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Quota {
+    pub max_future_slots: Option<i32>,
+    pub max_hours_per_week: Option<i32>,
+    pub max_horizon_days: Option<i32>,
+}
+
+impl Quota {
+    /// Quota of the user, which should be found within the transaction of the reservation. The user is locked so that
+    /// concurrent reservations of the user are checked against the quota one after another.
+    pub fn of_user(conn: &PgConnection, user: &User) -> QueryResult<Self> {
+        users::table
+            .find(user.id)
+            .select(users::id)
+            .for_update()
+            .first::<ModelId>(conn)?;
+
+        let role = role_slot_quotas::table
+            .find(user.role_id)
+            .select((role_slot_quotas::max_future_slots, role_slot_quotas::max_hours_per_week, role_slot_quotas::max_horizon_days))
+            .first::<Limits>(conn)
+            .optional()?;
+
+        let user = user_slot_quotas::table
+            .find(user.id)
+            .select((user_slot_quotas::max_future_slots, user_slot_quotas::max_hours_per_week, user_slot_quotas::max_horizon_days))
+            .first::<Limits>(conn)
+            .optional()?;
+
+        Ok(Self::merge(role, user))
+    }
+
+    /// Limits given for the user override the ones of the role one by one
+    fn merge(role: Option<Limits>, user: Option<Limits>) -> Self {
+        let role = role.unwrap_or_default();
+        let user = user.unwrap_or_default();
+
+        Quota {
+            max_future_slots: user.0.or(role.0),
+            max_hours_per_week: user.1.or(role.1),
+            max_horizon_days: user.2.or(role.2),
+        }
+    }
+
+    /// Whether the user can have the slot of the interval in addition to the reserved ones, apart from the ignored one
+    pub fn allows(
+        &self,
+        conn: &PgConnection,
+        user_id: ModelId,
+        (start_at, end_at): (NaiveDateTime, NaiveDateTime),
+        ignored: Option<ModelId>,
+        now: NaiveDateTime,
+    ) -> QueryResult<bool> {
+        if let Some(days) = self.max_horizon_days {
+            if start_at > now + Duration::days(days as i64) {
+                return Ok(false);
+            }
+        }
+
+        let usage = Usage::of(conn, user_id, start_at, ignored, now)?;
+
+        let within_slots = self.max_future_slots.iter()
+            .all(|max| usage.future_slots < *max as i64);
+        let within_hours = self.max_hours_per_week.iter()
+            .all(|max| usage.week_minutes + (end_at - start_at).num_minutes() <= *max as i64 * 60);
+
+        Ok(within_slots && within_hours)
+    }
+}
+
+/// Slots of a user that have not ended yet, and the minutes of the slots starting in a week
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Usage {
+    pub future_slots: i64,
+    pub week_minutes: i64,
+}
+
+impl Usage {
+    /// Usage in the week of the given time, the ignored slot is not counted
+    pub fn of(conn: &PgConnection, user_id: ModelId, at: NaiveDateTime, ignored: Option<ModelId>, now: NaiveDateTime) -> QueryResult<Self> {
+        let week_start = week_start(at);
+        // ids start from 1, so that nothing is ignored by default
+        let ignored = ignored.unwrap_or_default();
+
+        let future_slots = slots::table
+            .filter(slots::user_id.eq(user_id))
+            .filter(slots::end_at.gt(now))
+            .filter(slots::id.ne(ignored))
+            .count()
+            .get_result::<i64>(conn)?;
+
+        let week_minutes = slots::table
+            .filter(slots::user_id.eq(user_id))
+            .filter(slots::start_at.ge(week_start).and(slots::start_at.lt(week_start + Duration::weeks(1))))
+            .filter(slots::id.ne(ignored))
+            .select((slots::start_at, slots::end_at))
+            .load::<(NaiveDateTime, NaiveDateTime)>(conn)?
+            .into_iter()
+            .map(|(start_at, end_at)| (end_at - start_at).num_minutes())
+            .sum();
+
+        Ok(Usage { future_slots, week_minutes })
+    }
+}
+
+/// Usage of the user against the quota in the current week, remaining ones are missing if they are not limited
+#[derive(Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaResponse {
+    pub quota: Quota,
+    pub usage: Usage,
+    pub remaining_future_slots: Option<i64>,
+    pub remaining_week_minutes: Option<i64>,
+}
+
+impl QuotaResponse {
+    pub fn new(quota: Quota, usage: Usage) -> Self {
+        QuotaResponse {
+            quota,
+            usage,
+            remaining_future_slots: quota.max_future_slots.map(|max| (max as i64 - usage.future_slots).max(0)),
+            remaining_week_minutes: quota.max_hours_per_week.map(|max| (max as i64 * 60 - usage.week_minutes).max(0)),
+        }
+    }
+}
+
+/// Monday midnight of the week of the given time
+fn week_start(at: NaiveDateTime) -> NaiveDateTime {
+    at.date().and_hms(0, 0, 0) - Duration::days(at.weekday().num_days_from_monday() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+
+    #[test]
+    fn weeks_start_on_monday() {
+        assert_eq!(week_start(NaiveDate::from_ymd(2026, 10, 25).and_hms(23, 59, 0)), NaiveDate::from_ymd(2026, 10, 19).and_hms(0, 0, 0));
+        assert_eq!(week_start(NaiveDate::from_ymd(2026, 10, 19).and_hms(0, 0, 0)), NaiveDate::from_ymd(2026, 10, 19).and_hms(0, 0, 0));
+    }
+
+    #[test]
+    fn limits_of_the_user_override_the_ones_of_the_role() {
+        let quota = Quota::merge(Some((Some(10), Some(10), Some(30))), Some((None, Some(20), None)));
+
+        assert_eq!(quota, Quota { max_future_slots: Some(10), max_hours_per_week: Some(20), max_horizon_days: Some(30) });
+        assert_eq!(Quota::merge(None, None), Quota::default());
+    }
+}
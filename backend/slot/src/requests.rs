@@ -1,8 +1,19 @@
 use chrono::NaiveDateTime;
+use schemars::JsonSchema;
 use serde::Deserialize;
+
 use core::types::ModelId;
 
-#[derive(Deserialize)]
+use crate::ErrorMessage;
+use crate::models::{Frequency, SlotPolicyChangeset};
+use crate::quota::Quota;
+
+const MINUTES_IN_DAY: i32 = 60 * 24;
+const MAX_HORIZON_DAYS: i32 = 365;
+const MAX_NOTICE_MINUTES: i32 = MINUTES_IN_DAY * 7;
+const MAX_REASON_LENGTH: usize = 255;
+
+#[derive(Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ReservedQueryRequest {
     pub start_at: NaiveDateTime,
@@ -10,9 +21,160 @@ pub struct ReservedQueryRequest {
     pub count: u32,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SlotReserveRequest {
     pub start_at: NaiveDateTime,
     pub controller_id: ModelId,
 }
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotMoveRequest {
+    pub start_at: NaiveDateTime,
+}
+
+/// Slots of the controller that start and end within the window are offered once they are released
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct WaitlistRequest {
+    pub controller_id: ModelId,
+    pub window_start: NaiveDateTime,
+    pub window_end: NaiveDateTime,
+}
+
+impl WaitlistRequest {
+    pub fn validate(&self, now: NaiveDateTime) -> Result<(), ErrorMessage> {
+        if self.window_start < self.window_end && now < self.window_end {
+            Ok(())
+        } else {
+            Err(ErrorMessage::InvalidWaitlistWindow)
+        }
+    }
+}
+
+/// Reservations overlapping the window are cancelled if asked, otherwise they are only reported
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceWindowRequest {
+    pub controller_id: ModelId,
+    pub start_at: NaiveDateTime,
+    pub end_at: NaiveDateTime,
+    pub reason: Option<String>,
+    #[serde(default)]
+    pub cancel_reservations: bool,
+}
+
+impl MaintenanceWindowRequest {
+    pub fn validate(&self, now: NaiveDateTime) -> Result<(), ErrorMessage> {
+        let valid_reason = self.reason.iter().all(|reason| reason.chars().count() <= MAX_REASON_LENGTH);
+
+        if self.start_at < self.end_at && now < self.end_at && valid_reason {
+            Ok(())
+        } else {
+            Err(ErrorMessage::InvalidMaintenanceWindow)
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SlotSeriesRequest {
+    pub controller_id: ModelId,
+    #[serde(flatten)]
+    pub recurrence: RecurrenceRequest,
+}
+
+/// Series ends at the given time or after the given number of occurrences, at least one of them is required
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurrenceRequest {
+    pub start_at: NaiveDateTime,
+    pub frequency: Frequency,
+    pub until: Option<NaiveDateTime>,
+    pub count: Option<i32>,
+}
+
+impl RecurrenceRequest {
+    pub fn validate(&self) -> Result<(), ErrorMessage> {
+        let valid_until = self.until.iter().all(|until| *until >= self.start_at);
+        let valid_count = self.count.iter().all(|count| *count >= 1);
+
+        if (self.until.is_some() || self.count.is_some()) && valid_until && valid_count {
+            Ok(())
+        } else {
+            Err(ErrorMessage::InvalidRecurrence)
+        }
+    }
+}
+
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSlotPolicyRequest {
+    pub slot_minutes: i32,
+    pub gap_minutes: i32,
+    pub horizon_days: i32,
+    pub earliest_hour: i32,
+    pub latest_hour: i32,
+    pub notice_minutes: i32,
+}
+
+impl UpdateSlotPolicyRequest {
+    /// Bounds are the same as the check constraints of the slot_policies table
+    pub fn validate(&self) -> Result<(), ErrorMessage> {
+        let valid = (1..=MINUTES_IN_DAY).contains(&self.slot_minutes) &&
+            (0..=MINUTES_IN_DAY).contains(&self.gap_minutes) &&
+            (1..=MAX_HORIZON_DAYS).contains(&self.horizon_days) &&
+            0 <= self.earliest_hour && self.earliest_hour < self.latest_hour && self.latest_hour <= 24 &&
+            (0..=MAX_NOTICE_MINUTES).contains(&self.notice_minutes);
+
+        if valid {
+            Ok(())
+        } else {
+            Err(ErrorMessage::InvalidSlotPolicy)
+        }
+    }
+
+    pub fn into_changeset(self) -> SlotPolicyChangeset {
+        SlotPolicyChangeset {
+            slot_minutes: self.slot_minutes,
+            gap_minutes: self.gap_minutes,
+            horizon_days: self.horizon_days,
+            earliest_hour: self.earliest_hour,
+            latest_hour: self.latest_hour,
+            notice_minutes: self.notice_minutes,
+        }
+    }
+}
+
+/// Limits left empty are not enforced, or taken from the quota of the role when given for a user
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaRequest {
+    pub max_future_slots: Option<i32>,
+    pub max_hours_per_week: Option<i32>,
+    pub max_horizon_days: Option<i32>,
+}
+
+impl QuotaRequest {
+    pub fn validate(&self) -> Result<(), ErrorMessage> {
+        let valid = [self.max_future_slots, self.max_hours_per_week, self.max_horizon_days]
+            .iter()
+            .flatten()
+            .all(|limit| *limit >= 0);
+
+        if valid {
+            Ok(())
+        } else {
+            Err(ErrorMessage::InvalidQuota)
+        }
+    }
+
+    pub fn into_quota(self) -> Quota {
+        Quota {
+            max_future_slots: self.max_future_slots,
+            max_hours_per_week: self.max_hours_per_week,
+            max_horizon_days: self.max_horizon_days,
+        }
+    }
+}
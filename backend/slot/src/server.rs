@@ -0,0 +1,150 @@
+use std::time::Duration;
+
+use actix::prelude::*;
+use actix_web::web;
+use askama::Template;
+use chrono::{NaiveDateTime, Utc};
+use log::{error, info};
+use serde::Serialize;
+
+use core::types::{DBPool, ModelId};
+use service::{Notification, NotificationKind, NotificationMessage, NotificationServer};
+use service::mail::MailService;
+use user::models::user::User;
+
+use crate::models::WaitlistEntry;
+use crate::templates::SlotOfferMailTemplate;
+use crate::waitlist;
+
+/// Slot of the controller is released, it is offered to the waitlist
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct SlotReleased {
+    pub controller_id: ModelId,
+    pub start_at: NaiveDateTime,
+    pub end_at: NaiveDateTime,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SlotOffer {
+    entry_id: ModelId,
+    controller_id: ModelId,
+    start_at: NaiveDateTime,
+    end_at: NaiveDateTime,
+    expires_at: NaiveDateTime,
+}
+
+pub struct WaitlistServer {
+    pool: DBPool,
+    notification: Addr<NotificationServer>,
+    mail: MailService,
+}
+
+impl WaitlistServer {
+    pub fn new(pool: DBPool, notification: Addr<NotificationServer>, mail: MailService) -> Self {
+        WaitlistServer {
+            pool,
+            notification,
+            mail,
+        }
+    }
+
+    fn expire_offers(&mut self, ctx: &mut <Self as Actor>::Context) {
+        let conn = self.pool.get().unwrap();
+
+        async move {
+            web::block(move || waitlist::expire_offers(&conn, Utc::now().naive_utc()))
+                .await
+        }
+            .into_actor(self)
+            .then(|res, act, _| {
+                match res {
+                    Ok(offers) => for (entry, user) in offers {
+                        act.send_offer(entry, user);
+                    },
+                    Err(e) => error!("Error while expiring slot offers, {:?}", e)
+                }
+
+                fut::ready(())
+            })
+            .spawn(ctx);
+    }
+
+    fn send_offer(&self, entry: WaitlistEntry, user: User) {
+        let (start_at, end_at, expires_at) = match (entry.offered_start_at, entry.offered_end_at, entry.offer_expires_at) {
+            (Some(start_at), Some(end_at), Some(expires_at)) => (start_at, end_at, expires_at),
+            _ => return
+        };
+
+        info!("slot of controller {} at {} is offered to waitlist entry {}", entry.controller_id, start_at, entry.id);
+
+        self.notification.do_send(Notification {
+            user_id: user.id,
+            message: NotificationMessage {
+                kind: NotificationKind::SlotOffer,
+                data: SlotOffer {
+                    entry_id: entry.id,
+                    controller_id: entry.controller_id,
+                    start_at,
+                    end_at,
+                    expires_at,
+                },
+            },
+        });
+
+        let full_name = user.full_name();
+
+        let text = SlotOfferMailTemplate {
+            full_name: full_name.as_str(),
+            controller_id: entry.controller_id,
+            start_at,
+            end_at,
+            expires_at,
+        }
+            .render();
+
+        match text {
+            Ok(text) => self.mail.send_mail(user.email, String::from("Slot Offer"), text),
+            Err(e) => error!("Error while rendering slot offer mail, {:?}", e)
+        }
+    }
+}
+
+impl Actor for WaitlistServer {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        info!("WaitlistServer is started!");
+
+        ctx.run_interval(Duration::from_secs(waitlist::EXPIRY_CHECK_INTERVAL), |act, ctx| act.expire_offers(ctx));
+    }
+
+    fn stopped(&mut self, _: &mut Self::Context) {
+        info!("WaitlistServer is stopped!");
+    }
+}
+
+impl Handler<SlotReleased> for WaitlistServer {
+    type Result = ();
+
+    fn handle(&mut self, msg: SlotReleased, ctx: &mut Self::Context) {
+        let conn = self.pool.get().unwrap();
+
+        async move {
+            web::block(move || waitlist::offer(&conn, msg.controller_id, (msg.start_at, msg.end_at), Utc::now().naive_utc()))
+                .await
+        }
+            .into_actor(self)
+            .then(|res, act, _| {
+                match res {
+                    Ok(Some((entry, user))) => act.send_offer(entry, user),
+                    Ok(None) => {}
+                    Err(e) => error!("Error while offering released slot, {:?}", e)
+                }
+
+                fut::ready(())
+            })
+            .spawn(ctx);
+    }
+}
@@ -0,0 +1,24 @@
+use askama::Template;
+use chrono::NaiveDateTime;
+
+use core::types::ModelId;
+
+#[derive(Template)]
+#[template(path = "mails/slot-offer.html")]
+pub struct SlotOfferMailTemplate<'a> {
+    pub full_name: &'a str,
+    pub controller_id: ModelId,
+    pub start_at: NaiveDateTime,
+    pub end_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(Template)]
+#[template(path = "mails/slot-cancelled.html")]
+pub struct SlotCancelledMailTemplate<'a> {
+    pub full_name: &'a str,
+    pub controller_id: ModelId,
+    pub start_at: NaiveDateTime,
+    pub end_at: NaiveDateTime,
+    pub reason: &'a str,
+}
@@ -0,0 +1,124 @@
+use chrono::{Duration, NaiveDateTime};
+use diesel::prelude::*;
+
+use core::db::DieselEnum;
+use core::schema::{slot_policies, slot_waitlist_entries, users};
+use core::types::ModelId;
+use user::models::user::User;
+
+use crate::handlers::is_reserved;
+use crate::maintenance::is_under_maintenance;
+use crate::models::{SlotPolicy, WaitlistEntry, WaitlistStatus};
+
+/// How often expired offers are passed to the next waiters, in seconds
+pub const EXPIRY_CHECK_INTERVAL: u64 = 30;
+/// Released slot is held for the waiter this long, or until the slot starts if it is sooner, in minutes
+const CLAIM_PERIOD: i64 = 15;
+
+/// Whether an offer that has not expired holds a slot overlapping the interval, the offers of the given user do not
+/// count since the user can claim them
+pub fn is_held(
+    conn: &PgConnection,
+    policy: &SlotPolicy,
+    (start_at, end_at): (NaiveDateTime, NaiveDateTime),
+    user_id: Option<ModelId>,
+    now: NaiveDateTime,
+) -> QueryResult<bool> {
+    let mut query = slot_waitlist_entries::table
+        .filter(slot_waitlist_entries::controller_id.eq(policy.controller_id))
+        .filter(slot_waitlist_entries::status.eq(WaitlistStatus::Offered.value()))
+        .filter(slot_waitlist_entries::offer_expires_at.gt(now))
+        .filter(slot_waitlist_entries::offered_start_at.lt(end_at + policy.gap()))
+        .filter(slot_waitlist_entries::offered_end_at.gt(start_at - policy.gap()))
+        .into_boxed();
+
+    if let Some(user_id) = user_id {
+        query = query.filter(slot_waitlist_entries::user_id.ne(user_id));
+    }
+
+    diesel::dsl::select(diesel::dsl::exists(query))
+        .get_result(conn)
+}
+
+/// Offers the released slot to the first waiter whose window contains it, unless the slot has started or is taken
+/// again in the meantime
+pub fn offer(
+    conn: &PgConnection,
+    controller_id: ModelId,
+    (start_at, end_at): (NaiveDateTime, NaiveDateTime),
+    now: NaiveDateTime,
+) -> QueryResult<Option<(WaitlistEntry, User)>> {
+    conn.transaction(|| {
+        if start_at <= now {
+            return Ok(None);
+        }
+
+        let policy = slot_policies::table
+            .find(controller_id)
+            .first::<SlotPolicy>(conn)?;
+
+        if is_reserved(conn, &policy, (start_at, end_at), None)? ||
+            is_held(conn, &policy, (start_at, end_at), None, now)? ||
+            is_under_maintenance(conn, controller_id, (start_at, end_at))? {
+            return Ok(None);
+        }
+
+        let entry = slot_waitlist_entries::table
+            .filter(slot_waitlist_entries::controller_id.eq(controller_id))
+            .filter(slot_waitlist_entries::status.eq(WaitlistStatus::Waiting.value()))
+            .filter(slot_waitlist_entries::window_start.le(start_at))
+            .filter(slot_waitlist_entries::window_end.ge(end_at))
+            .order_by(slot_waitlist_entries::id.asc())
+            .for_update()
+            .skip_locked()
+            .first::<WaitlistEntry>(conn)
+            .optional()?;
+
+        let entry = match entry {
+            Some(entry) => entry,
+            None => return Ok(None)
+        };
+
+        let entry = diesel::update(&entry)
+            .set((
+                slot_waitlist_entries::status.eq(WaitlistStatus::Offered.value()),
+                slot_waitlist_entries::offered_start_at.eq(start_at),
+                slot_waitlist_entries::offered_end_at.eq(end_at),
+                slot_waitlist_entries::offer_expires_at.eq((now + Duration::minutes(CLAIM_PERIOD)).min(start_at)),
+            ))
+            .get_result::<WaitlistEntry>(conn)?;
+
+        let user = users::table
+            .find(entry.user_id)
+            .first::<User>(conn)?;
+
+        Ok(Some((entry, user)))
+    })
+}
+
+/// Expires the offers that are not claimed in time and offers their slots to the next waiters
+pub fn expire_offers(conn: &PgConnection, now: NaiveDateTime) -> QueryResult<Vec<(WaitlistEntry, User)>> {
+    let expired = conn.transaction(|| {
+        let due = slot_waitlist_entries::table
+            .filter(slot_waitlist_entries::status.eq(WaitlistStatus::Offered.value()))
+            .filter(slot_waitlist_entries::offer_expires_at.le(now))
+            .select(slot_waitlist_entries::id)
+            .for_update()
+            .skip_locked()
+            .load::<ModelId>(conn)?;
+
+        diesel::update(slot_waitlist_entries::table.filter(slot_waitlist_entries::id.eq_any(due)))
+            .set(slot_waitlist_entries::status.eq(WaitlistStatus::Expired.value()))
+            .get_results::<WaitlistEntry>(conn)
+    })?;
+
+    let mut offers = Vec::new();
+
+    for entry in expired {
+        if let (Some(start_at), Some(end_at)) = (entry.offered_start_at, entry.offered_end_at) {
+            offers.extend(offer(conn, entry.controller_id, (start_at, end_at), now)?);
+        }
+    }
+
+    Ok(offers)
+}
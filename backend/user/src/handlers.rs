@@ -1,20 +1,25 @@
 use actix_web::{get, put, Result, web, web::Json};
 use diesel::prelude::*;
 
+use core::client_ip::ClientIp;
+use core::models::audit::{self, AuditAction, AuditEntry, TargetKind};
 use core::responses::SuccessResponse;
 use core::sanitized::SanitizedJson;
 use core::schema::users;
 use core::types::DBPool;
 use core::utils::Hash;
+use derive::operation;
 
 use crate::models::user::User;
 use crate::requests::{UpdatePasswordRequest, UpdateProfileRequest};
 
+#[operation]
 #[get("/profile")]
 pub async fn fetch_profile(user: User) -> Result<Json<User>> {
     Ok(Json(user))
 }
 
+#[operation]
 #[put("/profile")]
 pub async fn update_profile(pool: web::Data<DBPool>, user: User, request: SanitizedJson<UpdateProfileRequest>) -> Result<Json<SuccessResponse>> {
     let conn = pool.get().unwrap();
@@ -29,16 +34,27 @@ pub async fn update_profile(pool: web::Data<DBPool>, user: User, request: Saniti
     Ok(Json(SuccessResponse::default()))
 }
 
+#[operation]
 #[put("/password")]
-pub async fn update_password(pool: web::Data<DBPool>, hash: web::Data<Hash>, user: User, request: web::Json<UpdatePasswordRequest>) -> Result<Json<SuccessResponse>> {
+pub async fn update_password(pool: web::Data<DBPool>, hash: web::Data<Hash>, user: User, ip: ClientIp, request: web::Json<UpdatePasswordRequest>) -> Result<Json<SuccessResponse>> {
     let conn = pool.get().unwrap();
 
     web::block(move || {
         let password = hash.sign512(&request.0.password);
 
-        diesel::update(users::table.find(user.id))
-            .set(users::password.eq(password))
-            .execute(&conn)
+        conn.transaction(|| {
+            diesel::update(users::table.find(user.id))
+                .set(users::password.eq(password))
+                .execute(&conn)?;
+
+            audit::record(&conn, AuditEntry {
+                actor_id: Some(user.id),
+                action: AuditAction::UpdatePassword,
+                target_kind: TargetKind::User,
+                target_id: user.id,
+                ip: ip.into_inner(),
+            })
+        })
     })
         .await?;
 
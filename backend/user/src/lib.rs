@@ -3,24 +3,23 @@ extern crate diesel;
 #[macro_use]
 extern crate lazy_static;
 
-use actix_web::web;
+use core::openapi::Access;
 
-use crate::middlewares::auth::Auth;
+use crate::router::Router;
 
 mod handlers;
 pub mod middlewares;
 pub mod models;
 mod requests;
+pub mod router;
 
-pub fn register(config: &mut web::ServiceConfig) {
-    config
-        .service(
-            web::scope("/api/user")
-                .wrap(Auth)
-                .service(handlers::fetch_profile)
-                .service(handlers::update_profile)
-                .service(handlers::update_password)
-        );
+pub fn register<R: Router>(router: &mut R) {
+    router.scope("/user", Access::User, |router| {
+        router
+            .service(handlers::fetch_profile)
+            .service(handlers::update_profile)
+            .service(handlers::update_password);
+    });
 }
 
 #[cfg(test)]
@@ -88,7 +88,7 @@ impl<S, B> Service for AuthMiddleware<S>
     }
 }
 
-fn parse_token(req: &ServiceRequest) -> Result<AuthToken, ErrorMessage> {
+pub fn parse_token(req: &ServiceRequest) -> Result<AuthToken, ErrorMessage> {
     lazy_static! {
                     static ref HEADER_RE: Regex = Regex::new(r"^Bearer ([A-Za-z0-9-_=]+\.[A-Za-z0-9-_=]+\.?[A-Za-z0-9-_.+/=]*$)").unwrap();
                     static ref QUERY_RE: Regex = Regex::new(r"token=([A-Za-z0-9-_=]+\.[A-Za-z0-9-_=]+\.?[A-Za-z0-9-_.+/=]*$)").unwrap();
@@ -1,2 +1,3 @@
 pub mod auth;
-pub mod role;
\ No newline at end of file
+pub mod rate_limit;
+pub mod role;
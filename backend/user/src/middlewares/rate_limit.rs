@@ -0,0 +1,259 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use actix_web::{Error, web};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderValue, RETRY_AFTER};
+use futures::future::{Either, ok, Ready};
+
+use core::client_ip::ClientIp;
+use core::error::ErrorMessaging;
+use core::ErrorMessage;
+use core::types::ModelId;
+
+use crate::middlewares::auth::parse_token;
+
+// buckets are pruned once there are this many of them, the full ones are dropped and then the least recently updated
+// ones until half of them are left, so that pruning is rare however many clients there are
+const MAX_BUCKETS: usize = 10_000;
+
+/// Number of requests allowed in a period, they can be made at once and are regained evenly over the period
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rate {
+    pub requests: u32,
+    pub period: Duration,
+}
+
+impl Rate {
+    pub fn new(requests: u32, period_secs: u64) -> Self {
+        Rate { requests, period: Duration::from_secs(period_secs) }
+    }
+
+    /// Parses the rate given as requests and period in seconds separated by a slash, such as `10/60`
+    pub fn parse(rate: &str) -> Option<Self> {
+        let mut parts = rate.trim().splitn(2, '/');
+
+        let requests = parts.next()?.trim().parse::<u32>().ok()?;
+        let period = parts.next()?.trim().parse::<u64>().ok()?;
+
+        if requests == 0 || period == 0 {
+            return None;
+        }
+
+        Some(Rate::new(requests, period))
+    }
+}
+
+/// Operations, given by the names of their handlers, sharing a rate limit
+pub struct RouteGroup {
+    pub name: &'static str,
+    pub rate: Rate,
+    pub operations: &'static [&'static str],
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Key {
+    User(ModelId),
+    Ip(String),
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    fn new(rate: Rate, now: Instant) -> Self {
+        Bucket { tokens: rate.requests as f64, updated_at: now }
+    }
+
+    /// Takes a token for a request, or gives how long to wait for the next one
+    fn take(&mut self, rate: Rate, now: Instant) -> Result<(), Duration> {
+        let per_token = rate.period.as_secs_f64() / rate.requests as f64;
+        let regained = now.duration_since(self.updated_at).as_secs_f64() / per_token;
+
+        self.tokens = (self.tokens + regained).min(rate.requests as f64);
+        self.updated_at = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) * per_token))
+        }
+    }
+
+    fn is_full(&self, rate: Rate, now: Instant) -> bool {
+        let per_token = rate.period.as_secs_f64() / rate.requests as f64;
+
+        self.tokens + now.duration_since(self.updated_at).as_secs_f64() / per_token >= rate.requests as f64
+    }
+}
+
+/// Buckets of the route groups, shared by the workers as app data
+pub struct RateLimiter {
+    groups: Vec<RouteGroup>,
+    buckets: Mutex<HashMap<(usize, Key), Bucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(groups: Vec<RouteGroup>) -> Self {
+        RateLimiter {
+            groups,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn groups(&self) -> &[RouteGroup] {
+        &self.groups
+    }
+
+    fn check(&self, operation: &str, key: Key, now: Instant) -> Result<(), Duration> {
+        let (index, group) = match self.groups.iter().enumerate().find(|(_, group)| group.operations.contains(&operation)) {
+            Some(group) => group,
+            None => return Ok(())
+        };
+
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if buckets.len() >= MAX_BUCKETS {
+            self.prune(&mut buckets, now);
+        }
+
+        buckets.entry((index, key))
+            .or_insert_with(|| Bucket::new(group.rate, now))
+            .take(group.rate, now)
+    }
+
+    fn prune(&self, buckets: &mut HashMap<(usize, Key), Bucket>, now: Instant) {
+        let groups = &self.groups;
+        buckets.retain(|(index, _), bucket| !bucket.is_full(groups[*index].rate, now));
+
+        let evicted = buckets.len().saturating_sub(MAX_BUCKETS / 2);
+        if evicted == 0 {
+            return;
+        }
+
+        let mut updates = buckets.values().map(|bucket| bucket.updated_at).collect::<Vec<_>>();
+        let (_, &mut oldest_kept, _) = updates.select_nth_unstable(evicted);
+        buckets.retain(|_, bucket| bucket.updated_at >= oldest_kept);
+    }
+}
+
+/// Limits the requests to the operations of the route groups of the `RateLimiter` in app data. Requests are counted
+/// per user if they carry a valid token, per client ip otherwise. It should wrap the app, operations are identified by
+/// the names of the resources they match.
+pub struct RateLimit;
+
+impl<S, B> Transform<S> for RateLimit
+    where
+        S: Service<Request=ServiceRequest, Response=ServiceResponse<B>, Error=Error> + 'static,
+        S::Future: 'static,
+        B: 'static
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RateLimitMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RateLimitMiddleware { service: Rc::new(RefCell::new(service)) })
+    }
+}
+
+pub struct RateLimitMiddleware<S> {
+    service: Rc<RefCell<S>>,
+}
+
+impl<S, B> Service for RateLimitMiddleware<S>
+    where
+        S: Service<Request=ServiceRequest, Response=ServiceResponse<B>, Error=Error> + 'static,
+        S::Future: 'static,
+        B: 'static
+{
+    type Request = ServiceRequest;
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Either<S::Future, Ready<Result<Self::Response, Self::Error>>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: ServiceRequest) -> Self::Future {
+        let res = match (req.app_data::<web::Data<RateLimiter>>(), req.match_name()) {
+            (Some(limiter), Some(operation)) => limiter.check(operation, key(&req), Instant::now()),
+            _ => Ok(())
+        };
+
+        match res {
+            Ok(()) => Either::Left(self.service.call(req)),
+            Err(retry_after) => {
+                let mut response = ErrorMessage::TooManyRequests.error();
+                // rounded up so that clients retrying after it are not rejected again
+                let seconds = retry_after.as_secs() + if retry_after.subsec_nanos() > 0 { 1 } else { 0 };
+                response.headers_mut().insert(RETRY_AFTER, HeaderValue::from(seconds));
+
+                Either::Right(ok(req.into_response(response.into_body())))
+            }
+        }
+    }
+}
+
+fn key(req: &ServiceRequest) -> Key {
+    if let Ok(token) = parse_token(req) {
+        return Key::User(token.user_id);
+    }
+
+    Key::Ip(ClientIp::of_service(req).into_inner().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rate_is_parsed() {
+        assert_eq!(Rate::parse("10/60"), Some(Rate::new(10, 60)));
+        assert_eq!(Rate::parse("0/60"), None);
+        assert_eq!(Rate::parse("10"), None);
+    }
+
+    #[test]
+    fn requests_over_the_rate_are_rejected_until_regained() {
+        let now = Instant::now();
+        let limiter = RateLimiter::new(vec![RouteGroup { name: "auth", rate: Rate::new(2, 60), operations: &["login"] }]);
+
+        assert!(limiter.check("login", Key::Ip(String::from("a")), now).is_ok());
+        assert!(limiter.check("login", Key::Ip(String::from("a")), now).is_ok());
+        assert_eq!(limiter.check("login", Key::Ip(String::from("a")), now), Err(Duration::from_secs(30)));
+
+        assert!(limiter.check("login", Key::Ip(String::from("b")), now).is_ok());
+        assert!(limiter.check("fetch_profile", Key::Ip(String::from("a")), now).is_ok());
+        assert!(limiter.check("login", Key::Ip(String::from("a")), now + Duration::from_secs(30)).is_ok());
+    }
+
+    #[test]
+    fn least_recently_updated_buckets_are_evicted() {
+        let now = Instant::now();
+        let limiter = RateLimiter::new(vec![RouteGroup { name: "auth", rate: Rate::new(1, 60), operations: &["login"] }]);
+
+        for i in 0..MAX_BUCKETS {
+            let at = now + Duration::from_millis(i as u64);
+            assert!(limiter.check("login", Key::Ip(i.to_string()), at).is_ok());
+        }
+
+        let at = now + Duration::from_secs(20);
+        assert!(limiter.check("login", Key::Ip(String::from("new")), at).is_ok());
+
+        assert!(limiter.buckets.lock().unwrap().len() <= MAX_BUCKETS / 2 + 1);
+        assert!(limiter.check("login", Key::Ip((MAX_BUCKETS - 1).to_string()), at).is_err());
+        assert!(limiter.check("login", Key::Ip(String::from("0")), at).is_ok());
+    }
+}
@@ -4,6 +4,7 @@ use diesel::{Identifiable, Insertable, Queryable};
 use diesel::pg::Pg;
 use diesel::sql_types::VarChar;
 use futures::future::{err, ok, Ready};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use core::db::DieselEnum;
@@ -12,7 +13,7 @@ use core::ErrorMessage;
 use core::schema::users;
 use core::types::ModelId;
 
-#[derive(Queryable, Identifiable, Serialize, Clone)]
+#[derive(Queryable, Identifiable, Serialize, Clone, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct User {
     pub id: ModelId,
@@ -20,6 +21,7 @@ pub struct User {
     pub last_name: String,
     pub email: String,
     #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub password: String,
     pub status: UserStatus,
     pub role_id: ModelId,
@@ -45,7 +47,7 @@ impl FromRequest for User {
     }
 }
 
-#[derive(Queryable, Serialize)]
+#[derive(Queryable, Serialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct SlimUser {
     pub id: ModelId,
@@ -72,7 +74,7 @@ pub const SLIM_USER_COLUMNS: (users::id, users::first_name, users::last_name, us
     users::role_id,
 );
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Clone, JsonSchema)]
 pub enum UserStatus {
     NotVerified,
     Verified,
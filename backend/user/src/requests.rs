@@ -1,10 +1,11 @@
+use schemars::JsonSchema;
 use serde::Deserialize;
 
 use core::sanitized::Sanitize;
 use core::schema::users;
 use derive::Sanitize;
 
-#[derive(AsChangeset, Sanitize, Deserialize)]
+#[derive(AsChangeset, Sanitize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 #[table_name = "users"]
 pub struct UpdateProfileRequest {
@@ -12,7 +13,7 @@ pub struct UpdateProfileRequest {
     pub last_name: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, JsonSchema)]
 pub struct UpdatePasswordRequest {
     pub password: String
 }
\ No newline at end of file
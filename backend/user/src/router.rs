@@ -0,0 +1,43 @@
+use actix_web::dev::HttpServiceFactory;
+use actix_web::web;
+
+use core::openapi::{Access, Operation, Spec};
+
+use crate::middlewares::auth::Auth;
+use crate::middlewares::role::AdminUser;
+
+/// Routes of the crates are registered through this trait, so that the same registration both serves the handlers and
+/// builds the OpenAPI document of them. Scope paths are relative to the version prefix, like `/experiment`.
+pub trait Router {
+    fn scope<F: FnOnce(&mut Self)>(&mut self, path: &str, access: Access, f: F);
+
+    fn service<S: HttpServiceFactory + Operation + 'static>(&mut self, service: S) -> &mut Self;
+}
+
+impl Router for web::ServiceConfig {
+    fn scope<F: FnOnce(&mut Self)>(&mut self, path: &str, access: Access, f: F) {
+        let scope = web::scope(path).configure(f);
+
+        match access {
+            Access::Public => self.service(scope),
+            Access::User => self.service(scope.wrap(Auth)),
+            Access::Admin => self.service(scope.wrap(AdminUser)),
+        };
+    }
+
+    fn service<S: HttpServiceFactory + Operation + 'static>(&mut self, service: S) -> &mut Self {
+        web::ServiceConfig::service(self, service)
+    }
+}
+
+impl Router for Spec {
+    fn scope<F: FnOnce(&mut Self)>(&mut self, path: &str, access: Access, f: F) {
+        Spec::scope(self, path, access, f)
+    }
+
+    fn service<S: HttpServiceFactory + Operation + 'static>(&mut self, _: S) -> &mut Self {
+        self.add::<S>();
+
+        self
+    }
+}
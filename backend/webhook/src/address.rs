@@ -0,0 +1,118 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
+
+use actix_connect::{Connect, ConnectError, Connection};
+use actix_service::{apply_fn, Service};
+use actix_web::http::Uri;
+use actix_web::rt::net::TcpStream;
+
+/// Tells whether webhooks can post to loopback, private and link-local addresses, given as app data. Only meant for
+/// trying a receiver running next to the backend.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WebhookConfig {
+    pub allow_private_hosts: bool,
+}
+
+/// Whether webhooks can post to the address. Addresses in the networks of the backend itself are refused so that
+/// webhooks can not be used to reach services that are not exposed.
+pub fn is_allowed(ip: IpAddr, config: WebhookConfig) -> bool {
+    config.allow_private_hosts || match ip {
+        IpAddr::V4(ip) => is_public_v4(ip),
+        // mapped and compatible addresses reach the ipv4 network, :: and ::1 become addresses in 0.0.0.0/8
+        IpAddr::V6(ip) => match ip.to_ipv4() {
+            Some(ip) => is_public_v4(ip),
+            None => is_public_v6(ip)
+        }
+    }
+}
+
+fn is_public_v4(ip: Ipv4Addr) -> bool {
+    let octets = ip.octets();
+
+    !(ip.is_loopback() ||
+        ip.is_private() ||
+        ip.is_link_local() ||
+        ip.is_unspecified() ||
+        ip.is_broadcast() ||
+        ip.is_multicast() ||
+        octets[0] == 0 ||
+        // shared address space of carrier-grade NAT, 100.64.0.0/10
+        (octets[0] == 100 && (octets[1] & 0xc0) == 64))
+}
+
+fn is_public_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+
+    !(ip.is_loopback() ||
+        ip.is_unspecified() ||
+        ip.is_multicast() ||
+        // unique local, fc00::/7
+        (first & 0xfe00) == 0xfc00 ||
+        // link-local, fe80::/10
+        (first & 0xffc0) == 0xfe80)
+}
+
+/// Resolves the host of the url and checks all of its addresses. Deliveries check the address they connect to as
+/// well, since the host may resolve to another address later.
+pub fn check_url(url: &str, config: WebhookConfig) -> bool {
+    let uri = match url.parse::<Uri>() {
+        Ok(uri) => uri,
+        Err(_) => return false
+    };
+
+    let host = match uri.host() {
+        // brackets of ipv6 hosts are not accepted by the resolver
+        Some(host) => host.trim_start_matches('[').trim_end_matches(']'),
+        None => return false
+    };
+
+    let port = uri.port_u16()
+        .unwrap_or(if uri.scheme_str() == Some("https") { 443 } else { 80 });
+
+    match (host, port).to_socket_addrs() {
+        Ok(addrs) => {
+            let addrs = addrs.collect::<Vec<SocketAddr>>();
+
+            !addrs.is_empty() && addrs.iter().all(|addr| is_allowed(addr.ip(), config))
+        }
+        Err(_) => false
+    }
+}
+
+/// Tcp connector of the deliveries, refuses the connections whose peer address is not allowed
+pub fn connector(config: WebhookConfig) -> impl Service<Request = Connect<Uri>, Response = Connection<Uri, TcpStream>, Error = ConnectError> + Clone {
+    apply_fn(actix_connect::default_connector(), move |req, srv| {
+        let connect = srv.call(req);
+
+        async move {
+            let connection = connect.await?;
+            let peer_addr = connection.get_ref().peer_addr()?;
+
+            if is_allowed(peer_addr.ip(), config) {
+                Ok(connection)
+            } else {
+                Err(ConnectError::Io(io::Error::new(io::ErrorKind::PermissionDenied, "address of the host is not allowed")))
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowed(ip: &str) -> bool {
+        is_allowed(ip.parse().unwrap(), WebhookConfig::default())
+    }
+
+    #[test]
+    fn internal_addresses_are_refused() {
+        for ip in &["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0", "::1", "fe80::1", "fd00::1", "::ffff:127.0.0.1"] {
+            assert!(!allowed(ip), "{} is allowed", ip);
+        }
+
+        assert!(allowed("93.184.216.34"));
+        assert!(allowed("2606:2800:220:1:248:1893:25c8:1946"));
+        assert!(is_allowed("127.0.0.1".parse().unwrap(), WebhookConfig { allow_private_hosts: true }));
+    }
+}
@@ -0,0 +1,210 @@
+use chrono::{Duration, NaiveDateTime};
+use diesel::prelude::*;
+use ring::hmac;
+use serde::Serialize;
+use serde_json::Value;
+
+use core::db::DieselEnum;
+use core::schema::{slots, webhook_deliveries, webhooks};
+use core::types::ModelId;
+
+use crate::models::{DeliveryStatus, EventKind, Webhook, WebhookDelivery};
+
+/// How often due deliveries are attempted and slots are checked, in seconds
+pub const DELIVERY_INTERVAL: u64 = 10;
+pub const SLOT_CHECK_INTERVAL: u64 = 30;
+/// Timeout of a single attempt, in seconds
+pub const REQUEST_TIMEOUT: u64 = 10;
+/// Deliveries are given up after this many attempts
+pub const MAX_ATTEMPTS: i32 = 6;
+// in seconds, delay before the first retry, doubled after each attempt
+const RETRY_DELAY: i64 = 30;
+// in seconds, deliveries being attempted are not picked again within this period
+const LEASE: i64 = 60;
+// deliveries attempted at once
+const BATCH_SIZE: i64 = 50;
+
+pub const SIGNATURE_HEADER: &str = "X-Testbed-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-Testbed-Timestamp";
+pub const EVENT_HEADER: &str = "X-Testbed-Event";
+pub const DELIVERY_HEADER: &str = "X-Testbed-Delivery";
+
+/// Body posted to webhooks, the same for every attempt of a delivery
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeliveryBody<'a> {
+    pub id: ModelId,
+    pub event: EventKind,
+    pub created_at: NaiveDateTime,
+    pub data: &'a Value,
+}
+
+impl<'a> DeliveryBody<'a> {
+    pub fn new(delivery: &'a WebhookDelivery) -> Self {
+        DeliveryBody {
+            id: delivery.id,
+            event: delivery.event,
+            created_at: delivery.created_at,
+            data: &delivery.payload,
+        }
+    }
+}
+
+/// Outcome of a single attempt
+pub struct Attempt {
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+}
+
+impl Attempt {
+    pub fn is_successful(&self) -> bool {
+        self.response_status.iter().any(|status| (200..300).contains(status))
+    }
+}
+
+/// Hex encoded HMAC-SHA256 of the timestamp and the body joined with a dot, signing the timestamp lets receivers
+/// reject replayed deliveries
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+    let signature = hmac::sign(&key, format!("{}.{}", timestamp, body).as_bytes());
+
+    signature.as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Delay before the next attempt of a delivery that is attempted the given times
+pub fn retry_delay(attempts: i32) -> Duration {
+    Duration::seconds(RETRY_DELAY << (attempts - 1).clamp(0, MAX_ATTEMPTS))
+}
+
+/// Creates a delivery of the event for each active webhook of the user subscribed to it
+pub fn enqueue(conn: &PgConnection, user_id: ModelId, kind: EventKind, payload: &Value) -> QueryResult<usize> {
+    let webhook_ids = webhooks::table
+        .filter(webhooks::user_id.eq(user_id))
+        .filter(webhooks::active.eq(true))
+        .filter(webhooks::events.contains(vec![kind.value()]))
+        .select(webhooks::id)
+        .load::<ModelId>(conn)?;
+
+    let deliveries = webhook_ids.into_iter()
+        .map(|webhook_id| (
+            webhook_deliveries::webhook_id.eq(webhook_id),
+            webhook_deliveries::event.eq(kind.value()),
+            webhook_deliveries::payload.eq(payload),
+        ))
+        .collect::<Vec<_>>();
+
+    diesel::insert_into(webhook_deliveries::table)
+        .values(&deliveries)
+        .execute(conn)
+}
+
+/// Claims the pending deliveries that are due by postponing them for the lease, so that they are not attempted twice.
+/// The conditions are checked again while updating, deliveries claimed by someone else in between are left out.
+pub fn claim_due(conn: &PgConnection, now: NaiveDateTime) -> QueryResult<Vec<(WebhookDelivery, Webhook)>> {
+    let due = webhook_deliveries::table
+        .filter(webhook_deliveries::status.eq(DeliveryStatus::Pending.value()))
+        .filter(webhook_deliveries::next_attempt_at.le(now))
+        .order_by(webhook_deliveries::next_attempt_at.asc())
+        .limit(BATCH_SIZE)
+        .select(webhook_deliveries::id)
+        .load::<ModelId>(conn)?;
+
+    let deliveries = diesel::update(
+        webhook_deliveries::table
+            .filter(webhook_deliveries::id.eq_any(due))
+            .filter(webhook_deliveries::status.eq(DeliveryStatus::Pending.value()))
+            .filter(webhook_deliveries::next_attempt_at.le(now))
+    )
+        .set(webhook_deliveries::next_attempt_at.eq(now + Duration::seconds(LEASE)))
+        .get_results::<WebhookDelivery>(conn)?;
+
+    let webhooks = webhooks::table
+        .filter(webhooks::id.eq_any(deliveries.iter().map(|delivery| delivery.webhook_id).collect::<Vec<ModelId>>()))
+        .load::<Webhook>(conn)?;
+
+    Ok(deliveries.into_iter()
+        .filter_map(|delivery| {
+            let webhook = webhooks.iter().find(|webhook| webhook.id == delivery.webhook_id)?.clone();
+
+            Some((delivery, webhook))
+        })
+        .collect())
+}
+
+/// Stores the outcome of an attempt, deliveries that fail are retried with backoff until they run out of attempts
+pub fn record(conn: &PgConnection, delivery: &WebhookDelivery, attempt: Attempt, now: NaiveDateTime) -> QueryResult<()> {
+    let attempts = delivery.attempts + 1;
+
+    let (status, next_attempt_at, delivered_at) = if attempt.is_successful() {
+        (DeliveryStatus::Delivered, now, Some(now))
+    } else if attempts >= MAX_ATTEMPTS {
+        (DeliveryStatus::Failed, now, None)
+    } else {
+        (DeliveryStatus::Pending, now + retry_delay(attempts), None)
+    };
+
+    diesel::update(webhook_deliveries::table.find(delivery.id))
+        .set((
+            webhook_deliveries::status.eq(status.value()),
+            webhook_deliveries::attempts.eq(attempts),
+            webhook_deliveries::next_attempt_at.eq(next_attempt_at),
+            webhook_deliveries::response_status.eq(attempt.response_status),
+            webhook_deliveries::error.eq(attempt.error),
+            webhook_deliveries::delivered_at.eq(delivered_at),
+        ))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+/// Slot reservations that have started or ended within the period, as events of their owners
+pub fn slot_events(conn: &PgConnection, from: NaiveDateTime, to: NaiveDateTime) -> QueryResult<Vec<(ModelId, EventKind, Value)>> {
+    let mut events = Vec::new();
+
+    let started = slots::table
+        .filter(slots::start_at.gt(from).and(slots::start_at.le(to)))
+        .select((slots::id, slots::user_id, slots::controller_id, slots::start_at, slots::end_at))
+        .load::<(ModelId, ModelId, ModelId, NaiveDateTime, NaiveDateTime)>(conn)?;
+
+    let ended = slots::table
+        .filter(slots::end_at.gt(from).and(slots::end_at.le(to)))
+        .select((slots::id, slots::user_id, slots::controller_id, slots::start_at, slots::end_at))
+        .load::<(ModelId, ModelId, ModelId, NaiveDateTime, NaiveDateTime)>(conn)?;
+
+    for (kind, slots) in [(EventKind::SlotStart, started), (EventKind::SlotEnd, ended)] {
+        for (slot_id, user_id, controller_id, start_at, end_at) in slots {
+            events.push((user_id, kind, serde_json::to_value(SlotEvent { slot_id, controller_id, start_at, end_at }).unwrap()));
+        }
+    }
+
+    Ok(events)
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SlotEvent {
+    slot_id: ModelId,
+    controller_id: ModelId,
+    start_at: NaiveDateTime,
+    end_at: NaiveDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_is_hmac_of_timestamp_and_body() {
+        // echo -n '1700000000.{}' | openssl dgst -sha256 -hmac 'secret'
+        assert_eq!(sign("secret", 1700000000, "{}"), "b8569b78799ff9e3cbff0fc2d63a33a2b57f3282abd07c37ae5e8e7d79a5f163");
+    }
+
+    #[test]
+    fn retries_back_off() {
+        assert_eq!(retry_delay(1), Duration::seconds(30));
+        assert_eq!(retry_delay(3), Duration::seconds(120));
+    }
+}
@@ -0,0 +1,207 @@
+use actix::Addr;
+use actix_web::{delete, get, post, put, web, web::Json};
+use chrono::Utc;
+use diesel::prelude::*;
+use serde_json::json;
+
+use core::db::DieselEnum;
+use core::models::paginate::{CountStarOver, Paginate, Pagination, PaginationRequest};
+use core::responses::SuccessResponse;
+use core::schema::{webhook_deliveries, webhooks};
+use core::types::{DBPool, ModelId, Result};
+use derive::operation;
+use user::models::user::User;
+
+use crate::address::{self, WebhookConfig};
+use crate::ErrorMessage;
+use crate::models::{EventKind, Webhook, WebhookDelivery};
+use crate::requests::{CreateWebhookRequest, UpdateWebhookRequest};
+use crate::server::{DeliverDue, WebhookServer};
+
+#[operation]
+#[get("webhooks")]
+pub async fn fetch_webhooks(pool: web::Data<DBPool>, user: User) -> Result<Json<Vec<Webhook>>> {
+    let conn = pool.get().unwrap();
+
+    let webhooks = web::block(move ||
+        webhooks::table
+            .filter(webhooks::user_id.eq(user.id))
+            .order_by(webhooks::id.asc())
+            .load::<Webhook>(&conn)
+    )
+        .await?;
+
+    Ok(Json(webhooks))
+}
+
+#[operation]
+#[post("webhooks")]
+pub async fn create_webhook(
+    pool: web::Data<DBPool>,
+    config: web::Data<WebhookConfig>,
+    user: User,
+    request: web::Json<CreateWebhookRequest>,
+) -> Result<Json<Webhook>> {
+    let request = request.into_inner();
+    request.validate()?;
+
+    let conn = pool.get().unwrap();
+    let config = *config.get_ref();
+
+    let webhook = web::block(move || -> Result<Webhook> {
+        check_host(request.url.as_str(), config)?;
+
+        diesel::insert_into(webhooks::table)
+            .values((
+                webhooks::user_id.eq(user.id),
+                webhooks::url.eq(request.url),
+                webhooks::secret.eq(request.secret),
+                webhooks::events.eq(event_values(&request.events)),
+                webhooks::active.eq(request.active),
+            ))
+            .get_result::<Webhook>(&conn)
+            .map_err(|e| e.into())
+    })
+        .await?;
+
+    Ok(Json(webhook))
+}
+
+#[operation]
+#[put("webhook/{id}")]
+pub async fn update_webhook(
+    pool: web::Data<DBPool>,
+    config: web::Data<WebhookConfig>,
+    user: User,
+    webhook_id: web::Path<ModelId>,
+    request: web::Json<UpdateWebhookRequest>,
+) -> Result<Json<Webhook>> {
+    let request = request.into_inner();
+    request.validate()?;
+
+    let conn = pool.get().unwrap();
+    let config = *config.get_ref();
+
+    let webhook = web::block(move || -> Result<Webhook> {
+        if let Some(url) = &request.url {
+            check_host(url.as_str(), config)?;
+        }
+
+        let target = webhooks::table
+            .filter(webhooks::user_id.eq(user.id))
+            .find(webhook_id.into_inner());
+
+        diesel::update(target)
+            .set((
+                request.url.map(|url| webhooks::url.eq(url)),
+                request.secret.map(|secret| webhooks::secret.eq(secret)),
+                request.events.map(|events| webhooks::events.eq(event_values(&events))),
+                request.active.map(|active| webhooks::active.eq(active)),
+            ))
+            .get_result::<Webhook>(&conn)
+            .map_err(|e| e.into())
+    })
+        .await?;
+
+    Ok(Json(webhook))
+}
+
+#[operation]
+#[delete("webhook/{id}")]
+pub async fn delete_webhook(pool: web::Data<DBPool>, user: User, webhook_id: web::Path<ModelId>) -> Result<Json<SuccessResponse>> {
+    let conn = pool.get().unwrap();
+
+    web::block(move ||
+        diesel::delete(
+            webhooks::table
+                .filter(webhooks::user_id.eq(user.id))
+                .find(webhook_id.into_inner())
+        )
+            .get_result::<Webhook>(&conn)
+    )
+        .await?;
+
+    Ok(Json(SuccessResponse::default()))
+}
+
+/// Latest deliveries of the webhook, to see what is sent and how the receiver responded
+#[operation]
+#[get("webhook/{id}/deliveries")]
+pub async fn fetch_deliveries(
+    pool: web::Data<DBPool>,
+    user: User,
+    webhook_id: web::Path<ModelId>,
+    pagination: web::Query<PaginationRequest>,
+) -> Result<Json<Pagination<WebhookDelivery>>> {
+    let conn = pool.get().unwrap();
+
+    let deliveries = web::block(move || -> Result<Pagination<WebhookDelivery>> {
+        let webhook = find_webhook(&conn, webhook_id.into_inner(), user.id)?;
+
+        webhook_deliveries::table
+            .filter(webhook_deliveries::webhook_id.eq(webhook.id))
+            .order_by(webhook_deliveries::id.desc())
+            .select((webhook_deliveries::all_columns, CountStarOver))
+            .paginate(pagination.page)
+            .per_page(pagination.per_page)
+            .load_and_count_pages::<WebhookDelivery>(&conn)
+            .map_err(|e| e.into())
+    })
+        .await?;
+
+    Ok(Json(deliveries))
+}
+
+/// Sends a Ping event to the webhook regardless of its subscriptions, so that the receiver can be checked
+#[operation]
+#[post("webhook/{id}/ping")]
+pub async fn ping_webhook(
+    pool: web::Data<DBPool>,
+    webhook_server: web::Data<Addr<WebhookServer>>,
+    user: User,
+    webhook_id: web::Path<ModelId>,
+) -> Result<Json<WebhookDelivery>> {
+    let conn = pool.get().unwrap();
+
+    let delivery = web::block(move || -> Result<WebhookDelivery> {
+        let webhook = find_webhook(&conn, webhook_id.into_inner(), user.id)?;
+
+        diesel::insert_into(webhook_deliveries::table)
+            .values((
+                webhook_deliveries::webhook_id.eq(webhook.id),
+                webhook_deliveries::event.eq(EventKind::Ping.value()),
+                webhook_deliveries::payload.eq(json!({ "webhookId": webhook.id, "sentAt": Utc::now().naive_utc() })),
+            ))
+            .get_result::<WebhookDelivery>(&conn)
+            .map_err(|e| e.into())
+    })
+        .await?;
+
+    webhook_server.do_send(DeliverDue);
+
+    Ok(Json(delivery))
+}
+
+fn find_webhook(conn: &PgConnection, webhook_id: ModelId, user_id: ModelId) -> QueryResult<Webhook> {
+    webhooks::table
+        .filter(webhooks::user_id.eq(user_id))
+        .find(webhook_id)
+        .first::<Webhook>(conn)
+}
+
+fn event_values(events: &[EventKind]) -> Vec<String> {
+    let mut values = events.iter().map(|event| event.value()).collect::<Vec<String>>();
+    values.sort();
+    values.dedup();
+
+    values
+}
+
+/// Refuses the urls whose host can not be resolved or resolves to an address webhooks can not post to
+fn check_host(url: &str, config: WebhookConfig) -> Result<()> {
+    if address::check_url(url, config) {
+        Ok(())
+    } else {
+        Err(Box::new(ErrorMessage::InvalidWebhook))
+    }
+}
@@ -0,0 +1,53 @@
+use actix_web::http::StatusCode;
+
+use core::error::{ErrorMessaging, HttpError};
+use core::openapi::Access;
+use user::router::Router;
+
+pub use crate::address::WebhookConfig;
+pub use crate::models::EventKind;
+pub use crate::server::{DeliverDue, Event, WebhookServer};
+
+mod address;
+mod delivery;
+mod handlers;
+pub mod models;
+mod requests;
+mod server;
+
+pub fn register<R: Router>(router: &mut R) {
+    router.scope("/webhook", Access::User, |router| {
+        router
+            .service(handlers::fetch_webhooks)
+            .service(handlers::create_webhook)
+            .service(handlers::update_webhook)
+            .service(handlers::delete_webhook)
+            .service(handlers::fetch_deliveries)
+            .service(handlers::ping_webhook);
+    });
+}
+
+#[derive(Debug)]
+pub enum ErrorMessage {
+    InvalidWebhook,
+}
+
+impl ErrorMessaging for ErrorMessage {
+    fn value(&self) -> HttpError {
+        match self {
+            ErrorMessage::InvalidWebhook => HttpError {
+                code: StatusCode::UNPROCESSABLE_ENTITY,
+                error_code: 100,
+                message: String::from("invalid_webhook"),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn it_works() {
+        assert_eq!(2 + 2, 4);
+    }
+}
@@ -0,0 +1,79 @@
+use chrono::NaiveDateTime;
+use diesel::{Identifiable, Queryable};
+use diesel::pg::Pg;
+use diesel::sql_types::VarChar;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use core::db::DieselEnum;
+use core::schema::{webhook_deliveries, webhooks};
+use core::types::ModelId;
+
+/// Events a webhook can subscribe to, Ping is only sent on request to check a webhook
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, Copy, JsonSchema, Default)]
+pub enum EventKind {
+    JobStatus,
+    JobArtifact,
+    SlotStart,
+    SlotEnd,
+    #[default]
+    Ping,
+}
+
+impl Queryable<VarChar, Pg> for EventKind {
+    type Row = String;
+
+    fn build(row: Self::Row) -> Self {
+        Self::build_from_string(row)
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq, Clone, JsonSchema, Default)]
+pub enum DeliveryStatus {
+    #[default]
+    Pending,
+    Delivered,
+    Failed,
+}
+
+impl Queryable<VarChar, Pg> for DeliveryStatus {
+    type Row = String;
+
+    fn build(row: Self::Row) -> Self {
+        Self::build_from_string(row)
+    }
+}
+
+/// Url the events of the user are posted to, signed with the secret which is never sent back
+#[derive(Identifiable, Queryable, Serialize, Clone, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub id: ModelId,
+    pub user_id: ModelId,
+    pub url: String,
+    #[serde(skip_serializing)]
+    #[schemars(skip)]
+    pub secret: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// An event posted to a webhook together with the outcome of its latest attempt
+#[derive(Identifiable, Queryable, Serialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+#[table_name = "webhook_deliveries"]
+pub struct WebhookDelivery {
+    pub id: ModelId,
+    pub webhook_id: ModelId,
+    pub event: EventKind,
+    pub payload: serde_json::Value,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    pub next_attempt_at: NaiveDateTime,
+    pub response_status: Option<i32>,
+    pub error: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub delivered_at: Option<NaiveDateTime>,
+}
@@ -0,0 +1,62 @@
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::ErrorMessage;
+use crate::models::EventKind;
+
+/// Upper bound of the length of urls, limited by the column size
+const MAX_URL_LENGTH: usize = 2048;
+const MIN_SECRET_LENGTH: usize = 16;
+const MAX_SECRET_LENGTH: usize = 255;
+
+// urls and secrets are not sanitized since they are never rendered and would be broken by html encoding
+#[derive(Deserialize, JsonSchema)]
+pub struct CreateWebhookRequest {
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<EventKind>,
+    #[serde(default = "active_default")]
+    pub active: bool,
+}
+
+fn active_default() -> bool {
+    true
+}
+
+#[derive(Deserialize, JsonSchema)]
+pub struct UpdateWebhookRequest {
+    pub url: Option<String>,
+    pub secret: Option<String>,
+    pub events: Option<Vec<EventKind>>,
+    pub active: Option<bool>,
+}
+
+impl CreateWebhookRequest {
+    pub fn validate(&self) -> Result<(), ErrorMessage> {
+        validate(Some(&self.url), Some(&self.secret), Some(&self.events))
+    }
+}
+
+impl UpdateWebhookRequest {
+    pub fn validate(&self) -> Result<(), ErrorMessage> {
+        validate(self.url.as_ref(), self.secret.as_ref(), self.events.as_ref())
+    }
+}
+
+fn validate(url: Option<&String>, secret: Option<&String>, events: Option<&Vec<EventKind>>) -> Result<(), ErrorMessage> {
+    let valid_url = url.iter().all(|url| {
+        (url.starts_with("http://") || url.starts_with("https://")) &&
+            url.len() <= MAX_URL_LENGTH &&
+            !url.chars().any(|c| c.is_whitespace() || c.is_control())
+    });
+
+    let valid_secret = secret.iter().all(|secret| (MIN_SECRET_LENGTH..=MAX_SECRET_LENGTH).contains(&secret.len()));
+
+    let valid_events = events.iter().all(|events| !events.is_empty() && !events.contains(&EventKind::Ping));
+
+    if valid_url && valid_secret && valid_events {
+        Ok(())
+    } else {
+        Err(ErrorMessage::InvalidWebhook)
+    }
+}
@@ -0,0 +1,205 @@
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix::prelude::*;
+use actix_web::client::{Client, Connector};
+use actix_web::web;
+use chrono::{NaiveDateTime, Utc};
+use log::{error, info, warn};
+use rustls::ClientConfig;
+use serde::Serialize;
+
+use core::db::DieselEnum;
+use core::types::{DBPool, ModelId};
+
+use crate::address::{self, WebhookConfig};
+use crate::delivery::{self, Attempt, DeliveryBody};
+use crate::models::{EventKind, Webhook, WebhookDelivery};
+
+/// Event of the user, posted to the webhooks of the user that are subscribed to its kind
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct Event<T> {
+    pub user_id: ModelId,
+    pub kind: EventKind,
+    pub data: T,
+}
+
+/// Attempts the deliveries that are due without waiting for the next interval
+#[derive(Message)]
+#[rtype(result = "()")]
+pub struct DeliverDue;
+
+pub struct WebhookServer {
+    pool: DBPool,
+    client: Rc<Client>,
+    // slots starting or ending after this time are not notified yet
+    slots_checked_at: NaiveDateTime,
+}
+
+impl WebhookServer {
+    pub fn new(pool: DBPool, config: WebhookConfig) -> Self {
+        let mut tls_config = ClientConfig::new();
+        tls_config.root_store.add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
+
+        // hosts are checked while connecting since they may resolve to other addresses than at registration
+        let connector = Connector::new()
+            .connector(address::connector(config))
+            .rustls(Arc::new(tls_config))
+            .finish();
+
+        let client = Client::builder()
+            .connector(connector)
+            .timeout(Duration::from_secs(delivery::REQUEST_TIMEOUT))
+            .finish();
+
+        WebhookServer {
+            pool,
+            client: Rc::new(client),
+            slots_checked_at: Utc::now().naive_utc(),
+        }
+    }
+
+    fn deliver_due(&mut self, ctx: &mut <Self as Actor>::Context) {
+        let conn = self.pool.get().unwrap();
+
+        async move {
+            web::block(move || delivery::claim_due(&conn, Utc::now().naive_utc()))
+                .await
+        }
+            .into_actor(self)
+            .then(|res, act, ctx| {
+                match res {
+                    Ok(deliveries) => for (delivery, webhook) in deliveries {
+                        Self::deliver(act.pool.clone(), act.client.clone(), delivery, webhook)
+                            .into_actor(act)
+                            .spawn(ctx);
+                    },
+                    Err(e) => error!("Error while claiming webhook deliveries, {:?}", e)
+                }
+
+                fut::ready(())
+            })
+            .spawn(ctx);
+    }
+
+    fn check_slots(&mut self, ctx: &mut <Self as Actor>::Context) {
+        let conn = self.pool.get().unwrap();
+        let from = self.slots_checked_at;
+        let to = Utc::now().naive_utc();
+
+        self.slots_checked_at = to;
+
+        async move {
+            web::block(move || -> Result<(), diesel::result::Error> {
+                for (user_id, kind, data) in delivery::slot_events(&conn, from, to)? {
+                    delivery::enqueue(&conn, user_id, kind, &data)?;
+                }
+
+                Ok(())
+            })
+                .await
+        }
+            .into_actor(self)
+            .then(|res, act, ctx| {
+                match res {
+                    Ok(()) => act.deliver_due(ctx),
+                    Err(e) => error!("Error while creating slot events, {:?}", e)
+                }
+
+                fut::ready(())
+            })
+            .spawn(ctx);
+    }
+
+    async fn deliver(pool: DBPool, client: Rc<Client>, delivery: WebhookDelivery, webhook: Webhook) {
+        let body = serde_json::to_string(&DeliveryBody::new(&delivery)).unwrap();
+        let timestamp = Utc::now().timestamp();
+
+        let res = client
+            .post(webhook.url.as_str())
+            .content_type("application/json")
+            .header(delivery::SIGNATURE_HEADER, format!("sha256={}", delivery::sign(webhook.secret.as_str(), timestamp, body.as_str())))
+            .header(delivery::TIMESTAMP_HEADER, timestamp.to_string())
+            .header(delivery::EVENT_HEADER, delivery.event.value())
+            .header(delivery::DELIVERY_HEADER, delivery.id.to_string())
+            .send_body(body)
+            .await;
+
+        let attempt = match res {
+            // bodies of the responses are not read, they could reveal what the receiver is not meant to expose
+            Ok(response) => Attempt {
+                response_status: Some(response.status().as_u16() as i32),
+                error: None,
+            },
+            Err(e) => Attempt {
+                response_status: None,
+                error: Some(e.to_string()),
+            }
+        };
+
+        if attempt.is_successful() {
+            info!("webhook delivery {} is delivered to webhook {}", delivery.id, webhook.id);
+        } else {
+            warn!("webhook delivery {} to webhook {} failed, status {:?}, error {:?}", delivery.id, webhook.id, attempt.response_status, attempt.error);
+        }
+
+        let conn = pool.get().unwrap();
+
+        let res = web::block(move || delivery::record(&conn, &delivery, attempt, Utc::now().naive_utc()))
+            .await;
+
+        if let Err(e) = res {
+            error!("Error while recording webhook delivery, {:?}", e);
+        }
+    }
+}
+
+impl Actor for WebhookServer {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        info!("WebhookServer is started!");
+
+        ctx.run_interval(Duration::from_secs(delivery::DELIVERY_INTERVAL), |act, ctx| act.deliver_due(ctx));
+        ctx.run_interval(Duration::from_secs(delivery::SLOT_CHECK_INTERVAL), |act, ctx| act.check_slots(ctx));
+    }
+
+    fn stopped(&mut self, _: &mut Self::Context) {
+        info!("WebhookServer is stopped!");
+    }
+}
+
+impl<T> Handler<Event<T>> for WebhookServer where T: Serialize + Send + 'static {
+    type Result = ();
+
+    fn handle(&mut self, msg: Event<T>, ctx: &mut Self::Context) {
+        let conn = self.pool.get().unwrap();
+        let data = serde_json::to_value(&msg.data).unwrap();
+
+        async move {
+            web::block(move || delivery::enqueue(&conn, msg.user_id, msg.kind, &data))
+                .await
+        }
+            .into_actor(self)
+            .then(|res, act, ctx| {
+                match res {
+                    Ok(0) => {}
+                    Ok(_) => act.deliver_due(ctx),
+                    Err(e) => error!("Error while creating webhook deliveries, {:?}", e)
+                }
+
+                fut::ready(())
+            })
+            .spawn(ctx);
+    }
+}
+
+impl Handler<DeliverDue> for WebhookServer {
+    type Result = ();
+
+    fn handle(&mut self, _: DeliverDue, ctx: &mut Self::Context) {
+        self.deliver_due(ctx);
+    }
+}
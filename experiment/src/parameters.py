@@ -0,0 +1,26 @@
+import json
+import os
+from typing import Any, Dict
+
+PARAMETERS_FILE_ENV = 'TESTBED_PARAMETERS_FILE'
+
+__PARAMETERS = None
+
+
+def load() -> Dict[str, Any]:
+    global __PARAMETERS
+
+    if __PARAMETERS is None:
+        path = os.environ.get(PARAMETERS_FILE_ENV)
+
+        if path is not None and os.path.exists(path):
+            with open(path) as f:
+                __PARAMETERS = json.load(f)
+        else:
+            __PARAMETERS = {}
+
+    return __PARAMETERS
+
+
+def get(name: str, default: Any = None) -> Any:
+    return load().get(name, default)
@@ -22,6 +22,10 @@ export interface SlimJob {
   status: JobStatus;
   createdAt: Date;
   updatedAt: Date;
+  startedAt: Date | null;
+  finishedAt: Date | null;
+  failureKind: string | null;
+  failureCause: FailureCause | null;
 }
 
 export interface Job extends SlimJob {
@@ -38,5 +42,14 @@ export enum JobStatus {
   Pending = 'Pending',
   Running = 'Running',
   Successful = 'Successful',
-  Failed = 'Failed'
+  Failed = 'Failed',
+  Aborted = 'Aborted',
+  TimedOut = 'TimedOut',
+  Cancelled = 'Cancelled'
+}
+
+export enum FailureCause {
+  User = 'User',
+  Abort = 'Abort',
+  Internal = 'Internal'
 }
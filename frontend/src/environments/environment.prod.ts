@@ -1,5 +1,5 @@
 export const environment = {
   production: true,
-  wsEndpoint: 'wss://stapiac.westeurope.cloudapp.azure.com/api/ws',
-  apiEndpoint: 'https://stapiac.westeurope.cloudapp.azure.com/api'
+  wsEndpoint: 'wss://stapiac.westeurope.cloudapp.azure.com/api/v1/ws',
+  apiEndpoint: 'https://stapiac.westeurope.cloudapp.azure.com/api/v1'
 };
@@ -4,8 +4,8 @@
 
 export const environment = {
   production: false,
-  wsEndpoint: 'ws://127.0.0.1:8040/api/ws',
-  apiEndpoint: 'http://127.0.0.1:8040/api'
+  wsEndpoint: 'ws://127.0.0.1:8040/api/v1/ws',
+  apiEndpoint: 'http://127.0.0.1:8040/api/v1'
 };
 
 /*
@@ -0,0 +1,29 @@
+import sys
+from receiver import Receiver
+from transmitter import State, WordEncoder, Spray
+
+
+def run_transmitter():
+    spray_duration = 20  # milliseconds
+    pause_duration = 25  # milliseconds
+
+    state = State(WordEncoder())
+    for i in range(0, 25):
+        state.emit([Spray.Spray_1], spray_duration * i)
+        state.wait(pause_duration)
+    state.execute()
+
+
+def run_receiver(device_paths):
+    receiver = Receiver(device_paths, 5)
+    ended, rx = receiver.next()
+    while not ended:
+        print(rx)
+        ended, rx = receiver.next()
+
+
+if __name__ == '__main__':
+    if sys.argv[1] == '--receiver':
+        run_receiver(sys.argv[2:])
+    else:
+        run_transmitter()
@@ -0,0 +1,21 @@
+import sys
+from random import random
+from receiver import Receiver
+from transmitter import State, WordEncoder, Spray
+
+
+def run_transmitter():
+    state = State(WordEncoder())
+    state.wait(5000)
+    state.execute()
+
+
+def run_receiver(device_paths):
+    raise Exception
+
+
+if __name__ == '__main__':
+    if sys.argv[1] == '--receiver':
+        run_receiver(sys.argv[2:])
+    else:
+        run_transmitter()
@@ -0,0 +1,30 @@
+import sys
+import parameters
+from receiver import Receiver
+from transmitter import State, WordEncoder, Spray
+
+
+def run_transmitter():
+    spray_duration = parameters.get('spray_duration', 500)  # milliseconds
+    pause_duration = parameters.get('pause_duration', 500)  # milliseconds
+
+    state = State(WordEncoder())
+    for i in range(0, parameters.get('repeat', 25)):
+        state.emit([Spray.Spray_1], spray_duration)
+        state.wait(pause_duration)
+    state.execute()
+
+
+def run_receiver(device_paths):
+    receiver = Receiver(device_paths, 5)
+    ended, rx = receiver.next()
+    while not ended:
+        print(rx, end='')
+        ended, rx = receiver.next()
+
+
+if __name__ == '__main__':
+    if sys.argv[1] == '--receiver':
+        run_receiver(sys.argv[2:])
+    else:
+        run_transmitter()